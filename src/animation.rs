@@ -0,0 +1,181 @@
+use crate::camera::Camera;
+use crate::vec3::{Point3, Vec3};
+
+/// Values that can be interpolated between keyframes
+pub trait Animatable: Copy {
+    /// a*self + b*other
+    fn combine(&self, a: f64, other: &Self, b: f64) -> Self;
+}
+
+impl Animatable for f64 {
+    fn combine(&self, a: f64, other: &Self, b: f64) -> Self {
+        a*self + b*other
+    }
+}
+
+impl Animatable for Vec3 {
+    fn combine(&self, a: f64, other: &Self, b: f64) -> Self {
+        a*self + b*other
+    }
+}
+
+/// How values change between keyframes
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Interpolation {
+    /// Jumps to the next value at its keyframe
+    Step,
+    /// Straight lines between keyframes
+    Linear,
+    /// Catmull-Rom spline through the keyframes, for smooth motion
+    Spline,
+}
+
+/// Value that changes over time, given at keyframes
+///
+/// Before the first keyframe and after the last the value holds still.
+#[derive(Debug, Clone)]
+pub struct Keyframes<T: Animatable> {
+    keys: Vec<(f64, T)>,  // (time in seconds, value), sorted by time
+    interpolation: Interpolation,
+}
+
+impl<T: Animatable> Keyframes<T> {
+    pub fn new(interpolation: Interpolation) -> Self {
+        Keyframes {keys: vec![], interpolation}
+    }
+
+    /// Value that never changes
+    pub fn constant(value: T) -> Self {
+        Keyframes::new(Interpolation::Step).key(0.0, value)
+    }
+
+    /// Adds a keyframe, replacing any other at the same time
+    pub fn key(mut self, time: f64, value: T) -> Self {
+        let index = self.keys.partition_point(|(t, _)| *t < time);
+        if self.keys.get(index).is_some_and(|(t, _)| *t == time) {
+            self.keys[index].1 = value;
+        } else {
+            self.keys.insert(index, (time, value));
+        }
+        self
+    }
+
+    /// Value at time. Panics if there are no keyframes.
+    pub fn at(&self, time: f64) -> T {
+        let n = self.keys.len();
+        let next = self.keys.partition_point(|(t, _)| *t <= time);
+        if next == 0 {
+            return self.keys[0].1;
+        }
+        if next == n {
+            return self.keys[n - 1].1;
+        }
+
+        let (t1, v1) = &self.keys[next - 1];
+        let (t2, v2) = &self.keys[next];
+        let u = (time - t1) / (t2 - t1);
+        match self.interpolation {
+            Interpolation::Step => *v1,
+            Interpolation::Linear => v1.combine(1.0 - u, v2, u),
+            Interpolation::Spline => {
+                // Missing neighbours at the ends are extrapolated in a straight line
+                let v0 = if next >= 2 {self.keys[next - 2].1} else {v1.combine(2.0, v2, -1.0)};
+                let v3 = if next + 1 < n {self.keys[next + 1].1} else {v2.combine(2.0, v1, -1.0)};
+                let (u2, u3) = (u*u, u*u*u);
+                let w0 = 0.5*(-u3 + 2.0*u2 - u);
+                let w1 = 0.5*(3.0*u3 - 5.0*u2 + 2.0);
+                let w2 = 0.5*(-3.0*u3 + 4.0*u2 + u);
+                let w3 = 0.5*(u3 - u2);
+                v0.combine(w0, v1, w1).combine(1.0, v2, w2).combine(1.0, &v3, w3)
+            },
+        }
+    }
+}
+
+/// Keyframed settings of a perspective camera
+#[derive(Debug, Clone)]
+pub struct CameraKeyframes {
+    pub lookfrom: Keyframes<Point3>,
+    pub lookat: Keyframes<Point3>,
+    pub vfov: Keyframes<f64>,
+    pub focus_distance: Keyframes<f64>,
+    pub roll: Keyframes<f64>,  // In degrees, counter-clockwise about the view direction
+    pub aperture_width: f64,
+}
+
+impl CameraKeyframes {
+    /// Camera at time, rendering images of aspect_ratio
+    pub fn camera(&self, time: f64, aspect_ratio: f64) -> Camera {
+        Camera::new(
+            self.lookfrom.at(time),
+            self.lookat.at(time),
+            Vec3::new(0.0, 1.0, 0.0),
+            self.vfov.at(time),
+            aspect_ratio,
+            self.aperture_width,
+            self.focus_distance.at(time),
+        ).roll(self.roll.at(time))
+    }
+}
+
+/// Frames of an animated scene
+///
+/// The world is built once and animated objects follow the time of the
+/// rays that hit them, so nothing is rebuilt between frames. Only the
+/// camera is set up again for every frame.
+pub struct Timeline {
+    pub fps: f64,
+    pub first_frame: u32,
+    pub last_frame: u32,
+    pub shutter: f64,  // Fraction of a frame the shutter is open for, for motion blur
+    camera: Box<dyn Fn(f64) -> Camera>,
+}
+
+impl Timeline {
+    /// Frames first_frame to last_frame (inclusive) at fps, with the
+    /// camera at every time given by camera
+    pub fn new<F: Fn(f64) -> Camera + 'static>(fps: f64, first_frame: u32, last_frame: u32, camera: F) -> Self {
+        Timeline {fps, first_frame, last_frame, shutter: 0.0, camera: Box::new(camera)}
+    }
+
+    /// Keeps the shutter open for fraction of every frame (0.5 is the
+    /// classic 180 degree shutter), blurring things that move
+    pub fn shutter(mut self, fraction: f64) -> Self {
+        self.shutter = fraction.clamp(0.0, 1.0);
+        self
+    }
+
+    /// Time in seconds at which frame starts
+    pub fn time(&self, frame: u32) -> f64 {
+        frame as f64 / self.fps
+    }
+
+    pub fn camera(&self, time: f64) -> Camera {
+        (self.camera)(time)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use approx::assert_relative_eq;
+    use super::*;
+
+    #[test]
+    fn test_keyframes() {
+        for interpolation in [Interpolation::Linear, Interpolation::Spline] {
+            let keys = Keyframes::new(interpolation).key(2.0, 4.0).key(0.0, 0.0).key(1.0, 2.0);
+
+            // Keyframes are hit exactly, and held before the first and after the last
+            for (time, expected) in [(-1.0, 0.0), (0.0, 0.0), (1.0, 2.0), (2.0, 4.0), (3.0, 4.0)] {
+                assert_relative_eq!(keys.at(time), expected, epsilon = 1e-12);
+            }
+
+            // A straight line stays straight
+            assert_relative_eq!(keys.at(0.5), 1.0, epsilon = 1e-12);
+        }
+
+        let steps = Keyframes::new(Interpolation::Step).key(0.0, 1.0).key(1.0, 2.0);
+        assert_eq!(steps.at(0.99), 1.0);
+        assert_eq!(steps.at(1.0), 2.0);
+    }
+}
@@ -0,0 +1,357 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use exr::meta::attribute::Chromaticities;
+use exr::prelude::{AnyChannel, AnyChannels, Encoding, FlatSamples, Image, Layer, LayerAttributes, SmallVec, Vec2, WritableImage};
+use image::{ImageResult, Rgb, RgbImage};
+
+use crate::color::{Color, ColorSpace, Encoding as ColorEncoding, into_pixel};
+use crate::film::Film;
+use crate::integrator::PathSample;
+use crate::vec3::Vec3;
+
+/// Arbitrary output variables: render passes written next to the beauty image
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Aov {
+    Depth,
+    Normal,
+    Albedo,
+    MaterialId,
+    ObjectId,
+    Direct,
+    Indirect,
+    Emission,
+    SampleCount,
+}
+
+impl Aov {
+    pub const ALL: [Aov; 9] = [
+        Aov::Depth,
+        Aov::Normal,
+        Aov::Albedo,
+        Aov::MaterialId,
+        Aov::ObjectId,
+        Aov::Direct,
+        Aov::Indirect,
+        Aov::Emission,
+        Aov::SampleCount,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Aov::Depth => "depth",
+            Aov::Normal => "normal",
+            Aov::Albedo => "albedo",
+            Aov::MaterialId => "material_id",
+            Aov::ObjectId => "object_id",
+            Aov::Direct => "direct",
+            Aov::Indirect => "indirect",
+            Aov::Emission => "emission",
+            Aov::SampleCount => "sample_count",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Aov> {
+        Aov::ALL.iter().copied().find(|aov| aov.name() == name)
+    }
+
+    /// Names of the channels the pass is made of
+    fn channel_names(&self) -> &'static [&'static str] {
+        match self {
+            Aov::Depth => &["Z"],
+            Aov::Normal => &["X", "Y", "Z"],
+            Aov::MaterialId | Aov::ObjectId => &["id"],
+            Aov::SampleCount => &["count"],
+            Aov::Albedo | Aov::Direct | Aov::Indirect | Aov::Emission => &["R", "G", "B"],
+        }
+    }
+}
+
+/// Per-pixel buffers for every AOV
+///
+/// Depth, normal and albedo are averaged over the samples that hit something,
+/// the lighting passes over all samples. Ids are taken from the first sample
+/// of each pixel that hits something and numbered from 1 in the order they are
+/// first seen, 0 being the background.
+pub struct AovBuffers {
+    width: u32,
+    height: u32,
+    samples: Vec<u32>,
+    hits: Vec<u32>,
+    depth: Vec<f64>,
+    normal: Vec<Vec3>,
+    albedo: Vec<Color>,
+    direct: Vec<Color>,
+    indirect: Vec<Color>,
+    emission: Vec<Color>,
+    material_id: Vec<u32>,
+    object_id: Vec<u32>,
+    material_ids: HashMap<usize, u32>,
+    object_ids: HashMap<usize, u32>,
+}
+
+impl AovBuffers {
+    pub fn new(width: u32, height: u32) -> Self {
+        let n_pixels = (width as usize)*(height as usize);
+        let black = Color::new(0.0, 0.0, 0.0);
+        AovBuffers {
+            width,
+            height,
+            samples: vec![0; n_pixels],
+            hits: vec![0; n_pixels],
+            depth: vec![0.0; n_pixels],
+            normal: vec![Vec3::new(0.0, 0.0, 0.0); n_pixels],
+            albedo: vec![black; n_pixels],
+            direct: vec![black; n_pixels],
+            indirect: vec![black; n_pixels],
+            emission: vec![black; n_pixels],
+            material_id: vec![0; n_pixels],
+            object_id: vec![0; n_pixels],
+            material_ids: HashMap::new(),
+            object_ids: HashMap::new(),
+        }
+    }
+
+    pub fn width(&self) -> u32 {self.width}
+    pub fn height(&self) -> u32 {self.height}
+
+    fn index(&self, x: u32, y: u32) -> usize {
+        (y as usize)*(self.width as usize) + (x as usize)
+    }
+
+    pub fn add_sample(&mut self, x: u32, y: u32, sample: &PathSample) {
+        let i = self.index(x, y);
+        self.samples[i] += 1;
+        self.direct[i] += sample.direct;
+        self.indirect[i] += sample.indirect;
+        self.emission[i] += sample.emission;
+
+        if let Some(first_hit) = &sample.first_hit {
+            self.hits[i] += 1;
+            self.depth[i] += first_hit.distance;
+            self.normal[i] += first_hit.normal;
+            self.albedo[i] += first_hit.albedo;
+
+            // Hits are numbered from 1, so 0 means no sample has hit anything yet
+            if self.material_id[i] == 0 {
+                let next_id = self.material_ids.len() as u32 + 1;
+                self.material_id[i] = *self.material_ids.entry(first_hit.material_id).or_insert(next_id);
+                let next_id = self.object_ids.len() as u32 + 1;
+                self.object_id[i] = *self.object_ids.entry(first_hit.object_id).or_insert(next_id);
+            }
+        }
+    }
+
+    /// Number of samples gathered for pixel (x, y)
+    pub fn samples_at(&self, x: u32, y: u32) -> u32 {
+        self.samples[self.index(x, y)]
+    }
+
+    /// Mean depth of pixel (x, y), infinite if nothing was hit
+    pub fn depth(&self, x: u32, y: u32) -> f64 {
+        let i = self.index(x, y);
+        if self.hits[i] == 0 {f64::INFINITY} else {self.depth[i] / (self.hits[i] as f64)}
+    }
+
+    /// Mean first-hit normal of pixel (x, y), zero if nothing was hit
+    pub fn normal(&self, x: u32, y: u32) -> Vec3 {
+        let i = self.index(x, y);
+        let n = self.normal[i];
+        if n.is_near_zero() {n} else {n.unit_vector()}
+    }
+
+    /// Mean first-hit albedo of pixel (x, y)
+    pub fn albedo(&self, x: u32, y: u32) -> Color {
+        let i = self.index(x, y);
+        if self.hits[i] == 0 {Color::new(0.0, 0.0, 0.0)} else {self.albedo[i] / (self.hits[i] as f64)}
+    }
+
+    fn mean(&self, buffer: &[Color], i: usize) -> Color {
+        if self.samples[i] == 0 {Color::new(0.0, 0.0, 0.0)} else {buffer[i] / (self.samples[i] as f64)}
+    }
+
+    /// Values of every channel of aov for pixel (x, y)
+    fn values(&self, aov: Aov, film: &Film, x: u32, y: u32) -> [f64; 3] {
+        let i = self.index(x, y);
+        let rgb = |c: Color| [c.r(), c.g(), c.b()];
+        match aov {
+            Aov::Depth => [self.depth(x, y), 0.0, 0.0],
+            Aov::Normal => {
+                let n = self.normal(x, y);
+                [n.x(), n.y(), n.z()]
+            },
+            Aov::Albedo => rgb(self.albedo(x, y)),
+            Aov::MaterialId => [self.material_id[i] as f64, 0.0, 0.0],
+            Aov::ObjectId => [self.object_id[i] as f64, 0.0, 0.0],
+            Aov::Direct => rgb(self.mean(&self.direct, i)),
+            Aov::Indirect => rgb(self.mean(&self.indirect, i)),
+            Aov::Emission => rgb(self.mean(&self.emission, i)),
+            Aov::SampleCount => [film.samples_at(x, y) as f64, 0.0, 0.0],
+        }
+    }
+
+    /// Renders aov into an 8-bit image for viewing
+    ///
+    /// Depth is normalized so the nearest point is white, normals are mapped
+    /// from [-1, 1] to [0, 1], ids get a distinct color each and color passes
+    /// are sRGB encoded.
+    pub fn to_image(&self, aov: Aov, film: &Film) -> RgbImage {
+        let max_depth = (0..self.height)
+            .flat_map(|y| (0..self.width).map(move |x| (x, y)))
+            .map(|(x, y)| self.depth(x, y))
+            .filter(|d| d.is_finite())
+            .fold(0.0, f64::max);
+        let max_count = film.samples().iter().copied().max().unwrap_or(0).max(1) as f64;
+
+        let mut img = RgbImage::new(self.width, self.height);
+        for y in 0..self.height {
+            for x in 0..self.width {
+                let v = self.values(aov, film, x, y);
+                let pixel = match aov {
+                    Aov::Depth => {
+                        let d = if v[0].is_finite() && max_depth > 0.0 {1.0 - v[0]/max_depth} else {0.0};
+                        into_pixel(&Color::new(d, d, d))
+                    },
+                    Aov::Normal => into_pixel(&(0.5*(Color::new(v[0], v[1], v[2]) + 1.0))),
+                    Aov::MaterialId | Aov::ObjectId => id_color(v[0] as u32),
+                    Aov::SampleCount => {
+                        let c = v[0] / max_count;
+                        into_pixel(&Color::new(c, c, c))
+                    },
+                    Aov::Albedo | Aov::Direct | Aov::Indirect | Aov::Emission => {
+                        into_pixel(&ColorEncoding::SRGB.encode(&Color::new(v[0], v[1], v[2])))
+                    },
+                };
+                img.put_pixel(x, y, pixel);
+            }
+        }
+        img
+    }
+
+    /// Writes every pass in aovs as its own image next to path
+    ///
+    /// eg. passes.png becomes passes.depth.png, passes.normal.png, ...
+    pub fn save_images<P: AsRef<Path>>(&self, path: P, film: &Film, aovs: &[Aov]) -> ImageResult<()> {
+        for aov in aovs {
+            self.to_image(*aov, film).save(pass_path(path.as_ref(), aov.name()))?;
+        }
+        Ok(())
+    }
+
+    /// Writes the beauty pass and every pass in aovs as layers of a single
+    /// multi-layer OpenEXR file
+    ///
+    /// Layers use the "layer.channel" naming convention (eg. normal.X) which
+    /// compositing packages group into layers. Values are linear and unclamped,
+    /// with the working space primaries recorded in the chromaticities attribute.
+    pub fn save_exr<P: AsRef<Path>>(&self, path: P, film: &Film, aovs: &[Aov]) -> exr::error::UnitResult {
+        let pixels = || (0..self.height).flat_map(|y| (0..self.width).map(move |x| (x, y)));
+        let mut channels: Vec<AnyChannel<FlatSamples>> = vec![];
+
+        for (c, name) in ["R", "G", "B"].iter().enumerate() {
+            let samples: Vec<f32> = pixels().map(|(x, y)| film.pixel(x, y)[c] as f32).collect();
+            channels.push(AnyChannel::new(*name, FlatSamples::F32(samples)));
+        }
+
+        for aov in aovs {
+            for (c, channel_name) in aov.channel_names().iter().enumerate() {
+                let name = format!("{}.{}", aov.name(), channel_name);
+                let values = pixels().map(|(x, y)| self.values(*aov, film, x, y)[c]);
+                let samples = match aov {
+                    Aov::MaterialId | Aov::ObjectId | Aov::SampleCount => FlatSamples::U32(values.map(|v| v as u32).collect()),
+                    _ => FlatSamples::F32(values.map(|v| v as f32).collect()),
+                };
+                channels.push(AnyChannel::new(name.as_str(), samples));
+            }
+        }
+
+        let layer = Layer::new(
+            Vec2(self.width as usize, self.height as usize),
+            LayerAttributes::default(),
+            Encoding::FAST_LOSSLESS,
+            AnyChannels::sort(SmallVec::from_vec(channels)),
+        );
+        let [r, g, b, white] = ColorSpace::Srgb.chromaticities();
+        let xy = |(x, y): (f64, f64)| Vec2(x as f32, y as f32);
+        let mut image = Image::from_layer(layer);
+        image.attributes.chromaticities = Some(Chromaticities {red: xy(r), green: xy(g), blue: xy(b), white: xy(white)});
+        image.write().to_file(path)
+    }
+}
+
+/// Path of a single pass image, eg. out.png -> out.depth.png
+fn pass_path(path: &Path, pass_name: &str) -> PathBuf {
+    let stem = path.file_stem().map(|s| s.to_string_lossy().into_owned()).unwrap_or_default();
+    let file_name = match path.extension() {
+        Some(ext) => format!("{}.{}.{}", stem, pass_name, ext.to_string_lossy()),
+        None => format!("{}.{}", stem, pass_name),
+    };
+    path.with_file_name(file_name)
+}
+
+/// Distinct color for an id, background (0) is black
+fn id_color(id: u32) -> Rgb<u8> {
+    if id == 0 {
+        return Rgb([0, 0, 0]);
+    }
+
+    // Integer hash so neighbouring ids get unrelated colors
+    let mut h = id.wrapping_mul(0x9e3779b1);
+    h ^= h >> 16;
+    h = h.wrapping_mul(0x85ebca6b);
+    h ^= h >> 13;
+    Rgb([(h >> 16) as u8 | 0x20, (h >> 8) as u8 | 0x20, h as u8 | 0x20])
+}
+
+#[cfg(test)]
+mod tests {
+    use std::rc::Rc;
+
+    use super::*;
+    use crate::hittable::Hittable;
+    use crate::hittable::sphere::Sphere;
+    use crate::integrator::{trace, FirstHit};
+    use crate::material::{fresnel, Conductor, Material};
+    use crate::ray::Ray;
+    use crate::vec3::Point3;
+
+    fn hit(material_id: usize, object_id: usize) -> PathSample {
+        PathSample {
+            first_hit: Some(FirstHit {
+                distance: 1.0,
+                normal: Vec3::new(0.0, 0.0, 1.0),
+                albedo: Color::new(0.5, 0.5, 0.5),
+                material_id,
+                object_id,
+            }),
+            ..PathSample::default()
+        }
+    }
+
+    #[test]
+    fn test_ids() {
+        // A pixel whose first sample misses still gets the id of a later hit
+        let mut aovs = AovBuffers::new(2, 1);
+        aovs.add_sample(0, 0, &PathSample::default());
+        aovs.add_sample(0, 0, &hit(0xa0, 0xb0));
+        aovs.add_sample(0, 0, &hit(0xa1, 0xb1));
+        aovs.add_sample(1, 0, &hit(0xa1, 0xb0));
+        assert_eq!(aovs.material_id, vec![1, 2]);
+        assert_eq!(aovs.object_id, vec![1, 1]);
+    }
+
+    #[test]
+    fn test_albedo_is_reflectance() {
+        // The albedo of a rough conductor is its reflectance at normal
+        // incidence, not the weight of whichever direction was sampled
+        let gold = Conductor::gold(0.8);
+        let expected = fresnel::conductor_rgb(1.0, &Color::new(0.143, 0.374, 1.442), &Color::new(3.983, 2.385, 1.603));
+        let material: Rc<dyn Material> = Rc::new(gold);
+        let world: Rc<dyn Hittable> = Rc::new(Sphere::new(Point3::new(0.0, 0.0, 0.0), 1.0, &material));
+        let r = Ray::new(Point3::new(0.0, 0.0, 5.0), Vec3::new(0.1, 0.0, -1.0));
+        for _ in 0..10 {
+            let albedo = trace(&r, &world, 2).first_hit.unwrap().albedo;
+            (0..3).for_each(|i| assert_eq!(albedo[i], expected[i]));
+        }
+    }
+}
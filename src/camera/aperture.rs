@@ -0,0 +1,143 @@
+use std::fmt;
+use std::path::Path;
+
+use image::{GrayImage, ImageResult};
+
+use crate::PI;
+use crate::checkpoint::Fnv64;
+use crate::random_f64;
+use crate::vec3::Vec3;
+
+/// Shape of the opening in a lens, which is the shape out of focus
+/// highlights (bokeh) take
+///
+/// Shapes are sampled in lens coordinates scaled so the aperture fits in
+/// the unit disc.
+#[derive(Debug)]
+pub enum Aperture {
+    Circle,
+    /// Opening between straight blades, a regular polygon with a vertex at
+    /// rotation degrees counterclockwise from the +u axis
+    Polygon {blades: u32, rotation: f64},
+    /// Opening drawn as a grayscale image stretched over the unit disc's
+    /// bounding square, brighter pixels letting more light through
+    Image(ApertureImage),
+}
+
+impl Aperture {
+    pub fn polygon(blades: u32, rotation: f64) -> Self {
+        Aperture::Polygon {blades: blades.max(3), rotation}
+    }
+
+    /// Loads an aperture shape from an image file
+    pub fn open<P: AsRef<Path>>(path: P) -> ImageResult<Self> {
+        Ok(Self::from_image(&image::open(path)?.to_luma8()))
+    }
+
+    /// Aperture shaped like img. An image that is all black gives a circle.
+    pub fn from_image(img: &GrayImage) -> Self {
+        match ApertureImage::new(img) {
+            Some(image) => Aperture::Image(image),
+            None => Aperture::Circle,
+        }
+    }
+
+    /// Random point on the aperture, with z = 0
+    pub fn sample(&self) -> Vec3 {
+        match self {
+            Aperture::Circle => Vec3::random_in_unit_disc(),
+            Aperture::Polygon {blades, rotation} => {
+                // Pick one of the triangles between the centre and two
+                // adjacent vertices, then a uniform point in it
+                let step = 2.0*PI / *blades as f64;
+                let start = rotation.to_radians() + step*(random_f64()*(*blades as f64)).floor();
+                let (a, b) = (Vec3::new(start.cos(), start.sin(), 0.0), Vec3::new((start + step).cos(), (start + step).sin(), 0.0));
+                let (mut s, mut t) = (random_f64(), random_f64());
+                if s + t > 1.0 {
+                    (s, t) = (1.0 - s, 1.0 - t);
+                }
+                s*a + t*b
+            },
+            Aperture::Image(image) => image.sample(),
+        }
+    }
+}
+
+/// Aperture image prepared for sampling pixels in proportion to their
+/// brightness
+pub struct ApertureImage {
+    width: u32,
+    height: u32,
+    cdf: Vec<f64>,  // Running sum of pixel weights, normalized to end at 1
+}
+
+impl ApertureImage {
+    /// None if the image has no light getting through
+    fn new(img: &GrayImage) -> Option<Self> {
+        let mut total = 0.0;
+        let mut cdf: Vec<f64> = img.pixels().map(|p| {
+            total += p.0[0] as f64;
+            total
+        }).collect();
+        if total <= 0.0 {
+            return None;
+        }
+        cdf.iter_mut().for_each(|c| *c /= total);
+        Some(ApertureImage {width: img.width(), height: img.height(), cdf})
+    }
+
+    fn sample(&self) -> Vec3 {
+        let u = random_f64();
+        let index = self.cdf.partition_point(|c| *c <= u).min(self.cdf.len() - 1);
+        let (x, y) = ((index as u32 % self.width) as f64, (index as u32 / self.width) as f64);
+
+        // Image rows go down while v goes up
+        Vec3::new(
+            2.0*(x + random_f64()) / self.width as f64 - 1.0,
+            1.0 - 2.0*(y + random_f64()) / self.height as f64,
+            0.0,
+        )
+    }
+}
+
+// The camera is fingerprinted through its Debug output, so this stands in
+// for the pixels with a hash of them
+impl fmt::Debug for ApertureImage {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut hasher = Fnv64::new();
+        self.cdf.iter().for_each(|c| hasher.write_f64(*c));
+        f.debug_struct("ApertureImage")
+            .field("width", &self.width)
+            .field("height", &self.height)
+            .field("hash", &hasher.finish())
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use image::Luma;
+    use super::*;
+
+    #[test]
+    fn test_polygon_sample_inside() {
+        // Every point of a square with vertices on the axes has |x| + |y| <= 1
+        let square = Aperture::polygon(4, 0.0);
+        for _ in 0..1000 {
+            let p = square.sample();
+            assert!(p.x().abs() + p.y().abs() <= 1.0 + 1e-12);
+        }
+    }
+
+    #[test]
+    fn test_image_sample_follows_pixels() {
+        // Only the top right pixel of a 2x2 image lets light through
+        let img = GrayImage::from_fn(2, 2, |x, y| Luma([if x == 1 && y == 0 {255} else {0}]));
+        let aperture = Aperture::from_image(&img);
+        for _ in 0..1000 {
+            let p = aperture.sample();
+            assert!((0.0..=1.0).contains(&p.x()) && (0.0..=1.0).contains(&p.y()));
+        }
+        assert!(matches!(Aperture::from_image(&GrayImage::new(2, 2)), Aperture::Circle));
+    }
+}
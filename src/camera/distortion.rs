@@ -0,0 +1,86 @@
+/// Brown-Conrady model of how a lens bends straight lines, with radial
+/// terms k1, k2, k3 and tangential (decentering) terms p1, p2
+///
+/// Works on image coordinates centred on the image and scaled so its half
+/// height is 1. Coefficients follow the usual convention of mapping
+/// undistorted points to distorted ones, so a negative k1 gives barrel
+/// distortion and a positive one pincushion.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct LensDistortion {
+    pub k1: f64,
+    pub k2: f64,
+    pub k3: f64,
+    pub p1: f64,
+    pub p2: f64,
+}
+
+impl LensDistortion {
+    /// Purely radial distortion
+    pub fn radial(k1: f64, k2: f64, k3: f64) -> Self {
+        LensDistortion {k1, k2, k3, p1: 0.0, p2: 0.0}
+    }
+
+    /// Adds tangential distortion, from a lens not quite centred on the sensor
+    pub fn tangential(mut self, p1: f64, p2: f64) -> Self {
+        self.p1 = p1;
+        self.p2 = p2;
+        self
+    }
+
+    /// Where the lens moves the undistorted point (x, y)
+    pub fn distort(&self, x: f64, y: f64) -> (f64, f64) {
+        let r2 = x*x + y*y;
+        let radial = 1.0 + r2*(self.k1 + r2*(self.k2 + r2*self.k3));
+        let (dx, dy) = self.tangential_offset(x, y, r2);
+        (x*radial + dx, y*radial + dy)
+    }
+
+    /// Undistorted point the lens moves to (x, y), found by fixed point
+    /// iteration, which converges for distortions of a realistic size
+    pub fn undistort(&self, x: f64, y: f64) -> (f64, f64) {
+        const MAX_ITERATIONS: u32 = 100;
+        const TOLERANCE: f64 = 1e-12;
+
+        let (mut ux, mut uy) = (x, y);
+        for _ in 0..MAX_ITERATIONS {
+            let r2 = ux*ux + uy*uy;
+            let radial = 1.0 + r2*(self.k1 + r2*(self.k2 + r2*self.k3));
+            let (dx, dy) = self.tangential_offset(ux, uy, r2);
+            let (next_x, next_y) = ((x - dx) / radial, (y - dy) / radial);
+            let converged = (next_x - ux).abs() < TOLERANCE && (next_y - uy).abs() < TOLERANCE;
+            (ux, uy) = (next_x, next_y);
+            if converged {
+                break;
+            }
+        }
+        (ux, uy)
+    }
+
+    fn tangential_offset(&self, x: f64, y: f64, r2: f64) -> (f64, f64) {
+        (
+            2.0*self.p1*x*y + self.p2*(r2 + 2.0*x*x),
+            self.p1*(r2 + 2.0*y*y) + 2.0*self.p2*x*y,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use approx::assert_relative_eq;
+    use super::*;
+
+    #[test]
+    fn test_lens_distortion() {
+        let distortion = LensDistortion::radial(-0.1, 0.02, 0.0).tangential(0.005, -0.003);
+        for (x, y) in [(0.0, 0.0), (0.5, 0.25), (-1.2, 0.9), (1.7, -1.0)] {
+            let (dx, dy) = distortion.distort(x, y);
+            let (ux, uy) = distortion.undistort(dx, dy);
+            assert_relative_eq!(ux, x, epsilon = 1e-9);
+            assert_relative_eq!(uy, y, epsilon = 1e-9);
+        }
+
+        // Barrel distortion pulls the edges in
+        let (x, _) = LensDistortion::radial(-0.1, 0.0, 0.0).distort(1.0, 0.0);
+        assert_relative_eq!(x, 0.9);
+    }
+}
@@ -0,0 +1,245 @@
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use crate::{invalid_data, parse_number};
+use crate::vec3::{Point3, Vec3};
+
+/// One surface of a lens system
+#[derive(Debug, Clone, Copy)]
+pub struct LensSurface {
+    pub radius: f64,     // Radius of curvature in mm, positive when convex towards the scene, 0 for the aperture stop
+    pub thickness: f64,  // Distance along the axis to the next surface (or the film) in mm
+    pub ior: f64,        // Index of refraction of the glass behind the surface, 1 for air
+    pub aperture: f64,   // Diameter of the surface in mm
+}
+
+/// Lens made of a sequence of spherical surfaces, traced exactly rather
+/// than approximated by a thin lens, so it has the distortion, vignetting
+/// and focus breathing of the real design
+///
+/// Coordinates inside the lens are in mm along the optical axis z, with the
+/// film at z = 0 and the scene towards +z.
+#[derive(Debug, Clone)]
+pub struct LensSystem {
+    surfaces: Vec<LensSurface>,  // From the front of the lens to the back
+    mm_per_unit: f64,
+    pupil_radius: f64,  // Radius of the rear surface that light from the film centre gets through
+}
+
+impl LensSystem {
+    /// Lens from its surfaces, front to back. The thickness of the last
+    /// surface is the distance to the film, which focusing overrides.
+    pub fn new(surfaces: Vec<LensSurface>) -> Self {
+        LensSystem {surfaces, mm_per_unit: 1000.0, pupil_radius: 0.0}
+    }
+
+    /// Loads a lens prescription
+    ///
+    /// Every line that isn't blank or a # comment describes one surface,
+    /// front to back, as "radius thickness ior aperture" in mm, in the
+    /// style of the lens files of pbrt. An ior of 0 is air, like 1.
+    pub fn open<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        Self::parse(&fs::read_to_string(path)?)
+    }
+
+    pub fn parse(text: &str) -> io::Result<Self> {
+        let mut surfaces = vec![];
+        for line in text.lines().map(|line| line.trim()).filter(|line| !line.is_empty() && !line.starts_with('#')) {
+            let values = line.split_whitespace()
+                .map(parse_number)
+                .collect::<io::Result<Vec<f64>>>()?;
+            if values.len() != 4 {
+                return Err(invalid_data(&format!("expected 'radius thickness ior aperture', got '{}'", line)));
+            }
+            surfaces.push(LensSurface {
+                radius: values[0],
+                thickness: values[1],
+                ior: if values[2] == 0.0 {1.0} else {values[2]},
+                aperture: values[3],
+            });
+        }
+        if surfaces.is_empty() {
+            return Err(invalid_data("lens has no surfaces"));
+        }
+        Ok(Self::new(surfaces))
+    }
+
+    /// Sets the size of a world unit, by default a metre
+    pub fn mm_per_unit(mut self, mm_per_unit: f64) -> Self {
+        self.mm_per_unit = mm_per_unit;
+        self
+    }
+
+    pub fn surfaces(&self) -> &[LensSurface] {
+        &self.surfaces
+    }
+
+    /// Moves the film so that things distance world units in front of
+    /// the lens are in focus. Fails if the lens can't form an image there.
+    pub(super) fn focus(&mut self, distance: f64) -> io::Result<()> {
+        // Trace a ray close to the axis from a point at the focus distance
+        // and see where it crosses the axis behind the lens
+        let front = self.front_z();
+        let object = Point3::new(0.0, 0.0, front + distance*self.mm_per_unit);
+        let height = 0.01*self.surfaces[0].aperture / 2.0;
+        let dir = (Point3::new(height, 0.0, front) - object).unit_vector();
+
+        // Start just in front of the lens, sphere intersections lose
+        // precision from far away
+        let start = object + ((front + 1.0 - object.z()) / dir.z())*dir;
+        let (o, d) = self.trace(start, dir, false)
+            .ok_or_else(|| invalid_data("focusing ray doesn't get through the lens"))?;
+        if d.x() == 0.0 || d.z() >= 0.0 {
+            return Err(invalid_data("lens doesn't focus"));
+        }
+        let image_z = o.z() - o.x()*d.z() / d.x();
+
+        let last = self.surfaces.len() - 1;
+        self.surfaces[last].thickness -= image_z;
+        if self.surfaces[last].thickness <= 0.0 {
+            return Err(invalid_data("lens can't focus that close"));
+        }
+        self.pupil_radius = self.find_pupil_radius();
+        Ok(())
+    }
+
+    /// Ray leaving the lens, in lens space but in world units, for light
+    /// arriving at point (x, y) on the film (in mm). None if it's blocked.
+    pub(super) fn sample_ray(&self, x: f64, y: f64) -> Option<(Point3, Vec3)> {
+        let rear = self.surfaces.last()?;
+        let p = self.pupil_radius*Vec3::random_in_unit_disc();
+        let target = Point3::new(p.x(), p.y(), rear.thickness);
+        let film = Point3::new(x, y, 0.0);
+        let (o, d) = self.trace(film, (target - film).unit_vector(), true)?;
+        Some((o / self.mm_per_unit, d))
+    }
+
+    /// Distance from the film to the front of the lens in mm
+    fn front_z(&self) -> f64 {
+        self.surfaces.iter().map(|s| s.thickness).sum()
+    }
+
+    /// Radius of the rear surface, slightly padded, through which light
+    /// from the centre of the film gets out of the lens. Rays are aimed
+    /// within it, so off axis the exit pupil slides out of it and the
+    /// corners darken, like mechanical vignetting.
+    fn find_pupil_radius(&self) -> f64 {
+        const STEPS: u32 = 256;
+
+        let rear = self.surfaces[self.surfaces.len() - 1];
+        let max_radius = rear.aperture / 2.0;
+        let mut radius: f64 = 0.0;
+        for i in 0..=STEPS {
+            let r = max_radius*(i as f64) / (STEPS as f64);
+            let dir = Vec3::new(r, 0.0, rear.thickness).unit_vector();
+            if self.trace(Point3::new(0.0, 0.0, 0.0), dir, true).is_some() {
+                radius = r;
+            }
+        }
+        (radius + max_radius / STEPS as f64).min(max_radius)
+    }
+
+    /// Follows a ray through every surface, from the film out if
+    /// from_film, otherwise from the scene in. Returns where and in which
+    /// direction it leaves, or None if it's blocked.
+    fn trace(&self, mut o: Point3, mut d: Vec3, from_film: bool) -> Option<(Point3, Vec3)> {
+        let n = self.surfaces.len();
+
+        // Distance of every surface from the film
+        let mut vertex_z = vec![0.0; n];
+        let mut z = 0.0;
+        for i in (0..n).rev() {
+            z += self.surfaces[i].thickness;
+            vertex_z[i] = z;
+        }
+
+        let order: Box<dyn Iterator<Item = usize>> = if from_film {Box::new((0..n).rev())} else {Box::new(0..n)};
+        for i in order {
+            let surface = &self.surfaces[i];
+            let ior_front = if i == 0 {1.0} else {self.surfaces[i - 1].ior};
+            let (eta_i, eta_t) = if from_film {(surface.ior, ior_front)} else {(ior_front, surface.ior)};
+
+            let (p, normal) = if surface.radius == 0.0 {
+                // The aperture stop is a hole in a plane
+                let t = (vertex_z[i] - o.z()) / d.z();
+                if t <= 0.0 {
+                    return None;
+                }
+                (o + t*d, None)
+            } else {
+                let center = Point3::new(0.0, 0.0, vertex_z[i] - surface.radius);
+                let t = intersect_sphere(&o, &d, &center, surface.radius)?;
+                let p = o + t*d;
+                (p, Some((p - center).unit_vector()))
+            };
+
+            if p.x()*p.x() + p.y()*p.y() > surface.aperture*surface.aperture / 4.0 {
+                return None;
+            }
+            if let Some(normal) = normal {
+                let normal = if Vec3::dot(&normal, &d) > 0.0 {-normal} else {normal};
+                d = refract(&d, &normal, eta_i / eta_t)?;
+            }
+            o = p;
+        }
+
+        Some((o, d))
+    }
+}
+
+/// Nearest t at which the ray crosses the spherical cap of the given
+/// signed radius around its vertex
+fn intersect_sphere(o: &Point3, d: &Vec3, center: &Point3, radius: f64) -> Option<f64> {
+    let oc = o - center;
+    let half_b = Vec3::dot(&oc, d);
+    let c = oc.length_squared() - radius*radius;
+    let discriminant = half_b*half_b - c;
+    if discriminant < 0.0 {
+        return None;
+    }
+
+    // The cap is the half of the sphere facing the scene when the radius
+    // is positive (its centre is towards the film), otherwise the half
+    // facing the film
+    let sqrt_d = discriminant.sqrt();
+    let closer = (d.z() > 0.0) == (radius < 0.0);
+    let t = if closer {-half_b - sqrt_d} else {-half_b + sqrt_d};
+    if t > 0.0 {Some(t)} else {None}
+}
+
+/// Refracts unit vector d across unit normal n facing against it, None on
+/// total internal reflection
+fn refract(d: &Vec3, n: &Vec3, eta_ratio: f64) -> Option<Vec3> {
+    let cos_i = -Vec3::dot(d, n);
+    let sin2_t = eta_ratio*eta_ratio*(1.0 - cos_i*cos_i);
+    if sin2_t > 1.0 {
+        return None;
+    }
+    let cos_t = (1.0 - sin2_t).sqrt();
+    Some(eta_ratio*d + (eta_ratio*cos_i - cos_t)*n)
+}
+
+#[cfg(test)]
+mod tests {
+    use approx::assert_relative_eq;
+    use super::*;
+
+    #[test]
+    fn test_focus_singlet() {
+        // Thick biconvex lens, focused at infinity the film is at the back
+        // focal distance f(1 - (n - 1)t / nR) from the lensmaker's equation
+        let mut lens = LensSystem::parse("# singlet\n100.0 4.0 1.5 30.0\n-100.0 90.0 0 30.0\n").unwrap();
+        lens.focus(1e9).unwrap();
+        let back = lens.surfaces()[1].thickness;
+        let f = 1.0 / (0.5*(2.0 / 100.0 - 0.5*4.0 / (1.5*100.0*100.0)));
+        assert_relative_eq!(back, f*(1.0 - 0.5*4.0 / (1.5*100.0)), epsilon = 0.01);
+
+        // Focusing closer moves the film away from the lens
+        let mut near = lens.clone();
+        near.focus(1.0).unwrap();
+        assert!(near.surfaces()[1].thickness > back);
+
+        assert!(LensSystem::parse("51.7 4.0 1.517\n").is_err());
+    }
+}
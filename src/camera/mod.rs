@@ -0,0 +1,530 @@
+pub mod aperture;
+pub mod distortion;
+pub mod lens_system;
+pub mod orientation;
+pub mod physical;
+pub mod projection;
+
+pub use aperture::Aperture;
+pub use distortion::LensDistortion;
+pub use lens_system::{LensSurface, LensSystem};
+pub use orientation::Orientation;
+pub use physical::{Focus, PhysicalCamera};
+pub use projection::{FisheyeMapping, Projection, StereoLayout};
+
+use std::io;
+
+use projection::Eye;
+
+use crate::random_f64;
+use crate::color::Color;
+use crate::ray::Ray;
+use crate::vec3::{Point3, Vec3};
+
+/// Wavelengths in nm standing in for the red, green and blue channels
+/// when magnifying them for chromatic aberration
+const CHANNEL_WAVELENGTHS: [f64; 3] = [610.0, 550.0, 465.0];
+
+#[derive(Debug)]
+pub struct Camera {
+    pub origin: Point3,
+    lower_left_corner: Point3,
+    horizontal: Vec3,
+    vertical: Vec3,
+    u: Vec3,
+    v: Vec3,
+    w: Vec3,
+    lens_radius: f64,
+    aperture: Aperture,
+    cat_eye: f64,
+    focus_distance: f64,
+    focal_plane_normal: Option<Vec3>,  // Set when the focal plane is tilted away from the sensor
+    projection: Projection,
+    stereo: Option<(StereoLayout, f64)>,  // Layout and interocular distance of a stereo pair
+    lens_system: Option<(LensSystem, f64)>,  // Lens replacing the thin lens, and the sensor height in mm
+    exposure: f64,  // Scale from radiance to image values
+    distortion: Option<LensDistortion>,
+    chromatic_aberration: f64,  // Extra magnification of red over green
+    vignetting: f64,  // How much of the cos^4 falloff towards the edges is applied, in [0, 1]
+}
+
+impl Camera {
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(
+        lookfrom: Point3,
+        lookat: Point3,
+        vup: Vec3,
+        vfov: f64,  // Vertical field-of-view in degrees
+        aspect_ratio: f64,
+        aperture_width: f64,
+        focus_distance: f64,
+    ) -> Self {
+        let vfov = vfov.to_radians();
+        let h = (vfov/2.0).tan();
+
+        let viewport_height = 2.0*h;
+        let viewport_width = aspect_ratio*viewport_height;
+
+        let orientation = Orientation::look_at(&lookfrom, &lookat, &vup);
+        let (u, v, w) = (*orientation.right(), *orientation.up(), *orientation.back());
+
+        let origin = lookfrom;
+        let horizontal = focus_distance * viewport_width * u; // Horizontal unit vector across +u-axis in focus plane
+        let vertical = focus_distance * viewport_height * v;  // Vertical unit vector across +v-axis in focus plane
+        let lower_left_corner = origin - &horizontal/2.0 - &vertical/2.0 - w*focus_distance;
+
+        Camera {
+            origin,
+            lower_left_corner,
+            horizontal,
+            vertical,
+            u,
+            v,
+            w,
+            lens_radius: aperture_width / 2.0,
+            aperture: Aperture::Circle,
+            cat_eye: 0.0,
+            focus_distance,
+            focal_plane_normal: None,
+            projection: Projection::Perspective,
+            stereo: None,
+            lens_system: None,
+            exposure: 1.0,
+            distortion: None,
+            chromatic_aberration: 0.0,
+            vignetting: 0.0,
+        }
+    }
+
+    /// Camera set up from photographic settings, focused focus_distance
+    /// world units away (Scene::physical resolves auto-focus)
+    pub fn physical(
+        lookfrom: Point3,
+        lookat: Point3,
+        vup: Vec3,
+        aspect_ratio: f64,
+        settings: &PhysicalCamera,
+        focus_distance: f64,
+    ) -> Self {
+        Camera::new(
+            lookfrom,
+            lookat,
+            vup,
+            settings.vfov(aspect_ratio),
+            aspect_ratio,
+            settings.aperture_width(),
+            focus_distance,
+        ).exposure(settings.exposure_scale())
+    }
+
+    /// Scales the light reaching the image, 1 by default
+    pub fn exposure(mut self, exposure: f64) -> Self {
+        self.exposure = exposure;
+        self
+    }
+
+    pub fn exposure_scale(&self) -> f64 {
+        self.exposure
+    }
+
+    /// Turns the camera to face the other way, keeping its position and
+    /// everything else about it, including the focus distance
+    pub fn orientation(mut self, orientation: Orientation) -> Self {
+        let reframe = |v: &Vec3| {
+            Vec3::dot(v, &self.u)*orientation.right() + Vec3::dot(v, &self.v)*orientation.up() + Vec3::dot(v, &self.w)*orientation.back()
+        };
+        let corner = reframe(&(self.lower_left_corner - self.origin));
+        let horizontal = reframe(&self.horizontal);
+        let vertical = reframe(&self.vertical);
+        let focal_plane_normal = self.focal_plane_normal.as_ref().map(reframe);
+
+        self.lower_left_corner = self.origin + corner;
+        self.horizontal = horizontal;
+        self.vertical = vertical;
+        self.focal_plane_normal = focal_plane_normal;
+        self.u = *orientation.right();
+        self.v = *orientation.up();
+        self.w = *orientation.back();
+        self
+    }
+
+    /// Keeps the view direction but turns the camera so vup points up in
+    /// the image (or as close to it as the view direction allows)
+    pub fn up(self, vup: Vec3) -> Self {
+        let orientation = self.current_orientation(&vup);
+        self.orientation(orientation)
+    }
+
+    /// Rolls the camera counter-clockwise by degrees about the view
+    /// direction, so the scene turns clockwise in the image
+    pub fn roll(self, degrees: f64) -> Self {
+        let orientation = self.current_orientation(&self.v).roll(degrees);
+        self.orientation(orientation)
+    }
+
+    /// Orientation along the current view direction with vup up
+    fn current_orientation(&self, vup: &Vec3) -> Orientation {
+        Orientation::look_at(&self.origin, &(self.origin - self.w), vup)
+    }
+
+    /// Changes how directions are mapped onto the image. The lens settings
+    /// (aperture, focus, tilt and shift) only apply to Perspective.
+    pub fn projection(mut self, projection: Projection) -> Self {
+        self.projection = projection;
+        self
+    }
+
+    /// Renders a stereo pair into the image, with the eyes interocular
+    /// world units apart (about 0.065 for people at a scale of metres)
+    ///
+    /// Each eye keeps the vertical field of view, so its image is narrower
+    /// (side by side) or wider (top and bottom) than the whole image. Eyes
+    /// are offset across every ray rather than across the view direction,
+    /// which gives omni-directional stereo for panoramas.
+    pub fn stereo(mut self, layout: StereoLayout, interocular: f64) -> Self {
+        let centre = self.lower_left_corner + &self.horizontal/2.0 + &self.vertical/2.0;
+        let aspect_ratio = self.aspect_ratio();
+        self.horizontal *= layout.eye_aspect_ratio(aspect_ratio) / aspect_ratio;
+        self.lower_left_corner = centre - &self.horizontal/2.0 - &self.vertical/2.0;
+        self.stereo = Some((layout, interocular));
+        self
+    }
+
+    /// Images the scene through a lens system instead of a thin lens, onto
+    /// a sensor sensor_height mm tall, focused at the focus distance
+    ///
+    /// The field of view comes from the lens and the sensor, so vfov and
+    /// the other lens settings are ignored. Fails if the lens can't focus
+    /// at the focus distance.
+    pub fn lens_system(mut self, mut lens: LensSystem, sensor_height: f64) -> io::Result<Self> {
+        lens.focus(self.focus_distance)?;
+        self.lens_system = Some((lens, sensor_height));
+        Ok(self)
+    }
+
+    /// Bends straight lines like a real lens, in barrels or pincushions
+    pub fn distortion(mut self, distortion: LensDistortion) -> Self {
+        self.distortion = Some(distortion);
+        self
+    }
+
+    /// Lateral chromatic aberration, magnifying the colors by different
+    /// amounts so edges get colored fringes towards the sides of the image
+    ///
+    /// Red is magnified by 1 + amount relative to green, and other
+    /// wavelengths in proportion to their distance from green (so blue
+    /// shrinks by about 1.4 amount). Every camera ray follows one color,
+    /// which adds some color noise.
+    pub fn chromatic_aberration(mut self, amount: f64) -> Self {
+        self.chromatic_aberration = amount;
+        self
+    }
+
+    /// Darkens the image towards the edges, by strength in [0, 1] of the
+    /// natural cos^4 falloff of light reaching the sensor at an angle
+    pub fn vignetting(mut self, strength: f64) -> Self {
+        self.vignetting = strength.clamp(0.0, 1.0);
+        self
+    }
+
+    /// Changes the shape of the aperture, and so of the bokeh
+    pub fn aperture(mut self, aperture: Aperture) -> Self {
+        self.aperture = aperture;
+        self
+    }
+
+    /// Clips the aperture towards the edges of the image, like the barrel
+    /// of a real lens does, so bokeh turns into cat's eyes off axis
+    ///
+    /// amount in [0, 1] is how far the clipping circle has moved off the
+    /// aperture, in aperture radii, at the corners of the image.
+    pub fn cat_eye(mut self, amount: f64) -> Self {
+        self.cat_eye = amount.clamp(0.0, 1.0);
+        self
+    }
+
+    /// Tilts the focal plane away from the sensor, by tilt degrees about the
+    /// horizontal axis (positive focuses further away at the top of the
+    /// image) and swing degrees about the vertical axis (positive focuses
+    /// further away on the right)
+    ///
+    /// Tilting along a receding surface keeps all of it sharp, tilting
+    /// against it gives the shallow "miniature" look.
+    pub fn tilt(mut self, tilt: f64, swing: f64) -> Self {
+        let tan = |degrees: f64| degrees.clamp(-89.0, 89.0).to_radians().tan();
+        self.focal_plane_normal = Some((self.w + tan(tilt)*self.v + tan(swing)*self.u).unit_vector());
+        self
+    }
+
+    /// Shifts the lens parallel to the sensor by fractions of the image
+    /// width and height, moving the framing without changing perspective,
+    /// eg. to keep verticals straight while looking up at a building
+    pub fn shift(mut self, x: f64, y: f64) -> Self {
+        self.lower_left_corner = self.lower_left_corner + self.horizontal*x + self.vertical*y;
+        self
+    }
+
+    /// Ray through (s, t) on the image, both in [0, 1] from the bottom left
+    ///
+    /// None if no light reaches that part of the image, like the corners
+    /// of a fisheye.
+    pub fn get_ray(&self, s: f64, t: f64) -> Option<Ray> {
+        self.image_ray(s, t, 1.0)
+    }
+
+    /// Ray through (s, t) like get_ray, along with the weight of the light
+    /// it brings back in each channel, which carries the vignetting and
+    /// chromatic aberration. wavelength is that of the ray in spectral mode.
+    pub fn sample_ray(&self, s: f64, t: f64, wavelength: Option<f64>) -> Option<(Ray, Color)> {
+        let (magnification, weight) = match wavelength {
+            _ if self.chromatic_aberration == 0.0 => (1.0, Color::gray(1.0)),
+            Some(lambda) => (self.magnification(lambda), Color::gray(1.0)),
+            None => {
+                // The chosen channel makes up for the two that aren't traced
+                let channel = ((3.0*random_f64()) as usize).min(2);
+                let mut weight = [0.0; 3];
+                weight[channel] = 3.0;
+                (self.magnification(CHANNEL_WAVELENGTHS[channel]), Color::new(weight[0], weight[1], weight[2]))
+            },
+        };
+
+        let r = self.image_ray(s, t, magnification)?;
+        if self.vignetting == 0.0 {
+            return Some((r, weight));
+        }
+        let cos_theta = (-Vec3::dot(&r.dir().unit_vector(), &self.w)).max(0.0);
+        Some((r, weight*(1.0 - self.vignetting*(1.0 - cos_theta.powi(4)))))
+    }
+
+    /// Ray through (s, t) for light the lens magnifies by magnification
+    fn image_ray(&self, s: f64, t: f64, magnification: f64) -> Option<Ray> {
+        let (eye, s, t) = match &self.stereo {
+            Some((layout, _)) => {
+                let (eye, s, t) = layout.split(s, t);
+                (Some(eye), s, t)
+            },
+            None => (None, s, t),
+        };
+        let (s, t) = self.undistort(s, t, magnification);
+
+        let r = match self.projection {
+            Projection::Perspective => match &self.lens_system {
+                Some((lens, sensor_height)) => {
+                    // The lens flips the image, so the top of the sensor sees the bottom of the scene
+                    let sensor_width = sensor_height*self.aspect_ratio();
+                    let (o, d) = lens.sample_ray((0.5 - s)*sensor_width, (0.5 - t)*sensor_height)?;
+                    Ray::new(
+                        self.origin + self.u*o.x() + self.v*o.y() - self.w*o.z(),
+                        self.u*d.x() + self.v*d.y() - self.w*d.z(),
+                    )
+                },
+                None => self.perspective_ray(s, t),
+            },
+            Projection::Orthographic {height} => {
+                let width = height*self.aspect_ratio();
+                Ray::new(self.origin + (s - 0.5)*width*self.u + (t - 0.5)*height*self.v, -self.w)
+            },
+            projection => {
+                let d = projection.local_direction(s, t, self.aspect_ratio())?;
+                Ray::new(self.origin, self.u*d.x() + self.v*d.y() + self.w*d.z())
+            },
+        };
+
+        match (eye, &self.stereo) {
+            (Some(eye), Some((_, interocular))) => Some(self.eye_ray(r, eye, *interocular)),
+            _ => Some(r),
+        }
+    }
+
+    /// Where on the undistorted image the lens shows what is seen at (s, t)
+    /// in light magnified by magnification
+    fn undistort(&self, s: f64, t: f64, magnification: f64) -> (f64, f64) {
+        if self.distortion.is_none() && magnification == 1.0 {
+            return (s, t);
+        }
+        let aspect_ratio = self.aspect_ratio();
+        let (x, y) = (aspect_ratio*(2.0*s - 1.0) / magnification, (2.0*t - 1.0) / magnification);
+        let (x, y) = match &self.distortion {
+            Some(distortion) => distortion.undistort(x, y),
+            None => (x, y),
+        };
+        (0.5*(x / aspect_ratio + 1.0), 0.5*(y + 1.0))
+    }
+
+    /// Magnification of light of wavelength lambda (in nm) from lateral
+    /// chromatic aberration
+    fn magnification(&self, lambda: f64) -> f64 {
+        let [red, green, _] = CHANNEL_WAVELENGTHS;
+        1.0 + self.chromatic_aberration*(lambda - green) / (red - green)
+    }
+
+    /// Width over height of the image (of one eye for stereo pairs)
+    fn aspect_ratio(&self) -> f64 {
+        self.horizontal.length() / self.vertical.length()
+    }
+
+    /// r moved sideways to the given eye, across r and the up direction
+    fn eye_ray(&self, r: Ray, eye: Eye, interocular: f64) -> Ray {
+        let right = Vec3::cross(r.dir(), &self.v);
+        if right.length_squared() < 1e-12 {
+            // Looking straight up or down both eyes see the same
+            return r;
+        }
+        let side = if eye == Eye::Left {-0.5} else {0.5};
+        Ray::new(r.origin() + side*interocular*right.unit_vector(), *r.dir())
+    }
+
+    fn perspective_ray(&self, s: f64, t: f64) -> Ray {
+        // s and t are in [0, 1]
+        let target = self.lower_left_corner + self.horizontal*s + self.vertical*t;
+        let rd = self.lens_radius * self.sample_lens(s, t);
+        let offset = self.u*rd.x() + self.v*rd.y();
+
+        Ray::new(
+            self.origin + offset,
+            self.focus_point(&target) - self.origin - offset
+        )
+    }
+
+    /// Point on the aperture, in unit lens coordinates, for a ray through (s, t)
+    fn sample_lens(&self, s: f64, t: f64) -> Vec3 {
+        const MAX_TRIES: u32 = 64;
+
+        if self.cat_eye == 0.0 {
+            return self.aperture.sample();
+        }
+
+        // The clipping circle moves towards the image centre, so rays
+        // leaving the side of the lens away from it are cut off
+        let (x, y) = (2.0*s - 1.0, 2.0*t - 1.0);
+        let aspect = self.horizontal.length() / self.vertical.length();
+        let clip = (-self.cat_eye / (aspect*aspect + 1.0).sqrt())*Vec3::new(aspect*x, y, 0.0);
+        for _ in 0..MAX_TRIES {
+            let p = self.aperture.sample();
+            if (p - clip).length_squared() <= 1.0 {
+                return p;
+            }
+        }
+        Vec3::new(0.0, 0.0, 0.0)
+    }
+
+    /// Point in focus seen through target, a point on the untilted focal plane
+    fn focus_point(&self, target: &Point3) -> Point3 {
+        let n = match &self.focal_plane_normal {
+            Some(n) => n,
+            None => return *target,
+        };
+
+        // Where the chief ray through target meets the tilted plane, which
+        // passes through the focus point on the optical axis
+        let dir = target - self.origin;
+        let denom = Vec3::dot(n, &dir);
+        let k = if denom.abs() < 1e-12 {0.0} else {-self.focus_distance*Vec3::dot(n, &self.w) / denom};
+        if k <= 0.0 {
+            // The plane is parallel to or behind this ray, so nothing along it is in focus
+            return self.origin + dir*1e6;
+        }
+        self.origin + dir*k
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use approx::assert_relative_eq;
+    use super::*;
+
+    fn camera(aperture_width: f64) -> Camera {
+        // Looking down -z from the origin, focused 2 units away
+        Camera::new(
+            Point3::new(0.0, 0.0, 0.0),
+            Point3::new(0.0, 0.0, -1.0),
+            Vec3::new(0.0, 1.0, 0.0),
+            60.0,
+            1.5,
+            aperture_width,
+            2.0,
+        )
+    }
+
+    #[test]
+    fn test_tilt_focus() {
+        // Rays through a point on the image all meet where it is in focus,
+        // on the optical axis at the focus distance, and further away at the top
+        let camera = camera(0.5).tilt(20.0, 0.0);
+        let focus = |s: f64, t: f64| {
+            let r = camera.get_ray(s, t).unwrap();
+            let p = r.at(1.0);
+            for _ in 0..100 {
+                assert!((camera.get_ray(s, t).unwrap().at(1.0) - p).length() < 1e-9);
+            }
+            p
+        };
+        assert!((focus(0.5, 0.5) - Point3::new(0.0, 0.0, -2.0)).length() < 1e-9);
+        assert!(-focus(0.5, 0.9).z() > 2.0);
+        assert!(-focus(0.5, 0.1).z() < 2.0);
+    }
+
+    #[test]
+    fn test_shift() {
+        // Shifting moves the framing across the focal plane without turning the camera
+        let (reference, shifted) = (camera(0.0), camera(0.0).shift(0.1, -0.2));
+        let moved = reference.lower_left_corner + reference.horizontal*0.1 - reference.vertical*0.2;
+        assert!((shifted.lower_left_corner - moved).length() < 1e-12);
+        assert!((shifted.w - reference.w).length() < 1e-12);
+
+        let (r, r0) = (shifted.get_ray(0.5, 0.5).unwrap(), reference.get_ray(0.6, 0.3).unwrap());
+        assert!((r.dir().unit_vector() - r0.dir().unit_vector()).length() < 1e-12);
+        assert!((r.origin() - r0.origin()).length() < 1e-12);
+    }
+
+    #[test]
+    fn test_cat_eye_clips_corners() {
+        // At the top right corner the clipping circle has moved a whole
+        // aperture radius towards the image centre
+        let camera = camera(0.5).cat_eye(1.0);
+        let clip = -Vec3::new(1.5, 1.0, 0.0).unit_vector();
+        for _ in 0..1000 {
+            let p = camera.sample_lens(1.0, 1.0);
+            assert!(p.length_squared() <= 1.0 + 1e-12);
+            assert!((p - clip).length_squared() <= 1.0 + 1e-12);
+        }
+    }
+
+    #[test]
+    fn test_channel_weights() {
+        // Tracing one channel per ray at three times the weight averages to white
+        let camera = camera(0.0).chromatic_aberration(0.02);
+        let n = 30000;
+        let mut total = Color::BLACK;
+        for _ in 0..n {
+            total += camera.sample_ray(0.5, 0.5, None).unwrap().1;
+        }
+        let mean = total / n as f64;
+        for c in [mean.r(), mean.g(), mean.b()] {
+            assert_relative_eq!(c, 1.0, epsilon = 0.05);
+        }
+    }
+
+    #[test]
+    fn test_magnification() {
+        // Red is magnified by 1 + amount relative to green
+        let camera = camera(0.0).chromatic_aberration(0.02);
+        assert_relative_eq!(camera.magnification(610.0), 1.02);
+        assert_relative_eq!(camera.magnification(550.0), 1.0);
+    }
+
+    #[test]
+    fn test_vignetting() {
+        // Full vignetting darkens by cos^4 of the angle off the optical axis
+        let camera = camera(0.0).vignetting(1.0);
+        let weight = |s: f64, t: f64| camera.sample_ray(s, t, None).unwrap().1;
+        assert_relative_eq!(weight(0.5, 0.5).g(), 1.0);
+
+        let h = 30.0_f64.to_radians().tan();
+        let cos_theta = 1.0 / (1.0 + (1.5*h).powi(2) + h*h).sqrt();
+        let corner = weight(0.0, 0.0);
+        for c in [corner.r(), corner.g(), corner.b()] {
+            assert_relative_eq!(c, cos_theta.powi(4), epsilon = 1e-12);
+        }
+    }
+}
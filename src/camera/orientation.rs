@@ -0,0 +1,127 @@
+use crate::vec3::{Point3, Vec3};
+
+/// Which way a camera faces, as the right, up and backward directions of
+/// its image (u, v and w), which are unit length and at right angles
+#[derive(Debug, Clone, Copy)]
+pub struct Orientation {
+    u: Vec3,
+    v: Vec3,
+    w: Vec3,
+}
+
+impl Orientation {
+    /// Looking from lookfrom towards lookat, with vup pointing up in the image
+    ///
+    /// When vup is along the view direction (eg. looking straight down with
+    /// +y up) it can't say which way is up, so the top of the image points
+    /// towards -z instead, or +x when looking along z. If lookfrom and lookat
+    /// are the same point the camera looks down -z.
+    pub fn look_at(lookfrom: &Point3, lookat: &Point3, vup: &Vec3) -> Self {
+        let back = lookfrom - lookat;
+        let w = if back.length_squared() > 0.0 {back.unit_vector()} else {Vec3::new(0.0, 0.0, 1.0)};
+        Self::from_back_up(w, vup)
+    }
+
+    /// The default camera (looking down -z with +y up) turned by the
+    /// rotation quaternion w + xi + yj + zk, which needn't be normalized
+    pub fn from_quaternion(w: f64, x: f64, y: f64, z: f64) -> Self {
+        let norm = (w*w + x*x + y*y + z*z).sqrt();
+        let (w, x, y, z) = (w / norm, x / norm, y / norm, z / norm);
+        Orientation {
+            u: Vec3::new(1.0 - 2.0*(y*y + z*z), 2.0*(x*y + w*z), 2.0*(x*z - w*y)),
+            v: Vec3::new(2.0*(x*y - w*z), 1.0 - 2.0*(x*x + z*z), 2.0*(y*z + w*x)),
+            w: Vec3::new(2.0*(x*z + w*y), 2.0*(y*z - w*x), 1.0 - 2.0*(x*x + y*y)),
+        }
+    }
+
+    /// From a camera to world rotation matrix, whose columns are the right,
+    /// up and backward directions of the camera
+    ///
+    /// The matrix is straightened out if it isn't quite a rotation: the
+    /// backward column is kept and the up column made perpendicular to it.
+    pub fn from_matrix(m: [[f64; 3]; 3]) -> Self {
+        let column = |i: usize| Vec3::new(m[0][i], m[1][i], m[2][i]);
+        Self::from_back_up(column(2).unit_vector(), &column(1))
+    }
+
+    /// Turned counter-clockwise by degrees about the view direction, so
+    /// the scene turns clockwise in the image
+    pub fn roll(&self, degrees: f64) -> Self {
+        let (sin, cos) = degrees.to_radians().sin_cos();
+        Orientation {
+            u: cos*self.u + sin*self.v,
+            v: cos*self.v - sin*self.u,
+            w: self.w,
+        }
+    }
+
+    /// Direction pointing right in the image
+    pub fn right(&self) -> &Vec3 {
+        &self.u
+    }
+
+    /// Direction pointing up in the image
+    pub fn up(&self) -> &Vec3 {
+        &self.v
+    }
+
+    /// Direction pointing back out of the image, opposite the view direction
+    pub fn back(&self) -> &Vec3 {
+        &self.w
+    }
+
+    /// Frame around the unit backward direction w with vup as close to up
+    /// as possible
+    fn from_back_up(w: Vec3, vup: &Vec3) -> Self {
+        let right = Vec3::cross(vup, &w);
+        let right = if right.length_squared() > 1e-12*vup.length_squared() {
+            right
+        } else {
+            let fallback = if w.y().abs() > 0.5 {Vec3::new(0.0, 0.0, -1.0)} else {Vec3::new(1.0, 0.0, 0.0)};
+            Vec3::cross(&fallback, &w)
+        };
+        let u = right.unit_vector();
+        let v = Vec3::cross(&w, &u).unit_vector();
+        Orientation {u, v, w}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use approx::assert_relative_eq;
+    use super::*;
+
+    fn assert_vec_eq(a: &Vec3, b: [f64; 3]) {
+        assert_relative_eq!(a.x(), b[0], epsilon = 1e-12);
+        assert_relative_eq!(a.y(), b[1], epsilon = 1e-12);
+        assert_relative_eq!(a.z(), b[2], epsilon = 1e-12);
+    }
+
+    #[test]
+    fn test_orientation() {
+        // Looking straight down with +y up still gives a frame, with -z up in the image
+        let down = Orientation::look_at(&Point3::new(0.0, 5.0, 0.0), &Point3::new(0.0, 0.0, 0.0), &Vec3::new(0.0, 1.0, 0.0));
+        assert_vec_eq(down.right(), [1.0, 0.0, 0.0]);
+        assert_vec_eq(down.up(), [0.0, 0.0, -1.0]);
+        assert_vec_eq(down.back(), [0.0, 1.0, 0.0]);
+
+        // The identity quaternion is the default camera, and a quarter turn about y looks down -x
+        let identity = Orientation::from_quaternion(1.0, 0.0, 0.0, 0.0);
+        assert_vec_eq(identity.right(), [1.0, 0.0, 0.0]);
+        assert_vec_eq(identity.up(), [0.0, 1.0, 0.0]);
+        let s = 0.5_f64.sqrt();
+        let left = Orientation::from_quaternion(s, 0.0, s, 0.0);
+        assert_vec_eq(left.back(), [1.0, 0.0, 0.0]);
+        assert_vec_eq(left.right(), [0.0, 0.0, -1.0]);
+
+        // Matrix columns are right, up and back
+        let m = Orientation::from_matrix([[0.0, 0.0, 1.0], [0.0, 1.0, 0.0], [-1.0, 0.0, 0.0]]);
+        assert_vec_eq(m.right(), [0.0, 0.0, -1.0]);
+        assert_vec_eq(m.back(), [1.0, 0.0, 0.0]);
+
+        // Rolling a quarter turn counter-clockwise puts right where up was
+        let rolled = identity.roll(90.0);
+        assert_vec_eq(rolled.right(), [0.0, 1.0, 0.0]);
+        assert_vec_eq(rolled.up(), [-1.0, 0.0, 0.0]);
+    }
+}
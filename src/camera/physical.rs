@@ -0,0 +1,108 @@
+/// Where a physically specified camera focuses
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Focus {
+    /// Focus this many world units in front of the camera
+    Distance(f64),
+    /// Focus on whatever is seen through pixel (x, y), counted from the
+    /// top left of the image
+    Auto {x: u32, y: u32},
+}
+
+/// Camera settings in the terms of a photographer, as an alternative to
+/// vfov, aperture width and focus distance
+#[derive(Debug, Clone, Copy)]
+pub struct PhysicalCamera {
+    pub focal_length: f64,  // In mm
+    pub f_stop: f64,
+    pub sensor: (f64, f64),  // Width and height in mm
+    pub shutter: f64,  // In seconds
+    pub iso: f64,
+    pub focus: Focus,
+    pub mm_per_unit: f64,  // Size of a world unit
+}
+
+impl PhysicalCamera {
+    /// A lens of focal_length mm at f_stop on a full frame sensor, exposed
+    /// for 1/125s at ISO 100, focused a world unit away
+    pub fn new(focal_length: f64, f_stop: f64) -> Self {
+        PhysicalCamera {
+            focal_length,
+            f_stop,
+            sensor: (36.0, 24.0),
+            shutter: 1.0 / 125.0,
+            iso: 100.0,
+            focus: Focus::Distance(1.0),
+            mm_per_unit: 1000.0,
+        }
+    }
+
+    pub fn sensor(mut self, width: f64, height: f64) -> Self {
+        self.sensor = (width, height);
+        self
+    }
+
+    /// Sets the shutter time in seconds and the ISO sensitivity
+    pub fn exposure(mut self, shutter: f64, iso: f64) -> Self {
+        self.shutter = shutter;
+        self.iso = iso;
+        self
+    }
+
+    pub fn focus(mut self, focus: Focus) -> Self {
+        self.focus = focus;
+        self
+    }
+
+    /// Sets the size of a world unit, by default a metre
+    pub fn mm_per_unit(mut self, mm_per_unit: f64) -> Self {
+        self.mm_per_unit = mm_per_unit;
+        self
+    }
+
+    /// Vertical field of view in degrees of an image of aspect_ratio, as
+    /// big as fits on the sensor
+    pub fn vfov(&self, aspect_ratio: f64) -> f64 {
+        let (width, height) = self.sensor;
+        let film_height = height.min(width / aspect_ratio);
+        2.0*(film_height / (2.0*self.focal_length)).atan().to_degrees()
+    }
+
+    /// Diameter of the entrance pupil in world units
+    pub fn aperture_width(&self) -> f64 {
+        self.focal_length / self.f_stop / self.mm_per_unit
+    }
+
+    /// Factor from radiance in cd/m^2 to the image's values, set so a
+    /// value of 1 is the brightest the camera records without clipping
+    ///
+    /// Uses the saturation based exposure value of Lagarde and de Rousiers
+    /// 2014, so eg. f/1 for 1s at ISO 100 takes 1.2 cd/m^2 to white.
+    pub fn exposure_scale(&self) -> f64 {
+        let ev100 = (self.f_stop*self.f_stop / self.shutter*100.0 / self.iso).log2();
+        1.0 / (1.2*2.0_f64.powf(ev100))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use approx::assert_relative_eq;
+    use super::*;
+
+    #[test]
+    fn test_physical_camera() {
+        // A 50mm lens on a full frame sensor sees about 27 degrees vertically at 3:2
+        let camera = PhysicalCamera::new(50.0, 2.0);
+        assert_relative_eq!(camera.vfov(1.5), 26.99, epsilon = 0.01);
+        assert_relative_eq!(camera.aperture_width(), 0.025);
+
+        // A wider image is cropped out of the sensor's width
+        assert_relative_eq!(camera.vfov(3.0), 2.0*(6.0_f64 / 50.0).atan().to_degrees());
+
+        // One stop less light, by closing the aperture or shortening the shutter, halves the exposure
+        let reference = PhysicalCamera::new(50.0, 1.0).exposure(1.0, 100.0);
+        assert_relative_eq!(reference.exposure_scale(), 1.0 / 1.2);
+        assert_relative_eq!(PhysicalCamera::new(50.0, 2.0_f64.sqrt()).exposure(1.0, 100.0).exposure_scale(), 0.5 / 1.2, epsilon = 1e-12);
+        assert_relative_eq!(reference.exposure(0.5, 100.0).exposure_scale(), 0.5 / 1.2);
+        assert_relative_eq!(reference.exposure(1.0, 200.0).exposure_scale(), 2.0 / 1.2);
+    }
+}
@@ -0,0 +1,149 @@
+use crate::PI;
+use crate::vec3::Vec3;
+
+/// How the camera maps directions onto the image
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Projection {
+    /// Pinhole or thin lens, set up by vfov and the lens settings of the camera
+    Perspective,
+    /// Parallel rays along the view direction through a view height world
+    /// units tall, for technical drawings
+    Orthographic {height: f64},
+    /// Full 360 by 180 degree panorama, longitude across and latitude up
+    /// the image, centred on the view direction. Usually rendered at 2:1.
+    Equirectangular,
+    /// Circular fisheye filling the height of the image with fov degrees
+    Fisheye {mapping: FisheyeMapping, fov: f64},
+    /// The six faces of a cube around the camera as 90 degree views in a
+    /// 3x2 grid, usually rendered at 3:2. The top row holds the +x, -x and
+    /// +y faces, the bottom row -y, +z and -z, in the camera's frame where
+    /// -z is the view direction and +y is up.
+    Cubemap,
+}
+
+/// How a fisheye lens maps the angle off axis to the distance from the
+/// centre of the image
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FisheyeMapping {
+    /// Distance proportional to the angle
+    Equidistant,
+    /// Distance proportional to sin(angle/2), which keeps areas in proportion
+    Equisolid,
+}
+
+/// How the two eyes of a stereo pair share the image
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StereoLayout {
+    /// Left eye in the left half
+    SideBySide,
+    /// Left eye in the top half
+    TopBottom,
+}
+
+/// Which eye of a stereo pair a ray belongs to
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) enum Eye {
+    Left,
+    Right,
+}
+
+impl StereoLayout {
+    /// The eye of image position (s, t), and the position in that eye's image
+    pub(super) fn split(&self, s: f64, t: f64) -> (Eye, f64, f64) {
+        match self {
+            StereoLayout::SideBySide if s < 0.5 => (Eye::Left, 2.0*s, t),
+            StereoLayout::SideBySide => (Eye::Right, 2.0*s - 1.0, t),
+            StereoLayout::TopBottom if t >= 0.5 => (Eye::Left, s, 2.0*t - 1.0),
+            StereoLayout::TopBottom => (Eye::Right, s, 2.0*t),
+        }
+    }
+
+    /// Aspect ratio of one eye's image in an image of aspect_ratio
+    pub(super) fn eye_aspect_ratio(&self, aspect_ratio: f64) -> f64 {
+        match self {
+            StereoLayout::SideBySide => aspect_ratio / 2.0,
+            StereoLayout::TopBottom => aspect_ratio*2.0,
+        }
+    }
+}
+
+impl Projection {
+    /// Direction seen at (s, t) in [0, 1]^2 on an image of aspect_ratio, in
+    /// the camera's frame (x right, y up, z back), for the projections that
+    /// only depend on direction. None outside the image of a fisheye.
+    pub(super) fn local_direction(&self, s: f64, t: f64, aspect_ratio: f64) -> Option<Vec3> {
+        match *self {
+            Projection::Equirectangular => {
+                let phi = 2.0*PI*(s - 0.5);
+                let theta = PI*(t - 0.5);
+                Some(Vec3::new(theta.cos()*phi.sin(), theta.sin(), -theta.cos()*phi.cos()))
+            },
+            Projection::Fisheye {mapping, fov} => {
+                let (x, y) = (aspect_ratio*(2.0*s - 1.0), 2.0*t - 1.0);
+                let r = (x*x + y*y).sqrt();
+                if r > 1.0 {
+                    return None;
+                }
+                let half_fov = 0.5*fov.clamp(0.0, 360.0).to_radians();
+                let theta = match mapping {
+                    FisheyeMapping::Equidistant => r*half_fov,
+                    FisheyeMapping::Equisolid => 2.0*(r*(0.5*half_fov).sin()).asin(),
+                };
+                let (cos_phi, sin_phi) = if r > 0.0 {(x / r, y / r)} else {(1.0, 0.0)};
+                Some(Vec3::new(theta.sin()*cos_phi, theta.sin()*sin_phi, -theta.cos()))
+            },
+            Projection::Cubemap => {
+                let column = ((3.0*s).floor() as usize).min(2);
+                let row = ((2.0*(1.0 - t)).floor() as usize).min(1);
+                let a = 2.0*(3.0*s - column as f64) - 1.0;
+                let b = 1.0 - 2.0*(2.0*(1.0 - t) - row as f64);
+
+                // Forward and up of each face, right is forward x up
+                let (forward, up) = match (row, column) {
+                    (0, 0) => (Vec3::new( 1.0,  0.0,  0.0), Vec3::new(0.0, 1.0,  0.0)),
+                    (0, 1) => (Vec3::new(-1.0,  0.0,  0.0), Vec3::new(0.0, 1.0,  0.0)),
+                    (0, _) => (Vec3::new( 0.0,  1.0,  0.0), Vec3::new(0.0, 0.0,  1.0)),
+                    (_, 0) => (Vec3::new( 0.0, -1.0,  0.0), Vec3::new(0.0, 0.0, -1.0)),
+                    (_, 1) => (Vec3::new( 0.0,  0.0,  1.0), Vec3::new(0.0, 1.0,  0.0)),
+                    (_, _) => (Vec3::new( 0.0,  0.0, -1.0), Vec3::new(0.0, 1.0,  0.0)),
+                };
+                let right = Vec3::cross(&forward, &up);
+                Some((forward + a*right + b*up).unit_vector())
+            },
+            Projection::Perspective | Projection::Orthographic {..} => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use approx::assert_relative_eq;
+    use super::*;
+
+    #[test]
+    fn test_local_direction() {
+        let to_array = |v: Vec3| [v.x(), v.y(), v.z()];
+
+        // The centre of a panorama looks forward, its edges behind
+        let equirectangular = Projection::Equirectangular;
+        for (s, t, expected) in [(0.5, 0.5, [0.0, 0.0, -1.0]), (0.75, 0.5, [1.0, 0.0, 0.0]), (1.0, 0.5, [0.0, 0.0, 1.0]), (0.5, 1.0, [0.0, 1.0, 0.0])] {
+            let d = to_array(equirectangular.local_direction(s, t, 2.0).unwrap());
+            (0..3).for_each(|i| assert_relative_eq!(d[i], expected[i], epsilon = 1e-12));
+        }
+
+        // The rim of a 180 degree fisheye is 90 degrees off axis, whatever the mapping
+        for mapping in [FisheyeMapping::Equidistant, FisheyeMapping::Equisolid] {
+            let fisheye = Projection::Fisheye {mapping, fov: 180.0};
+            let d = to_array(fisheye.local_direction(0.5, 1.0, 1.0).unwrap());
+            (0..3).for_each(|i| assert_relative_eq!(d[i], [0.0, 1.0, 0.0][i], epsilon = 1e-12));
+            assert!(fisheye.local_direction(1.0, 1.0, 1.0).is_none());
+        }
+
+        // Face centres of a cubemap look down the axes
+        let cubemap = Projection::Cubemap;
+        let d = to_array(cubemap.local_direction(5.0/6.0, 0.25, 1.5).unwrap());
+        (0..3).for_each(|i| assert_relative_eq!(d[i], [0.0, 0.0, -1.0][i], epsilon = 1e-12));
+        let d = to_array(cubemap.local_direction(1.0/6.0, 0.75, 1.5).unwrap());
+        (0..3).for_each(|i| assert_relative_eq!(d[i], [1.0, 0.0, 0.0][i], epsilon = 1e-12));
+    }
+}
@@ -0,0 +1,186 @@
+use std::fs::{self, File};
+use std::io::{self, BufReader, BufWriter, Read, Write};
+use std::path::Path;
+
+use crate::invalid_data;
+use crate::color::Color;
+use crate::film::{Film, Region};
+
+const MAGIC: &[u8; 8] = b"RRTCKPT\0";
+const VERSION: u32 = 2;
+
+/// 64-bit FNV-1a hasher
+///
+/// Unlike std's DefaultHasher its output is stable across Rust versions,
+/// so hashes written into checkpoint files stay valid.
+pub struct Fnv64 {
+    hash: u64,
+}
+
+impl Fnv64 {
+    pub fn new() -> Self {
+        Fnv64 {hash: 0xcbf29ce484222325}
+    }
+
+    pub fn write(&mut self, bytes: &[u8]) {
+        for byte in bytes {
+            self.hash ^= *byte as u64;
+            self.hash = self.hash.wrapping_mul(0x100000001b3);
+        }
+    }
+
+    pub fn write_u32(&mut self, x: u32) {
+        self.write(&x.to_le_bytes());
+    }
+
+    pub fn write_f64(&mut self, x: f64) {
+        self.write(&x.to_bits().to_le_bytes());
+    }
+
+    pub fn finish(&self) -> u64 {
+        self.hash
+    }
+}
+
+impl Default for Fnv64 {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Snapshot of an in-progress render
+///
+/// Stores everything needed to continue a render exactly where it left off:
+/// the accumulated film, the seed the scene was built with and the state of
+/// the random number generator at the time of the snapshot.
+pub struct Checkpoint {
+    pub scene_hash: u64,
+    pub seed: u64,
+    pub rng_state: u64,
+    pub film: Film,
+}
+
+impl Checkpoint {
+    /// Writes the checkpoint to path
+    ///
+    /// The file is written next to path first and then renamed over it
+    /// so a crash mid-write never destroys the previous checkpoint.
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        let path = path.as_ref();
+        let mut tmp_path = path.as_os_str().to_owned();
+        tmp_path.push(".tmp");
+
+        {
+            let mut w = BufWriter::new(File::create(&tmp_path)?);
+            w.write_all(MAGIC)?;
+            w.write_all(&VERSION.to_le_bytes())?;
+            w.write_all(&self.scene_hash.to_le_bytes())?;
+            w.write_all(&self.seed.to_le_bytes())?;
+            w.write_all(&self.rng_state.to_le_bytes())?;
+            let region = self.film.region();
+            w.write_all(&region.x.to_le_bytes())?;
+            w.write_all(&region.y.to_le_bytes())?;
+            w.write_all(&region.width.to_le_bytes())?;
+            w.write_all(&region.height.to_le_bytes())?;
+            for (sum, n) in self.film.sum().iter().zip(self.film.samples()) {
+                w.write_all(&sum.r().to_le_bytes())?;
+                w.write_all(&sum.g().to_le_bytes())?;
+                w.write_all(&sum.b().to_le_bytes())?;
+                w.write_all(&n.to_le_bytes())?;
+            }
+            w.flush()?;
+        }
+
+        fs::rename(&tmp_path, path)
+    }
+
+    pub fn load<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        let mut r = BufReader::new(File::open(path)?);
+
+        let mut magic = [0u8; 8];
+        r.read_exact(&mut magic)?;
+        if &magic != MAGIC {
+            return Err(invalid_data("not a checkpoint file"));
+        }
+        let version = read_u32(&mut r)?;
+        if version == 0 || version > VERSION {
+            return Err(invalid_data(&format!("unsupported checkpoint version {}", version)));
+        }
+
+        let scene_hash = read_u64(&mut r)?;
+        let seed = read_u64(&mut r)?;
+        let rng_state = read_u64(&mut r)?;
+        // Version 1 checkpoints always covered the full image
+        let (x, y) = if version >= 2 {(read_u32(&mut r)?, read_u32(&mut r)?)} else {(0, 0)};
+        let region = Region::new(x, y, read_u32(&mut r)?, read_u32(&mut r)?);
+
+        let n_pixels = (region.width as usize)*(region.height as usize);
+        let mut sum = Vec::with_capacity(n_pixels);
+        let mut samples = Vec::with_capacity(n_pixels);
+        for _ in 0..n_pixels {
+            let x = read_f64(&mut r)?;
+            let y = read_f64(&mut r)?;
+            let z = read_f64(&mut r)?;
+            sum.push(Color::new(x, y, z));
+            samples.push(read_u32(&mut r)?);
+        }
+
+        let film = Film::from_raw(region, sum, samples)
+            .ok_or_else(|| invalid_data("film size mismatch"))?;
+
+        Ok(Checkpoint {scene_hash, seed, rng_state, film})
+    }
+}
+
+fn read_u32<R: Read>(r: &mut R) -> io::Result<u32> {
+    let mut buf = [0u8; 4];
+    r.read_exact(&mut buf)?;
+    Ok(u32::from_le_bytes(buf))
+}
+
+fn read_u64<R: Read>(r: &mut R) -> io::Result<u64> {
+    let mut buf = [0u8; 8];
+    r.read_exact(&mut buf)?;
+    Ok(u64::from_le_bytes(buf))
+}
+
+fn read_f64<R: Read>(r: &mut R) -> io::Result<f64> {
+    Ok(f64::from_bits(read_u64(r)?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_save_load_round_trip() {
+        let mut film = Film::new_region(Region::new(5, 4, 3, 2));
+        film.add_sample(0, 0, &Color::new(0.25, 0.5, 0.75));
+        film.add_sample(2, 1, &Color::new(1.0, 2.0, 3.0));
+        film.add_sample(2, 1, &Color::new(1.0, 2.0, 3.0));
+
+        let checkpoint = Checkpoint {scene_hash: 42, seed: 7, rng_state: 123456789, film};
+        let path = std::env::temp_dir().join(format!("rrt_checkpoint_test_{}.ckpt", std::process::id()));
+        checkpoint.save(&path).unwrap();
+        let loaded = Checkpoint::load(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+
+        assert_eq!(loaded.scene_hash, 42);
+        assert_eq!(loaded.seed, 7);
+        assert_eq!(loaded.rng_state, 123456789);
+        assert_eq!(loaded.film.region(), Region::new(5, 4, 3, 2));
+        assert_eq!(loaded.film.samples(), checkpoint.film.samples());
+        assert_eq!(loaded.film.pixel(0, 0).g(), 0.5);
+        assert_eq!(loaded.film.pixel(2, 1).b(), 3.0);
+    }
+
+    #[test]
+    fn test_rejects_garbage() {
+        let path = std::env::temp_dir().join(format!("rrt_checkpoint_garbage_{}.ckpt", std::process::id()));
+        fs::write(&path, b"definitely not a checkpoint").unwrap();
+        let loaded = Checkpoint::load(&path);
+        fs::remove_file(&path).unwrap();
+
+        assert!(loaded.is_err());
+    }
+}
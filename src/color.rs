@@ -1,15 +1,504 @@
+use std::ops;
+use std::sync::OnceLock;
+
 use image::Rgb;
 
-use crate::vec3::Vec3;
-use crate::clamp;
+use crate::{clamp, random_f64};
+
+/// Linear RGB color, such as a radiance or a reflectance
+///
+/// Unless noted otherwise colors are in the working color space, linear
+/// with sRGB / Rec.709 primaries. Use ColorSpace to convert to and from
+/// other primaries, and Encoding for colors stored in images.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct Color {
+    c: [f64; 3],
+}
+
+impl Color {
+    pub const BLACK: Color = Color {c: [0.0, 0.0, 0.0]};
+    pub const WHITE: Color = Color {c: [1.0, 1.0, 1.0]};
+
+    pub fn new(r: f64, g: f64, b: f64) -> Self {
+        Color {c: [r, g, b]}
+    }
+
+    /// Gray with all channels equal to value
+    pub fn gray(value: f64) -> Self {
+        Color {c: [value, value, value]}
+    }
+
+    /// Color with every channel random in [0, 1)
+    pub fn random() -> Self {
+        Color::new(random_f64(), random_f64(), random_f64())
+    }
+
+    pub fn r(&self) -> f64 {
+        self.c[0]
+    }
+
+    pub fn g(&self) -> f64 {
+        self.c[1]
+    }
+
+    pub fn b(&self) -> f64 {
+        self.c[2]
+    }
+
+    /// Relative luminance (the Y of CIE XYZ) of a working space color
+    pub fn luminance(&self) -> f64 {
+        0.2126*self.c[0] + 0.7152*self.c[1] + 0.0722*self.c[2]
+    }
+
+    /// Sum of the channels
+    pub fn sum(&self) -> f64 {
+        self.c[0] + self.c[1] + self.c[2]
+    }
+
+    pub fn max_component(&self) -> f64 {
+        self.c[0].max(self.c[1]).max(self.c[2])
+    }
+
+    pub fn is_black(&self) -> bool {
+        self.c.iter().all(|c| *c == 0.0)
+    }
+
+    /// Applies f to every channel
+    pub fn map<F: Fn(f64) -> f64>(&self, f: F) -> Color {
+        Color {c: [f(self.c[0]), f(self.c[1]), f(self.c[2])]}
+    }
+}
+
+impl ops::Index<usize> for Color {
+    type Output = f64;
+
+    fn index(&self, index: usize) -> &f64 {
+        &self.c[index]
+    }
+}
+
+// Implements a channel-wise binary operation for all combinations of
+// Color and &Color, and with f64 on either side
+macro_rules! binary_op {
+    ($Operation:ident $op_fn:ident $op_sym:tt) => {
+        impl ops::$Operation<Color> for Color {
+            type Output = Color;
+
+            fn $op_fn(self, other: Color) -> Color {
+                Color {c: [self.c[0] $op_sym other.c[0], self.c[1] $op_sym other.c[1], self.c[2] $op_sym other.c[2]]}
+            }
+        }
+
+        impl<'a> ops::$Operation<&'a Color> for Color {
+            type Output = Color;
+
+            fn $op_fn(self, other: &'a Color) -> Color {
+                self $op_sym *other
+            }
+        }
+
+        impl<'a> ops::$Operation<Color> for &'a Color {
+            type Output = Color;
+
+            fn $op_fn(self, other: Color) -> Color {
+                *self $op_sym other
+            }
+        }
+
+        impl<'a, 'b> ops::$Operation<&'b Color> for &'a Color {
+            type Output = Color;
+
+            fn $op_fn(self, other: &'b Color) -> Color {
+                *self $op_sym *other
+            }
+        }
+
+        impl ops::$Operation<f64> for Color {
+            type Output = Color;
+
+            fn $op_fn(self, other: f64) -> Color {
+                self.map(|c| c $op_sym other)
+            }
+        }
+
+        impl<'a> ops::$Operation<f64> for &'a Color {
+            type Output = Color;
+
+            fn $op_fn(self, other: f64) -> Color {
+                self.map(|c| c $op_sym other)
+            }
+        }
+
+        impl ops::$Operation<Color> for f64 {
+            type Output = Color;
+
+            fn $op_fn(self, other: Color) -> Color {
+                other.map(|c| self $op_sym c)
+            }
+        }
+
+        impl<'a> ops::$Operation<&'a Color> for f64 {
+            type Output = Color;
+
+            fn $op_fn(self, other: &'a Color) -> Color {
+                other.map(|c| self $op_sym c)
+            }
+        }
+    }
+}
+
+// Implements compound assignment with Color, &Color or f64 on the right
+macro_rules! assignment_op {
+    ($Operation:ident $op_fn:ident $op_sym:tt) => {
+        impl ops::$Operation<Color> for Color {
+            fn $op_fn(&mut self, other: Color) {
+                for i in 0..3 {
+                    self.c[i] $op_sym other.c[i];
+                }
+            }
+        }
+
+        impl<'a> ops::$Operation<&'a Color> for Color {
+            fn $op_fn(&mut self, other: &'a Color) {
+                for i in 0..3 {
+                    self.c[i] $op_sym other.c[i];
+                }
+            }
+        }
+
+        impl ops::$Operation<f64> for Color {
+            fn $op_fn(&mut self, other: f64) {
+                for c in self.c.iter_mut() {
+                    *c $op_sym other;
+                }
+            }
+        }
+    }
+}
+
+binary_op!(Add add +);
+assignment_op!(AddAssign add_assign +=);
+
+binary_op!(Sub sub -);
+assignment_op!(SubAssign sub_assign -=);
+
+binary_op!(Mul mul *);
+assignment_op!(MulAssign mul_assign *=);
+
+binary_op!(Div div /);
+assignment_op!(DivAssign div_assign /=);
+
+/// 3x3 matrix acting on colors, rows first
+type Matrix = [[f64; 3]; 3];
+
+fn apply(m: &Matrix, c: &Color) -> Color {
+    let row = |i: usize| m[i][0]*c.c[0] + m[i][1]*c.c[1] + m[i][2]*c.c[2];
+    Color::new(row(0), row(1), row(2))
+}
+
+fn multiply(a: &Matrix, b: &Matrix) -> Matrix {
+    let mut m = [[0.0; 3]; 3];
+    for (i, row) in m.iter_mut().enumerate() {
+        for (j, x) in row.iter_mut().enumerate() {
+            *x = (0..3).map(|k| a[i][k]*b[k][j]).sum();
+        }
+    }
+    m
+}
+
+fn invert(m: &Matrix) -> Matrix {
+    let cofactor = |r0: usize, r1: usize, c0: usize, c1: usize| m[r0][c0]*m[r1][c1] - m[r0][c1]*m[r1][c0];
+    let det = m[0][0]*cofactor(1, 2, 1, 2) - m[0][1]*cofactor(1, 2, 0, 2) + m[0][2]*cofactor(1, 2, 0, 1);
+    [
+        [cofactor(1, 2, 1, 2) / det, -cofactor(0, 2, 1, 2) / det, cofactor(0, 1, 1, 2) / det],
+        [-cofactor(1, 2, 0, 2) / det, cofactor(0, 2, 0, 2) / det, -cofactor(0, 1, 0, 2) / det],
+        [cofactor(1, 2, 0, 1) / det, -cofactor(0, 2, 0, 1) / det, cofactor(0, 1, 0, 1) / det],
+    ]
+}
+
+/// XYZ of a chromaticity (x, y) with Y = 1
+fn xy_to_xyz((x, y): (f64, f64)) -> Color {
+    Color::new(x / y, 1.0, (1.0 - x - y) / y)
+}
+
+const D65: (f64, f64) = (0.3127, 0.3290);
+const ACES_WHITE: (f64, f64) = (0.32168, 0.33767);
+
+/// Bradford chromatic adaptation from white point src to dst
+fn bradford(src: (f64, f64), dst: (f64, f64)) -> Matrix {
+    const BRADFORD: Matrix = [
+        [0.8951, 0.2664, -0.1614],
+        [-0.7502, 1.7135, 0.0367],
+        [0.0389, -0.0685, 1.0296],
+    ];
+    let (s, d) = (apply(&BRADFORD, &xy_to_xyz(src)), apply(&BRADFORD, &xy_to_xyz(dst)));
+    let scale = [[d.c[0] / s.c[0], 0.0, 0.0], [0.0, d.c[1] / s.c[1], 0.0], [0.0, 0.0, d.c[2] / s.c[2]]];
+    multiply(&invert(&BRADFORD), &multiply(&scale, &BRADFORD))
+}
+
+/// Chromaticity of a black body at temperature kelvin, from the cubic
+/// spline fit of Kim et al. 2002 (clamped to its range, 1667K to 25000K)
+pub fn blackbody_xy(kelvin: f64) -> (f64, f64) {
+    let t = kelvin.clamp(1667.0, 25000.0);
+    let (t2, t3) = (t*t, t*t*t);
+    let x = if t <= 4000.0 {
+        -0.2661239e9 / t3 - 0.2343589e6 / t2 + 0.8776956e3 / t + 0.179910
+    } else {
+        -3.0258469e9 / t3 + 2.1070379e6 / t2 + 0.2226347e3 / t + 0.240390
+    };
+    let (x2, x3) = (x*x, x*x*x);
+    let y = if t <= 2222.0 {
+        -1.1063814*x3 - 1.34811020*x2 + 2.18555832*x - 0.20219683
+    } else if t <= 4000.0 {
+        -0.9549476*x3 - 1.37418593*x2 + 2.09137015*x - 0.16748867
+    } else {
+        3.0817580*x3 - 5.87338670*x2 + 3.75112997*x - 0.37001483
+    };
+    (x, y)
+}
+
+/// Function white balancing working space colors like a camera set to
+/// kelvin, so light from a black body at that temperature looks white.
+/// 6500K leaves colors unchanged.
+pub fn white_balance(kelvin: f64) -> impl Fn(&Color) -> Color {
+    let (to_xyz, from_xyz) = ColorSpace::Srgb.xyz_matrices();
+    let adaptation = bradford(blackbody_xy(kelvin), blackbody_xy(6500.0));
+    let m = multiply(from_xyz, &multiply(&adaptation, to_xyz));
+    move |c: &Color| apply(&m, c)
+}
+
+/// RGB primaries and white point colors can be expressed in. All are linear;
+/// Encoding adds the transfer functions used in image files.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ColorSpace {
+    Srgb,     // Rec.709 primaries, D65 white. The working space.
+    Rec2020,  // Wide gamut UHDTV primaries, D65 white
+    AcesCg,   // ACES AP1 primaries, ACES white
+    Xyz,      // CIE 1931 XYZ, adapted to D65 white
+}
+
+impl ColorSpace {
+    pub const ALL: [ColorSpace; 4] = [ColorSpace::Srgb, ColorSpace::Rec2020, ColorSpace::AcesCg, ColorSpace::Xyz];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            ColorSpace::Srgb => "srgb",
+            ColorSpace::Rec2020 => "rec2020",
+            ColorSpace::AcesCg => "acescg",
+            ColorSpace::Xyz => "xyz",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<ColorSpace> {
+        ColorSpace::ALL.iter().copied().find(|space| space.name() == name)
+    }
+
+    /// Chromaticities of the red, green and blue primaries and the white point
+    pub fn chromaticities(self) -> [(f64, f64); 4] {
+        match self {
+            ColorSpace::Srgb => [(0.64, 0.33), (0.30, 0.60), (0.15, 0.06), D65],
+            ColorSpace::Rec2020 => [(0.708, 0.292), (0.170, 0.797), (0.131, 0.046), D65],
+            ColorSpace::AcesCg => [(0.713, 0.293), (0.165, 0.830), (0.128, 0.044), ACES_WHITE],
+            ColorSpace::Xyz => [(1.0, 0.0), (0.0, 1.0), (0.0, 0.0), D65],
+        }
+    }
+
+    /// Matrices taking colors in this space to D65-adapted XYZ and back,
+    /// worked out once for every space
+    fn xyz_matrices(self) -> &'static (Matrix, Matrix) {
+        static MATRICES: OnceLock<[(Matrix, Matrix); 4]> = OnceLock::new();
+        let matrices = MATRICES.get_or_init(|| ColorSpace::ALL.map(|space| {
+            let m = space.to_xyz_matrix();
+            (m, invert(&m))
+        }));
+        &matrices[self as usize]
+    }
+
+    /// Matrix taking colors in this space to D65-adapted XYZ
+    fn to_xyz_matrix(self) -> Matrix {
+        if self == ColorSpace::Xyz {
+            return [[1.0, 0.0, 0.0], [0.0, 1.0, 0.0], [0.0, 0.0, 1.0]];
+        }
+
+        // Scale the primaries so that RGB (1, 1, 1) is the white point
+        let [r, g, b, white] = self.chromaticities();
+        let (r, g, b) = (xy_to_xyz(r), xy_to_xyz(g), xy_to_xyz(b));
+        let primaries = [[r.c[0], g.c[0], b.c[0]], [r.c[1], g.c[1], b.c[1]], [r.c[2], g.c[2], b.c[2]]];
+        let s = apply(&invert(&primaries), &xy_to_xyz(white));
+        let m = [
+            [r.c[0]*s.c[0], g.c[0]*s.c[1], b.c[0]*s.c[2]],
+            [r.c[1]*s.c[0], g.c[1]*s.c[1], b.c[1]*s.c[2]],
+            [r.c[2]*s.c[0], g.c[2]*s.c[1], b.c[2]*s.c[2]],
+        ];
+        if white == D65 {m} else {multiply(&bradford(white, D65), &m)}
+    }
+
+    pub fn to_xyz(self, c: &Color) -> Color {
+        apply(&self.xyz_matrices().0, c)
+    }
+
+    pub fn from_xyz(self, xyz: &Color) -> Color {
+        apply(&self.xyz_matrices().1, xyz)
+    }
 
-pub type Color = Vec3;
+    /// Converts c from this color space to another
+    pub fn convert(self, c: &Color, to: ColorSpace) -> Color {
+        if self == to {
+            return *c;
+        }
+        to.from_xyz(&self.to_xyz(c))
+    }
+
+    /// Transfer function images in this color space are usually stored with
+    pub fn default_transfer(self) -> Transfer {
+        match self {
+            ColorSpace::Srgb => Transfer::Srgb,
+            ColorSpace::Rec2020 => Transfer::Rec709,
+            ColorSpace::AcesCg | ColorSpace::Xyz => Transfer::Linear,
+        }
+    }
+}
+
+/// Non-linear curve applied to linear values to store them in an image
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Transfer {
+    Linear,
+    Srgb,        // IEC 61966-2-1 piecewise curve
+    Rec709,      // ITU-R BT.709 / BT.2020 camera curve
+    Gamma(f64),  // Pure power law
+}
+
+impl Transfer {
+    /// Linear value to stored value
+    pub fn encode(&self, x: f64) -> f64 {
+        let x = x.max(0.0);
+        match *self {
+            Transfer::Linear => x,
+            Transfer::Srgb => if x <= 0.0031308 {12.92*x} else {1.055*x.powf(1.0/2.4) - 0.055},
+            Transfer::Rec709 => if x < 0.018 {4.5*x} else {1.099*x.powf(0.45) - 0.099},
+            Transfer::Gamma(gamma) => x.powf(1.0/gamma),
+        }
+    }
+
+    /// Stored value to linear value
+    pub fn decode(&self, v: f64) -> f64 {
+        let v = v.max(0.0);
+        match *self {
+            Transfer::Linear => v,
+            Transfer::Srgb => if v <= 0.04045 {v / 12.92} else {((v + 0.055) / 1.055).powf(2.4)},
+            Transfer::Rec709 => if v < 0.081 {v / 4.5} else {((v + 0.099) / 1.099).powf(1.0/0.45)},
+            Transfer::Gamma(gamma) => v.powf(gamma),
+        }
+    }
+}
+
+/// How colors are stored in an image: in which color space and with which
+/// transfer function
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Encoding {
+    pub space: ColorSpace,
+    pub transfer: Transfer,
+}
+
+impl Encoding {
+    /// Ordinary 8-bit images
+    pub const SRGB: Encoding = Encoding {space: ColorSpace::Srgb, transfer: Transfer::Srgb};
+    /// Working space values as they are, also used for data like normal maps
+    pub const LINEAR: Encoding = Encoding {space: ColorSpace::Srgb, transfer: Transfer::Linear};
+
+    /// Color space with its usual transfer function
+    pub fn of(space: ColorSpace) -> Self {
+        Encoding {space, transfer: space.default_transfer()}
+    }
+
+    /// Working space color to stored value
+    pub fn encode(&self, c: &Color) -> Color {
+        ColorSpace::Srgb.convert(c, self.space).map(|x| self.transfer.encode(x))
+    }
+
+    /// Stored value to working space color
+    pub fn decode(&self, c: &Color) -> Color {
+        self.space.convert(&c.map(|v| self.transfer.decode(v)), ColorSpace::Srgb)
+    }
+}
 
 /// Converts a Color vector with RGB randing [0, 1] to an array of u8
 pub fn into_pixel(pixel_color: &Color) -> Rgb<u8> {
     Rgb([
-        clamp(pixel_color.x()*255.0, 0.0, 255.0).trunc() as u8,
-        clamp(pixel_color.y()*255.0, 0.0, 255.0).trunc() as u8,
-        clamp(pixel_color.z()*255.0, 0.0, 255.0).trunc() as u8,
+        clamp(pixel_color.r()*255.0, 0.0, 255.0).trunc() as u8,
+        clamp(pixel_color.g()*255.0, 0.0, 255.0).trunc() as u8,
+        clamp(pixel_color.b()*255.0, 0.0, 255.0).trunc() as u8,
     ])
 }
+
+#[cfg(test)]
+mod tests {
+    use approx::assert_relative_eq;
+    use super::*;
+
+    #[test]
+    fn test_srgb_to_xyz() {
+        // Published sRGB matrix (IEC 61966-2-1), applied to pure red
+        let xyz = ColorSpace::Srgb.to_xyz(&Color::new(1.0, 0.0, 0.0));
+        assert_relative_eq!(xyz.r(), 0.4124, epsilon = 1e-4);
+        assert_relative_eq!(xyz.g(), 0.2126, epsilon = 1e-4);
+        assert_relative_eq!(xyz.b(), 0.0193, epsilon = 1e-4);
+
+        // XYZ is adapted to D65, so white is white in both and converts back
+        assert_eq!(ColorSpace::Xyz.chromaticities()[3], D65);
+        let white = ColorSpace::Srgb.to_xyz(&Color::new(1.0, 1.0, 1.0));
+        let (x, y) = (white.r() / white.sum(), white.g() / white.sum());
+        assert_relative_eq!(x, D65.0, epsilon = 1e-9);
+        assert_relative_eq!(y, D65.1, epsilon = 1e-9);
+        let back = ColorSpace::Xyz.convert(&white, ColorSpace::Srgb);
+        (0..3).for_each(|i| assert_relative_eq!(back[i], 1.0, epsilon = 1e-9));
+    }
+
+    #[test]
+    fn test_white_balance() {
+        // Close to the published chromaticity of a 3000K black body
+        let (x, y) = blackbody_xy(3000.0);
+        assert_relative_eq!(x, 0.4369, epsilon = 1e-3);
+        assert_relative_eq!(y, 0.4041, epsilon = 1e-3);
+
+        // 6500K is neutral, and balancing for a warm light makes it look like 6500K light
+        let c = Color::new(0.2, 0.5, 0.9);
+        let neutral = white_balance(6500.0)(&c);
+        let light = |kelvin: f64| ColorSpace::Srgb.from_xyz(&xy_to_xyz(blackbody_xy(kelvin)));
+        let balanced = white_balance(3000.0)(&light(3000.0));
+        let reference = light(6500.0);
+        for i in 0..3 {
+            assert_relative_eq!(neutral[i], c[i], epsilon = 1e-9);
+            assert_relative_eq!(balanced[i] / balanced[1], reference[i] / reference[1], epsilon = 1e-3);
+        }
+    }
+
+    #[test]
+    fn test_conversions_round_trip_and_keep_white() {
+        let c = Color::new(0.2, 0.5, 0.9);
+        for space in ColorSpace::ALL {
+            let back = space.convert(&ColorSpace::Srgb.convert(&c, space), ColorSpace::Srgb);
+            for i in 0..3 {
+                assert_relative_eq!(back[i], c[i], epsilon = 1e-9);
+            }
+        }
+
+        // White stays white between D65 spaces and through the ACES white adaptation
+        for space in [ColorSpace::Rec2020, ColorSpace::AcesCg] {
+            let white = ColorSpace::Srgb.convert(&Color::WHITE, space);
+            for i in 0..3 {
+                assert_relative_eq!(white[i], 1.0, epsilon = 1e-3);
+            }
+        }
+    }
+
+    #[test]
+    fn test_transfer_round_trip() {
+        for transfer in [Transfer::Srgb, Transfer::Rec709, Transfer::Gamma(2.2)] {
+            for x in [0.0, 0.001, 0.018, 0.2, 1.0] {
+                assert_relative_eq!(transfer.decode(transfer.encode(x)), x, epsilon = 1e-12);
+            }
+        }
+        assert_relative_eq!(Transfer::Srgb.encode(0.214041), 0.5, epsilon = 1e-5);
+    }
+}
@@ -0,0 +1,217 @@
+use crate::aov::AovBuffers;
+use crate::color::Color;
+use crate::film::Film;
+use crate::vec3::Vec3;
+
+/// B3 spline used as the à-trous filter kernel
+const KERNEL: [f64; 5] = [1.0/16.0, 1.0/4.0, 3.0/8.0, 1.0/4.0, 1.0/16.0];
+
+/// Tuning of the edge-stopping functions of the denoiser
+#[derive(Debug, Clone, Copy)]
+pub struct DenoiseSettings {
+    /// Number of à-trous passes, each doubling the filter footprint
+    pub iterations: u32,
+    /// How different two colors may be before they stop being averaged,
+    /// halved on every iteration
+    pub sigma_color: f64,
+    /// How different two normals may be (distance between unit vectors)
+    pub sigma_normal: f64,
+    /// How different two depths may be, relative to the depth of the pixel
+    pub sigma_depth: f64,
+    /// How different two albedos may be
+    pub sigma_albedo: f64,
+}
+
+impl Default for DenoiseSettings {
+    fn default() -> Self {
+        DenoiseSettings {
+            iterations: 5,
+            sigma_color: 1.0,
+            sigma_normal: 0.3,
+            sigma_depth: 0.05,
+            sigma_albedo: 0.1,
+        }
+    }
+}
+
+/// Per-pixel guide values
+struct Guide {
+    valid: bool,  // Whether the pixel has any guide samples
+    normal: Vec3,
+    depth: f64,
+    albedo: Color,
+}
+
+/// Denoises film with an edge-avoiding à-trous wavelet filter
+/// (Dammertz et al. 2010) guided by the first-hit normal, depth and albedo
+/// buffers in guides
+///
+/// Color is divided by albedo before filtering so only lighting is blurred
+/// and texture detail survives. The returned film keeps the sample counts
+/// of the input. Pixels without guide samples (eg. a checkpoint resumed
+/// without rendering new samples) are left untouched.
+pub fn denoise(film: &Film, guides: &AovBuffers, settings: &DenoiseSettings) -> Film {
+    let width = film.width();
+    let height = film.height();
+
+    let guide: Vec<Guide> = pixels(width, height).map(|(x, y)| Guide {
+        valid: guides.samples_at(x, y) > 0,
+        normal: guides.normal(x, y),
+        depth: guides.depth(x, y),
+        albedo: guides.albedo(x, y),
+    }).collect();
+
+    // Demodulate albedo
+    let mut color: Vec<Color> = pixels(width, height).enumerate().map(|(i, (x, y))| {
+        film.pixel(x, y) / demodulation_factor(&guide[i].albedo)
+    }).collect();
+
+    for iteration in 0..settings.iterations {
+        let step = 1i64 << iteration;
+        let sigma_color = settings.sigma_color / (1u64 << iteration) as f64;
+        let mut filtered = color.clone();
+
+        for (i, (x, y)) in pixels(width, height).enumerate() {
+            let p = &guide[i];
+            if !p.valid {
+                continue;
+            }
+
+            let mut sum = Color::new(0.0, 0.0, 0.0);
+            let mut weight_sum = 0.0;
+            for (ky, hy) in KERNEL.iter().enumerate() {
+                for (kx, hx) in KERNEL.iter().enumerate() {
+                    let qx = (x as i64) + step*(kx as i64 - 2);
+                    let qy = (y as i64) + step*(ky as i64 - 2);
+                    if qx < 0 || qy < 0 || qx >= width as i64 || qy >= height as i64 {
+                        continue;
+                    }
+
+                    let j = (qy as usize)*(width as usize) + (qx as usize);
+                    let q = &guide[j];
+                    if !q.valid {
+                        continue;
+                    }
+
+                    let w = hx*hy
+                        * edge_stop(distance_squared(&color[i], &color[j]), sigma_color)
+                        * edge_stop((p.normal - q.normal).length_squared(), settings.sigma_normal)
+                        * edge_stop(distance_squared(&p.albedo, &q.albedo), settings.sigma_albedo)
+                        * depth_weight(p.depth, q.depth, settings.sigma_depth*(step as f64));
+                    sum += w*color[j];
+                    weight_sum += w;
+                }
+            }
+
+            // The center pixel always has a weight of at least KERNEL[2]^2
+            filtered[i] = sum / weight_sum;
+        }
+
+        color = filtered;
+    }
+
+    // Remodulate albedo and scale back up to sums of samples
+    let sum = pixels(width, height).enumerate().map(|(i, (x, y))| {
+        if guide[i].valid {
+            color[i] * demodulation_factor(&guide[i].albedo) * (film.samples_at(x, y) as f64)
+        } else {
+            film.sum()[i]
+        }
+    }).collect();
+
+    Film::from_raw(film.region(), sum, film.samples().to_vec()).unwrap()
+}
+
+/// Iterates over pixel coordinates row by row
+fn pixels(width: u32, height: u32) -> impl Iterator<Item = (u32, u32)> {
+    (0..height).flat_map(move |y| (0..width).map(move |x| (x, y)))
+}
+
+/// Albedo with near-black channels replaced by 1 so they aren't demodulated
+fn demodulation_factor(albedo: &Color) -> Color {
+    let f = |c: f64| if c < 1e-3 {1.0} else {c};
+    albedo.map(f)
+}
+
+/// Squared euclidean distance between two colors
+fn distance_squared(a: &Color, b: &Color) -> f64 {
+    let d = a - b;
+    d.r()*d.r() + d.g()*d.g() + d.b()*d.b()
+}
+
+fn edge_stop(distance_squared: f64, sigma: f64) -> f64 {
+    (-distance_squared / (sigma*sigma)).exp()
+}
+
+/// Weight for two depths, infinite depth meaning the ray escaped to the sky
+fn depth_weight(p: f64, q: f64, sigma: f64) -> f64 {
+    match (p.is_finite(), q.is_finite()) {
+        (true, true) => (-(p - q).abs() / (sigma*p.max(1e-8))).exp(),
+        (false, false) => 1.0,
+        _ => 0.0,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::integrator::{FirstHit, PathSample};
+    use crate::random_f64;
+
+    /// Flat surface facing the camera whose left and right halves have
+    /// different normals, lit with a noisy constant radiance
+    fn noisy_scene(width: u32, height: u32) -> (Film, AovBuffers) {
+        let mut film = Film::new(width, height);
+        let mut aovs = AovBuffers::new(width, height);
+        for y in 0..height {
+            for x in 0..width {
+                let left = x < width/2;
+                let radiance = if left {0.2} else {0.8};
+                let noisy = Color::new(1.0, 1.0, 1.0)*radiance*(0.5 + random_f64());
+
+                let sample = PathSample {
+                    direct: noisy,
+                    first_hit: Some(FirstHit {
+                        distance: 1.0,
+                        normal: if left {Vec3::new(0.0, 0.0, 1.0)} else {Vec3::new(1.0, 0.0, 0.0)},
+                        albedo: Color::new(1.0, 1.0, 1.0),
+                        material_id: 1,
+                        object_id: 1,
+                    }),
+                    ..PathSample::default()
+                };
+                film.add_sample(x, y, &sample.radiance());
+                aovs.add_sample(x, y, &sample);
+            }
+        }
+        (film, aovs)
+    }
+
+    fn variance(film: &Film, xs: std::ops::Range<u32>) -> f64 {
+        let values: Vec<f64> = (0..film.height())
+            .flat_map(|y| xs.clone().map(move |x| (x, y)))
+            .map(|(x, y)| film.pixel(x, y).r())
+            .collect();
+        let mean = values.iter().sum::<f64>() / values.len() as f64;
+        values.iter().map(|v| (v - mean)*(v - mean)).sum::<f64>() / values.len() as f64
+    }
+
+    #[test]
+    fn test_reduces_noise() {
+        let (film, aovs) = noisy_scene(32, 32);
+        let denoised = denoise(&film, &aovs, &DenoiseSettings::default());
+
+        assert!(variance(&denoised, 0..16) < 0.25*variance(&film, 0..16));
+        assert!(variance(&denoised, 16..32) < 0.25*variance(&film, 16..32));
+    }
+
+    #[test]
+    fn test_preserves_normal_edges() {
+        let (film, aovs) = noisy_scene(32, 32);
+        let denoised = denoise(&film, &aovs, &DenoiseSettings::default());
+
+        // Brightness on either side of the edge must not bleed across it
+        assert!(denoised.pixel(15, 16).r() < 0.35);
+        assert!(denoised.pixel(16, 16).r() > 0.6);
+    }
+}
@@ -0,0 +1,120 @@
+use image::RgbImage;
+
+use crate::color::{Color, Encoding};
+use crate::write_pixel;
+
+/// Rectangle of pixels within the full image, (x, y) being its top left corner
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Region {
+    pub x: u32,
+    pub y: u32,
+    pub width: u32,
+    pub height: u32,
+}
+
+impl Region {
+    pub fn new(x: u32, y: u32, width: u32, height: u32) -> Self {
+        Region {x, y, width, height}
+    }
+
+    /// Whether the region lies entirely within an image of the given size
+    pub fn fits_within(&self, width: u32, height: u32) -> bool {
+        self.width > 0 && self.height > 0
+            && (self.x as u64) + (self.width as u64) <= width as u64
+            && (self.y as u64) + (self.height as u64) <= height as u64
+    }
+}
+
+/// Floating point framebuffer that accumulates radiance samples
+///
+/// Pixels are stored row by row with (0, 0) at the top left, which is the
+/// same layout as the image crate. A film can cover just a region of the
+/// full image, in which case film pixel (0, 0) is image pixel
+/// (region.x, region.y).
+#[derive(Debug, Clone)]
+pub struct Film {
+    region: Region,
+    sum: Vec<Color>,
+    samples: Vec<u32>,
+}
+
+impl Film {
+    pub fn new(width: u32, height: u32) -> Self {
+        Self::new_region(Region::new(0, 0, width, height))
+    }
+
+    /// Film covering only region of the full image
+    pub fn new_region(region: Region) -> Self {
+        let n_pixels = (region.width as usize)*(region.height as usize);
+        Film {
+            region,
+            sum: vec![Color::new(0.0, 0.0, 0.0); n_pixels],
+            samples: vec![0; n_pixels],
+        }
+    }
+
+    /// Rebuilds a Film from raw accumulation buffers
+    ///
+    /// Returns None if the buffer lengths don't match the size of region.
+    pub fn from_raw(region: Region, sum: Vec<Color>, samples: Vec<u32>) -> Option<Self> {
+        let n_pixels = (region.width as usize)*(region.height as usize);
+        if sum.len() != n_pixels || samples.len() != n_pixels {
+            return None;
+        }
+        Some(Film {region, sum, samples})
+    }
+
+    pub fn width(&self) -> u32 {self.region.width}
+    pub fn height(&self) -> u32 {self.region.height}
+
+    /// Part of the full image covered by the film
+    pub fn region(&self) -> Region {self.region}
+
+    /// Sum of all samples taken for each pixel
+    pub fn sum(&self) -> &[Color] {&self.sum}
+
+    /// Number of samples taken for each pixel
+    pub fn samples(&self) -> &[u32] {&self.samples}
+
+    fn index(&self, x: u32, y: u32) -> usize {
+        (y as usize)*(self.region.width as usize) + (x as usize)
+    }
+
+    /// Adds one radiance sample to pixel (x, y)
+    pub fn add_sample(&mut self, x: u32, y: u32, sample: &Color) {
+        let i = self.index(x, y);
+        self.sum[i] += sample;
+        self.samples[i] += 1;
+    }
+
+    pub fn samples_at(&self, x: u32, y: u32) -> u32 {
+        self.samples[self.index(x, y)]
+    }
+
+    /// Fewest samples taken by any pixel
+    pub fn min_samples(&self) -> u32 {
+        self.samples.iter().copied().min().unwrap_or(0)
+    }
+
+    /// Mean radiance of pixel (x, y)
+    pub fn pixel(&self, x: u32, y: u32) -> Color {
+        let i = self.index(x, y);
+        if self.samples[i] == 0 {
+            Color::new(0.0, 0.0, 0.0)
+        } else {
+            self.sum[i] / (self.samples[i] as f64)
+        }
+    }
+
+    /// Encodes and quantizes the film into an 8-bit image
+    pub fn to_image(&self, encoding: &Encoding) -> RgbImage {
+        let mut img = RgbImage::new(self.width(), self.height());
+        for y in 0..self.height() {
+            for x in 0..self.width() {
+                let i = self.index(x, y);
+                write_pixel(&mut img, &self.sum[i], x, y, self.samples[i].max(1), encoding);
+            }
+        }
+        img
+    }
+}
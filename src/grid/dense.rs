@@ -0,0 +1,100 @@
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use crate::invalid_data;
+use crate::grid::Grid;
+use crate::vec3::Vec3;
+
+/// Grid storing every voxel
+pub struct DenseGrid {
+    dims: [usize; 3],
+    data: Vec<f32>,  // x varying fastest, then y, then z
+    max_value: f64,
+    active_bounds: Option<(Vec3, Vec3)>,
+}
+
+impl DenseGrid {
+    /// Grid of the given size holding data, x varying fastest
+    pub fn new(dims: [usize; 3], data: Vec<f32>) -> io::Result<Self> {
+        if data.len() != dims[0]*dims[1]*dims[2] {
+            return Err(invalid_data(&format!("expected {} values for a {}x{}x{} grid, got {}",
+                dims[0]*dims[1]*dims[2], dims[0], dims[1], dims[2], data.len())));
+        }
+
+        let mut grid = DenseGrid {dims, data, max_value: 0.0, active_bounds: None};
+        grid.max_value = grid.data.iter().fold(0.0_f32, |m, v| m.max(*v)) as f64;
+        grid.active_bounds = grid.find_active_bounds();
+        Ok(grid)
+    }
+
+    /// Fills a grid with the value of f at every voxel (i, j, k)
+    pub fn from_fn<F: Fn(usize, usize, usize) -> f64>(dims: [usize; 3], f: F) -> Self {
+        let mut data = Vec::with_capacity(dims[0]*dims[1]*dims[2]);
+        for k in 0..dims[2] {
+            for j in 0..dims[1] {
+                for i in 0..dims[0] {
+                    data.push(f(i, j, k) as f32);
+                }
+            }
+        }
+        Self::new(dims, data).unwrap()
+    }
+
+    /// Loads headerless little-endian 32-bit floats, x varying fastest
+    pub fn open_raw<P: AsRef<Path>>(path: P, dims: [usize; 3]) -> io::Result<Self> {
+        let bytes = fs::read(path)?;
+        if bytes.len() % 4 != 0 {
+            return Err(invalid_data("raw grid size is not a multiple of 4 bytes"));
+        }
+        let data = bytes.chunks_exact(4).map(|b| f32::from_le_bytes([b[0], b[1], b[2], b[3]])).collect();
+        Self::new(dims, data)
+    }
+
+    fn find_active_bounds(&self) -> Option<(Vec3, Vec3)> {
+        let [nx, ny, nz] = self.dims;
+        let mut min = [usize::MAX; 3];
+        let mut max = [0; 3];
+        for k in 0..nz {
+            for j in 0..ny {
+                for i in 0..nx {
+                    if self.data[(k*ny + j)*nx + i] != 0.0 {
+                        for (axis, c) in [i, j, k].into_iter().enumerate() {
+                            min[axis] = min[axis].min(c);
+                            max[axis] = max[axis].max(c + 1);
+                        }
+                    }
+                }
+            }
+        }
+        if min[0] == usize::MAX {
+            return None;
+        }
+        Some((
+            Vec3::new(min[0] as f64, min[1] as f64, min[2] as f64),
+            Vec3::new(max[0] as f64, max[1] as f64, max[2] as f64),
+        ))
+    }
+}
+
+impl Grid for DenseGrid {
+    fn dims(&self) -> [usize; 3] {
+        self.dims
+    }
+
+    fn voxel(&self, i: i64, j: i64, k: i64) -> f64 {
+        let [nx, ny, nz] = self.dims;
+        if i < 0 || j < 0 || k < 0 || i >= nx as i64 || j >= ny as i64 || k >= nz as i64 {
+            return 0.0;
+        }
+        self.data[((k as usize)*ny + j as usize)*nx + i as usize] as f64
+    }
+
+    fn max_value(&self) -> f64 {
+        self.max_value
+    }
+
+    fn active_bounds(&self) -> Option<(Vec3, Vec3)> {
+        self.active_bounds
+    }
+}
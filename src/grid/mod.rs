@@ -0,0 +1,143 @@
+pub mod dense;
+pub mod sparse;
+
+// Re-export structs that implement Grid
+pub use dense::DenseGrid;
+pub use sparse::SparseGrid;
+
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::rc::Rc;
+
+use crate::{invalid_data, parse_number};
+use crate::vec3::Vec3;
+
+/// Scalar field sampled on a regular grid of voxels, like the density of
+/// smoke or the temperature of fire
+///
+/// Positions are in voxel coordinates: voxel (i, j, k) covers
+/// [i, i + 1] x [j, j + 1] x [k, k + 1] and its value is at its centre.
+pub trait Grid {
+    /// Number of voxels along x, y and z
+    fn dims(&self) -> [usize; 3];
+
+    /// Value of voxel (i, j, k), 0 outside the grid
+    fn voxel(&self, i: i64, j: i64, k: i64) -> f64;
+
+    /// Largest value of any voxel
+    fn max_value(&self) -> f64;
+
+    /// Smallest box, in voxel coordinates, holding every non-zero voxel.
+    /// None if the grid is empty. May scan the whole grid.
+    fn active_bounds(&self) -> Option<(Vec3, Vec3)>;
+
+    /// Value at p, trilinearly interpolated between voxel centres
+    fn lookup(&self, p: &Vec3) -> f64 {
+        let (x, y, z) = (p.x() - 0.5, p.y() - 0.5, p.z() - 0.5);
+        let (i, j, k) = (x.floor(), y.floor(), z.floor());
+        let (tx, ty, tz) = (x - i, y - j, z - k);
+        let (i, j, k) = (i as i64, j as i64, k as i64);
+
+        let lerp = |a: f64, b: f64, t: f64| (1.0 - t)*a + t*b;
+        let row = |j: i64, k: i64| lerp(self.voxel(i, j, k), self.voxel(i + 1, j, k), tx);
+        let slice = |k: i64| lerp(row(j, k), row(j + 1, k), ty);
+        lerp(slice(k), slice(k + 1), tz)
+    }
+}
+
+/// Loads a grid from a text file
+///
+/// The first line that isn't blank or a # comment gives the layout and
+/// the size of the grid. A dense grid lists every value, x varying fastest:
+///
+/// ```text
+/// dense 2 2 1
+/// 0.0 0.5
+/// 1.0 0.25
+/// ```
+///
+/// while a sparse grid lists the non-zero voxels, one "i j k value" per line:
+///
+/// ```text
+/// sparse 64 64 64
+/// 31 20 31 0.8
+/// ```
+pub fn open<P: AsRef<Path>>(path: P) -> io::Result<Rc<dyn Grid>> {
+    parse(&fs::read_to_string(path)?)
+}
+
+fn parse(text: &str) -> io::Result<Rc<dyn Grid>> {
+    let mut lines = text.lines().map(|line| line.trim()).filter(|line| !line.is_empty() && !line.starts_with('#'));
+    let header: Vec<&str> = lines.next().ok_or_else(|| invalid_data("empty grid file"))?.split_whitespace().collect();
+    if header.len() != 4 {
+        return Err(invalid_data("expected a header of the form '<dense|sparse> nx ny nz'"));
+    }
+    let dims = [parse_number(header[1])?, parse_number(header[2])?, parse_number(header[3])?];
+
+    let mut values = lines.flat_map(|line| line.split_whitespace());
+    match header[0] {
+        "dense" => {
+            let data = values.map(parse_number).collect::<io::Result<Vec<f32>>>()?;
+            Ok(Rc::new(DenseGrid::new(dims, data)?))
+        },
+        "sparse" => {
+            let mut grid = SparseGrid::new(dims);
+            loop {
+                let voxel: Vec<&str> = values.by_ref().take(4).collect();
+                match voxel.len() {
+                    0 => break,
+                    4 => {
+                        let (i, j, k): (usize, usize, usize) = (parse_number(voxel[0])?, parse_number(voxel[1])?, parse_number(voxel[2])?);
+                        if i >= dims[0] || j >= dims[1] || k >= dims[2] {
+                            return Err(invalid_data(&format!("voxel {} {} {} is outside the grid", i, j, k)));
+                        }
+                        grid.set(i, j, k, parse_number(voxel[3])?);
+                    },
+                    _ => return Err(invalid_data("expected 'i j k value' for every voxel")),
+                }
+            }
+            Ok(Rc::new(grid))
+        },
+        layout => Err(invalid_data(&format!("unknown grid layout '{}'", layout))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use approx::assert_relative_eq;
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        let dense = parse("# A tiny grid\ndense 2 2 1\n0.0 0.5\n1.0 0.25\n").unwrap();
+        assert_eq!(dense.dims(), [2, 2, 1]);
+        assert_eq!(dense.voxel(1, 0, 0), 0.5);
+        assert_eq!(dense.voxel(0, 1, 0), 1.0);
+        assert_eq!(dense.voxel(2, 0, 0), 0.0);
+        assert_eq!(dense.max_value(), 1.0);
+
+        let sparse = parse("sparse 64 64 64\n31 20 31 0.8\n0 0 63 0.5\n").unwrap();
+        assert_relative_eq!(sparse.voxel(31, 20, 31), 0.8, epsilon = 1e-6);
+        assert_eq!(sparse.voxel(31, 20, 30), 0.0);
+        let (min, max) = sparse.active_bounds().unwrap();
+        assert_eq!([min.x(), min.y(), min.z()], [0.0, 0.0, 31.0]);
+        assert_eq!([max.x(), max.y(), max.z()], [32.0, 21.0, 64.0]);
+
+        assert!(parse("dense 2 2 2\n1 2 3\n").is_err());
+        assert!(parse("sparse 4 4 4\n1 2 3\n").is_err());
+        assert!(parse("sparse 4 4 4\n1 2 9 1.0\n").is_err());
+    }
+
+    #[test]
+    fn test_lookup_interpolates() {
+        let grid = DenseGrid::new([2, 1, 1], vec![0.0, 1.0]).unwrap();
+        assert_relative_eq!(grid.lookup(&Vec3::new(0.5, 0.5, 0.5)), 0.0);
+        assert_relative_eq!(grid.lookup(&Vec3::new(1.25, 0.5, 0.5)), 0.75);
+        assert_relative_eq!(grid.lookup(&Vec3::new(1.5, 0.5, 0.5)), 1.0);
+
+        // Sparse grids hold the same values as the dense grid they came from
+        let sparse = SparseGrid::from_grid(&grid);
+        assert_relative_eq!(sparse.lookup(&Vec3::new(1.25, 0.5, 0.5)), 0.75);
+    }
+}
@@ -0,0 +1,101 @@
+use std::collections::HashMap;
+
+use crate::grid::Grid;
+use crate::vec3::Vec3;
+
+/// Voxels per side of a brick
+const BRICK_SIZE: usize = 8;
+
+/// Grid storing only the bricks of 8x8x8 voxels that have a non-zero
+/// voxel, like the leaf nodes of OpenVDB, for large mostly empty volumes
+pub struct SparseGrid {
+    dims: [usize; 3],
+    bricks: HashMap<[i64; 3], Box<[f32; BRICK_SIZE*BRICK_SIZE*BRICK_SIZE]>>,
+    max_value: f64,
+}
+
+impl SparseGrid {
+    /// Grid of the given size with every voxel 0
+    pub fn new(dims: [usize; 3]) -> Self {
+        SparseGrid {dims, bricks: HashMap::new(), max_value: 0.0}
+    }
+
+    /// Sparse copy of another grid
+    pub fn from_grid(grid: &dyn Grid) -> Self {
+        let dims = grid.dims();
+        let mut sparse = SparseGrid::new(dims);
+        for k in 0..dims[2] {
+            for j in 0..dims[1] {
+                for i in 0..dims[0] {
+                    sparse.set(i, j, k, grid.voxel(i as i64, j as i64, k as i64) as f32);
+                }
+            }
+        }
+        sparse
+    }
+
+    /// Sets voxel (i, j, k), ignored outside the grid
+    pub fn set(&mut self, i: usize, j: usize, k: usize, value: f32) {
+        if i >= self.dims[0] || j >= self.dims[1] || k >= self.dims[2] {
+            return;
+        }
+        let (key, index) = Self::locate(i as i64, j as i64, k as i64);
+        if value == 0.0 && !self.bricks.contains_key(&key) {
+            return;
+        }
+        let brick = self.bricks.entry(key).or_insert_with(|| Box::new([0.0; BRICK_SIZE*BRICK_SIZE*BRICK_SIZE]));
+        brick[index] = value;
+        self.max_value = self.max_value.max(value as f64);
+    }
+
+    /// Number of bricks allocated
+    pub fn brick_count(&self) -> usize {
+        self.bricks.len()
+    }
+
+    /// Brick holding voxel (i, j, k) and the index of the voxel within it
+    fn locate(i: i64, j: i64, k: i64) -> ([i64; 3], usize) {
+        let size = BRICK_SIZE as i64;
+        let key = [i.div_euclid(size), j.div_euclid(size), k.div_euclid(size)];
+        let (x, y, z) = (i.rem_euclid(size) as usize, j.rem_euclid(size) as usize, k.rem_euclid(size) as usize);
+        (key, (z*BRICK_SIZE + y)*BRICK_SIZE + x)
+    }
+}
+
+impl Grid for SparseGrid {
+    fn dims(&self) -> [usize; 3] {
+        self.dims
+    }
+
+    fn voxel(&self, i: i64, j: i64, k: i64) -> f64 {
+        let (key, index) = Self::locate(i, j, k);
+        self.bricks.get(&key).map_or(0.0, |brick| brick[index] as f64)
+    }
+
+    fn max_value(&self) -> f64 {
+        self.max_value
+    }
+
+    fn active_bounds(&self) -> Option<(Vec3, Vec3)> {
+        let mut bounds: Option<(Vec3, Vec3)> = None;
+        for (key, brick) in &self.bricks {
+            for (index, value) in brick.iter().enumerate() {
+                if *value == 0.0 {
+                    continue;
+                }
+                let (x, y, z) = (index % BRICK_SIZE, (index / BRICK_SIZE) % BRICK_SIZE, index / (BRICK_SIZE*BRICK_SIZE));
+                let corner = |axis: usize, c: usize| (key[axis]*BRICK_SIZE as i64 + c as i64) as f64;
+                let min = Vec3::new(corner(0, x), corner(1, y), corner(2, z));
+                let max = Vec3::new(min.x() + 1.0, min.y() + 1.0, min.z() + 1.0);
+                bounds = Some(match bounds {
+                    Some((lo, hi)) => (
+                        Vec3::new(lo.x().min(min.x()), lo.y().min(min.y()), lo.z().min(min.z())),
+                        Vec3::new(hi.x().max(max.x()), hi.y().max(max.y()), hi.z().max(max.z())),
+                    ),
+                    None => (min, max),
+                });
+            }
+        }
+        bounds
+    }
+}
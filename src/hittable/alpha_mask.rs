@@ -0,0 +1,123 @@
+use std::rc::Rc;
+
+use crate::hittable::{HitRecord, Hittable};
+use crate::random_f64;
+use crate::ray::Ray;
+use crate::texture::Texture;
+
+/// How the alpha texture decides whether a hit counts
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum AlphaMode {
+    Threshold(f64),  // Hits with alpha below the threshold are ignored
+    Stochastic,      // Hits are kept with probability alpha, for soft edges
+}
+
+/// Cuts holes in any hittable where an alpha texture is low, for leaves,
+/// fences and decals
+///
+/// Rays pass through the holes and carry on to whatever is behind,
+/// including the far side of the same object.
+pub struct AlphaMask {
+    object: Rc<dyn Hittable>,
+    alpha: Rc<dyn Texture>,
+    mode: AlphaMode,
+}
+
+impl AlphaMask {
+    /// Distance past an ignored hit, in world units, so the same hit isn't
+    /// found again
+    const EPSILON: f64 = 0.001;
+
+    pub fn new(object: &Rc<dyn Hittable>, alpha: &Rc<dyn Texture>, mode: AlphaMode) -> Self {
+        AlphaMask {
+            object: Rc::clone(object),
+            alpha: Rc::clone(alpha),
+            mode,
+        }
+    }
+
+    fn is_opaque(&self, hit_record: &HitRecord) -> bool {
+        let alpha = self.alpha.scalar(hit_record.u, hit_record.v, hit_record.p());
+        match self.mode {
+            AlphaMode::Threshold(threshold) => alpha >= threshold,
+            AlphaMode::Stochastic => alpha >= 1.0 || random_f64() < alpha,
+        }
+    }
+}
+
+impl Hittable for AlphaMask {
+    fn hit(&self, r: &Ray, t_min: f64, t_max: f64) -> Option<HitRecord> {
+        // Rays needn't be unit length, so the offset is scaled into t
+        let offset = Self::EPSILON / r.dir().length();
+        let mut t_min = t_min;
+        loop {
+            let hit_record = self.object.hit(r, t_min, t_max)?;
+            if self.is_opaque(&hit_record) {
+                return Some(hit_record);
+            }
+            t_min = hit_record.t + offset;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use approx::assert_relative_eq;
+    use super::*;
+    use crate::INFINITY;
+    use crate::color::Color;
+    use crate::hittable::sphere::Sphere;
+    use crate::material::{Lambertian, Material};
+    use crate::texture::SolidColor;
+    use crate::vec3::{Point3, Vec3};
+
+    /// Opaque on the far half (z < 0) of the sphere only
+    struct BackHalf;
+
+    impl Texture for BackHalf {
+        fn value(&self, _u: f64, _v: f64, p: &Point3) -> Color {
+            if p.z() < 0.0 {Color::new(1.0, 1.0, 1.0)} else {Color::new(0.0, 0.0, 0.0)}
+        }
+    }
+
+    fn sphere() -> Rc<dyn Hittable> {
+        let material: Rc<dyn Material> = Rc::new(Lambertian::new(Color::new(0.5, 0.5, 0.5)));
+        Rc::new(Sphere::new(Point3::new(0.0, 0.0, 0.0), 1.0, &material))
+    }
+
+    #[test]
+    fn test_threshold() {
+        // The ray is so long that the two sides of the sphere are closer than
+        // EPSILON in t, which mustn't skip the far side
+        let r = Ray::new(Point3::new(0.0, 0.0, 50.0), Vec3::new(0.0, 0.0, -1e4));
+        let alpha: Rc<dyn Texture> = Rc::new(BackHalf);
+        let mask = AlphaMask::new(&sphere(), &alpha, AlphaMode::Threshold(0.5));
+        let hit_record = mask.hit(&r, 0.001, INFINITY).unwrap();
+        assert_relative_eq!(hit_record.p().z(), -1.0, epsilon = 1e-9);
+
+        // Constant alpha is all or nothing
+        let faint: Rc<dyn Texture> = Rc::new(SolidColor::new(Color::new(0.3, 0.3, 0.3)));
+        assert!(AlphaMask::new(&sphere(), &faint, AlphaMode::Threshold(0.5)).hit(&r, 0.001, INFINITY).is_none());
+        assert!(AlphaMask::new(&sphere(), &faint, AlphaMode::Threshold(0.2)).hit(&r, 0.001, INFINITY).is_some());
+    }
+
+    #[test]
+    fn test_stochastic() {
+        // Each side is kept with probability alpha, so a ray through the
+        // centre hits the near side 30% of the time and the far side 21%
+        let faint: Rc<dyn Texture> = Rc::new(SolidColor::new(Color::new(0.3, 0.3, 0.3)));
+        let mask = AlphaMask::new(&sphere(), &faint, AlphaMode::Stochastic);
+        let r = Ray::new(Point3::new(0.0, 0.0, 50.0), Vec3::new(0.0, 0.0, -1e4));
+        let n = 100_000;
+        let (mut near, mut far) = (0, 0);
+        for _ in 0..n {
+            match mask.hit(&r, 0.001, INFINITY) {
+                Some(hit_record) if hit_record.front_face => near += 1,
+                Some(_) => far += 1,
+                None => (),
+            }
+        }
+        assert_relative_eq!(near as f64 / n as f64, 0.3, epsilon = 0.01);
+        assert_relative_eq!(far as f64 / n as f64, 0.21, epsilon = 0.01);
+    }
+}
@@ -0,0 +1,140 @@
+use std::rc::Rc;
+
+use crate::animation::Keyframes;
+use crate::hittable::{HitRecord, Hittable};
+use crate::ray::Ray;
+use crate::vec3::Vec3;
+
+/// Moves, rotates and scales any hittable over time
+///
+/// Rays are taken into the object's own space at their time instead of
+/// rebuilding the object every frame, so whatever the object precomputed
+/// (eg. the bounds of a volume) is kept for the whole animation.
+pub struct Animated {
+    object: Rc<dyn Hittable>,
+    translation: Keyframes<Vec3>,
+    rotation: Keyframes<Vec3>,  // Euler angles in degrees, applied about x, then y, then z
+    scale: Keyframes<f64>,
+}
+
+impl Animated {
+    /// object standing still, until transforms are added
+    pub fn new(object: &Rc<dyn Hittable>) -> Self {
+        Animated {
+            object: Rc::clone(object),
+            translation: Keyframes::constant(Vec3::new(0.0, 0.0, 0.0)),
+            rotation: Keyframes::constant(Vec3::new(0.0, 0.0, 0.0)),
+            scale: Keyframes::constant(1.0),
+        }
+    }
+
+    pub fn translation(mut self, translation: Keyframes<Vec3>) -> Self {
+        self.translation = translation;
+        self
+    }
+
+    /// Rotation in degrees about the x, y and z axes (in that order),
+    /// around the object's origin
+    pub fn rotation(mut self, rotation: Keyframes<Vec3>) -> Self {
+        self.rotation = rotation;
+        self
+    }
+
+    /// Uniform scale about the object's origin, which keeps normals and
+    /// the shape of spheres intact
+    pub fn scale(mut self, scale: Keyframes<f64>) -> Self {
+        self.scale = scale;
+        self
+    }
+}
+
+impl Hittable for Animated {
+    fn hit(&self, r: &Ray, t_min: f64, t_max: f64) -> Option<HitRecord> {
+        let time = r.time();
+        let translation = self.translation.at(time);
+        let angles = self.rotation.at(time);
+        let scale = self.scale.at(time);
+        if scale == 0.0 {
+            return None;
+        }
+
+        // The object space ray is scaled with the object, so it reaches the
+        // same points at the same t
+        let origin = unrotate(&(r.origin() - translation), &angles) / scale;
+        let dir = unrotate(r.dir(), &angles) / scale;
+        let local = Ray::new(origin, dir).with_wavelength(r.wavelength()).with_time(time);
+
+        let hit_record = self.object.hit(&local, t_min, t_max)?;
+        Some(HitRecord {
+            p: translation + scale*rotate(&hit_record.p, &angles),
+            normal: rotate(&hit_record.normal, &angles),
+            geometric_normal: rotate(&hit_record.geometric_normal, &angles),
+            dpdu: scale*rotate(&hit_record.dpdu, &angles),
+            dpdv: scale*rotate(&hit_record.dpdv, &angles),
+            ..hit_record
+        })
+    }
+}
+
+/// v rotated by angles (in degrees) about x, then y, then z
+fn rotate(v: &Vec3, angles: &Vec3) -> Vec3 {
+    let v = rotate_x(v, angles.x().to_radians());
+    let v = rotate_y(&v, angles.y().to_radians());
+    rotate_z(&v, angles.z().to_radians())
+}
+
+/// Undoes rotate
+fn unrotate(v: &Vec3, angles: &Vec3) -> Vec3 {
+    let v = rotate_z(v, -angles.z().to_radians());
+    let v = rotate_y(&v, -angles.y().to_radians());
+    rotate_x(&v, -angles.x().to_radians())
+}
+
+fn rotate_x(v: &Vec3, theta: f64) -> Vec3 {
+    let (sin, cos) = theta.sin_cos();
+    Vec3::new(v.x(), cos*v.y() - sin*v.z(), sin*v.y() + cos*v.z())
+}
+
+fn rotate_y(v: &Vec3, theta: f64) -> Vec3 {
+    let (sin, cos) = theta.sin_cos();
+    Vec3::new(cos*v.x() + sin*v.z(), v.y(), -sin*v.x() + cos*v.z())
+}
+
+fn rotate_z(v: &Vec3, theta: f64) -> Vec3 {
+    let (sin, cos) = theta.sin_cos();
+    Vec3::new(cos*v.x() - sin*v.y(), sin*v.x() + cos*v.y(), v.z())
+}
+
+#[cfg(test)]
+mod tests {
+    use approx::assert_relative_eq;
+    use super::*;
+    use crate::INFINITY;
+    use crate::animation::Interpolation;
+    use crate::color::Color;
+    use crate::hittable::sphere::Sphere;
+    use crate::material::{Lambertian, Material};
+    use crate::vec3::Point3;
+
+    #[test]
+    fn test_translation_follows_ray_time() {
+        // A unit sphere moving from the origin to x = 2 over a second
+        let material: Rc<dyn Material> = Rc::new(Lambertian::new(Color::new(0.5, 0.5, 0.5)));
+        let sphere: Rc<dyn Hittable> = Rc::new(Sphere::new(Point3::new(0.0, 0.0, 0.0), 1.0, &material));
+        let moving = Animated::new(&sphere).translation(
+            Keyframes::new(Interpolation::Linear).key(0.0, Vec3::new(0.0, 0.0, 0.0)).key(1.0, Vec3::new(2.0, 0.0, 0.0))
+        );
+
+        let r = |x: f64, time: f64| Ray::new(Point3::new(x, 0.0, 5.0), Vec3::new(0.0, 0.0, -1.0)).with_time(time);
+        assert!(moving.hit(&r(2.0, 0.0), 0.001, INFINITY).is_none());
+        let hit_record = moving.hit(&r(2.0, 1.0), 0.001, INFINITY).unwrap();
+        assert_relative_eq!(hit_record.p().x(), 2.0, epsilon = 1e-9);
+        assert_relative_eq!(hit_record.p().z(), 1.0, epsilon = 1e-9);
+        assert_relative_eq!(hit_record.t, 4.0, epsilon = 1e-9);
+
+        // Halfway through, the ray grazes the sphere off centre
+        let hit_record = moving.hit(&r(1.5, 0.5), 0.001, INFINITY).unwrap();
+        assert_relative_eq!(hit_record.p().z(), 0.75_f64.sqrt(), epsilon = 1e-9);
+        assert_relative_eq!(hit_record.normal().x(), 0.5, epsilon = 1e-9);
+    }
+}
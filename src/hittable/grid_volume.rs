@@ -0,0 +1,270 @@
+use std::rc::Rc;
+
+use crate::color::Color;
+use crate::grid::Grid;
+use crate::hittable::{HitRecord, Hittable, object_id};
+use crate::material::{BsdfFlags, BsdfSample, Material};
+use crate::medium::HenyeyGreenstein;
+use crate::random_f64;
+use crate::ray::Ray;
+use crate::stats::{self, Counter};
+use crate::vec3::{Point3, Vec3};
+
+/// Smoke, clouds and fire: a participating medium whose density comes from
+/// a voxel grid
+///
+/// Rays are "hit" where they collide with the medium, found by delta
+/// tracking against the densest voxel, and the collision scatters light
+/// with a Henyey-Greenstein phase function. The volume is bounded by the
+/// non-empty voxels of the grid, placed with voxel (0, 0, 0) at origin.
+///
+/// Shadow rays, once lights are sampled directly, should use transmittance
+/// (ratio tracking) rather than hit, which only says whether a ray got through.
+pub struct GridVolume {
+    density: Rc<dyn Grid>,
+    origin: Point3,
+    voxel_size: f64,
+    bounds: Option<(Point3, Point3)>,  // World space box around where the density isn't 0
+    density_scale: f64,  // Extinction coefficient, per unit length, of a density of 1
+    albedo: Color,
+    phase: HenyeyGreenstein,
+    emission: Option<(Rc<dyn Grid>, Color)>,
+    material: Rc<dyn Material>,
+}
+
+impl GridVolume {
+    pub fn new(density: &Rc<dyn Grid>, origin: Point3, voxel_size: f64) -> Self {
+        let albedo = Color::gray(0.9);
+        let phase = HenyeyGreenstein::new(0.0);
+        // Interpolation spreads every voxel half a voxel past its edges
+        let bounds = density.active_bounds().map(|(min, max)| (origin + voxel_size*(min - 0.5), origin + voxel_size*(max + 0.5)));
+        GridVolume {
+            density: Rc::clone(density),
+            origin,
+            voxel_size,
+            bounds,
+            density_scale: 1.0,
+            albedo,
+            phase,
+            emission: None,
+            material: Rc::new(VolumeScatter {albedo, phase, emission: None, origin, voxel_size}),
+        }
+    }
+
+    /// Extinction coefficient, per unit length, where the density is 1
+    pub fn density_scale(mut self, scale: f64) -> Self {
+        self.density_scale = scale;
+        self
+    }
+
+    /// Fraction of the light colliding with the medium that is scattered
+    /// rather than absorbed
+    pub fn albedo(mut self, albedo: Color) -> Self {
+        self.albedo = albedo;
+        self.update_material();
+        self
+    }
+
+    /// Mean cosine of the scattering angle, in (-1, 1). Clouds scatter
+    /// forward (around 0.85), the default is isotropic.
+    pub fn anisotropy(mut self, g: f64) -> Self {
+        self.phase = HenyeyGreenstein::new(g);
+        self.update_material();
+        self
+    }
+
+    /// Makes the medium glow with color times the value of a grid laid out
+    /// like the density grid, for fire
+    ///
+    /// The glow is the radiance of the absorbing part of the medium, so it
+    /// only shows where there's density.
+    pub fn emission(mut self, grid: &Rc<dyn Grid>, color: Color) -> Self {
+        self.emission = Some((Rc::clone(grid), color));
+        self.update_material();
+        self
+    }
+
+    fn update_material(&mut self) {
+        self.material = Rc::new(VolumeScatter {
+            albedo: self.albedo,
+            phase: self.phase,
+            emission: self.emission.clone(),
+            origin: self.origin,
+            voxel_size: self.voxel_size,
+        });
+    }
+
+    /// Fraction of the light travelling along r between t_min and t_max
+    /// that gets through the medium, estimated by ratio tracking
+    ///
+    /// Unlike counting how often hit misses, every estimate is between 0 and
+    /// 1 rather than either, so it is much less noisy.
+    pub fn transmittance(&self, r: &Ray, t_min: f64, t_max: f64) -> f64 {
+        let (t0, t1) = match self.bounds_overlap(r, t_min, t_max) {
+            Some(range) => range,
+            None => return 1.0,
+        };
+        let majorant = self.density_scale*self.density.max_value();
+        let speed = r.dir().length();
+        if majorant <= 0.0 || speed == 0.0 {
+            return 1.0;
+        }
+
+        // Tentative collisions as in hit, each letting through the part of
+        // the light a collision there would be fictitious
+        let mut transmittance = 1.0;
+        let mut t = t0;
+        loop {
+            t -= (1.0 - random_f64()).ln() / (majorant*speed);
+            if t >= t1 {
+                return transmittance;
+            }
+            let density = self.density_scale*self.density.lookup(&to_voxel(&r.at(t), &self.origin, self.voxel_size));
+            transmittance *= 1.0 - density / majorant;
+        }
+    }
+
+    /// Range of t over which r is inside the box around the non-empty voxels
+    fn bounds_overlap(&self, r: &Ray, t_min: f64, t_max: f64) -> Option<(f64, f64)> {
+        let (min, max) = self.bounds.as_ref()?;
+        let (mut t0, mut t1) = (t_min, t_max);
+        for axis in 0..3 {
+            let component = |v: &Vec3| [v.x(), v.y(), v.z()][axis];
+            let inv_dir = 1.0 / component(r.dir());
+            let mut near = (component(min) - component(r.origin()))*inv_dir;
+            let mut far = (component(max) - component(r.origin()))*inv_dir;
+            if inv_dir < 0.0 {
+                std::mem::swap(&mut near, &mut far);
+            }
+            t0 = t0.max(near);
+            t1 = t1.min(far);
+            if t0 >= t1 {
+                return None;
+            }
+        }
+        Some((t0, t1))
+    }
+}
+
+impl Hittable for GridVolume {
+    fn hit(&self, r: &Ray, t_min: f64, t_max: f64) -> Option<HitRecord> {
+        stats::count(Counter::IntersectionTests);
+
+        let (t0, t1) = self.bounds_overlap(r, t_min, t_max)?;
+        let majorant = self.density_scale*self.density.max_value();
+        let speed = r.dir().length();
+        if majorant <= 0.0 || speed == 0.0 {
+            return None;
+        }
+
+        // Delta tracking: tentative collisions come at the rate of the
+        // densest voxel and are real in proportion to the density found
+        let mut t = t0;
+        loop {
+            t -= (1.0 - random_f64()).ln() / (majorant*speed);
+            if t >= t1 {
+                return None;
+            }
+
+            let p = r.at(t);
+            let density = self.density_scale*self.density.lookup(&to_voxel(&p, &self.origin, self.voxel_size));
+            if random_f64()*majorant < density {
+                let normal = -r.dir().unit_vector();
+                return Some(HitRecord {
+                    p,
+                    normal,
+                    geometric_normal: normal,
+                    material: Rc::clone(&self.material),
+                    t,
+                    u: 0.0,
+                    v: 0.0,
+                    dpdu: Vec3::new(0.0, 0.0, 0.0),
+                    dpdv: Vec3::new(0.0, 0.0, 0.0),
+                    front_face: true,
+                    object_id: object_id(self),
+                });
+            }
+        }
+    }
+}
+
+/// Voxel coordinates of world space point p
+fn to_voxel(p: &Point3, origin: &Point3, voxel_size: f64) -> Vec3 {
+    (p - origin) / voxel_size
+}
+
+/// Scattering at a collision inside a GridVolume
+struct VolumeScatter {
+    albedo: Color,
+    phase: HenyeyGreenstein,
+    emission: Option<(Rc<dyn Grid>, Color)>,
+    origin: Point3,
+    voxel_size: f64,
+}
+
+impl Material for VolumeScatter {
+    // The phase function is sampled exactly, so the weight is the albedo
+    fn sample(&self, r: &Ray, _: &HitRecord) -> Option<BsdfSample> {
+        let dir = r.dir().unit_vector();
+        let wi = self.phase.sample(&dir, random_f64(), random_f64());
+        Some(BsdfSample {
+            wi,
+            weight: self.albedo,
+            pdf: self.phase.eval(Vec3::dot(&dir, &wi)),
+            flags: BsdfFlags::DIFFUSE | BsdfFlags::REFLECTION | BsdfFlags::TRANSMISSION,
+            origin: None,
+        })
+    }
+
+    fn eval(&self, r: &Ray, hit_record: &HitRecord, wi: &Vec3) -> Color {
+        self.albedo*self.pdf(r, hit_record, wi)
+    }
+
+    fn pdf(&self, r: &Ray, _: &HitRecord, wi: &Vec3) -> f64 {
+        self.phase.eval(Vec3::dot(&r.dir().unit_vector(), wi))
+    }
+
+    fn flags(&self) -> BsdfFlags {
+        BsdfFlags::DIFFUSE | BsdfFlags::REFLECTION | BsdfFlags::TRANSMISSION
+    }
+
+    fn albedo(&self, _hit_record: &HitRecord) -> Color {
+        self.albedo
+    }
+
+    // Collisions are absorbed with probability 1 - albedo, which is when
+    // the medium's own light is picked up
+    fn emitted(&self, hit_record: &HitRecord) -> Color {
+        match &self.emission {
+            Some((grid, color)) => {
+                let value = grid.lookup(&to_voxel(hit_record.p(), &self.origin, self.voxel_size));
+                (1.0 - self.albedo)*color*value
+            },
+            None => Color::BLACK,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use approx::assert_relative_eq;
+    use super::*;
+    use crate::INFINITY;
+    use crate::grid::DenseGrid;
+
+    #[test]
+    fn test_constant_grid_transmittance() {
+        // A 4 voxel cube of constant density has an optical depth of 1
+        // across, counting the half voxels the interpolation fades out over
+        let density: Rc<dyn Grid> = Rc::new(DenseGrid::new([4, 4, 4], vec![1.0; 64]).unwrap());
+        let volume = GridVolume::new(&density, Point3::new(0.0, 0.0, 0.0), 1.0).density_scale(0.25);
+        let r = Ray::new(Point3::new(-10.0, 2.0, 2.0), Vec3::new(2.0, 0.0, 0.0));
+        let expected = (-1.0_f64).exp();
+
+        let n = 100_000;
+        let misses = (0..n).filter(|_| volume.hit(&r, 0.001, INFINITY).is_none()).count();
+        assert_relative_eq!(misses as f64 / n as f64, expected, epsilon = 0.01);
+        let transmittance = (0..n).map(|_| volume.transmittance(&r, 0.001, INFINITY)).sum::<f64>() / n as f64;
+        assert_relative_eq!(transmittance, expected, epsilon = 0.01);
+    }
+}
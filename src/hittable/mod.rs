@@ -1,12 +1,21 @@
+pub mod alpha_mask;
+pub mod animated;
+pub mod grid_volume;
 pub mod hittable_list;
 pub mod sphere;
+pub mod subsurface;
 
 // Re-export structs that implement Hittable
+pub use alpha_mask::{AlphaMask, AlphaMode};
+pub use animated::Animated;
+pub use grid_volume::GridVolume;
 pub use sphere::Sphere;
+pub use subsurface::Subsurface;
 pub use hittable_list::HittableList;
 
 use std::rc::Rc;
 use crate::material::Material;
+use crate::onb::Onb;
 use crate::ray::Ray;
 use crate::vec3::{Point3, Vec3};
 
@@ -21,18 +30,42 @@ pub fn into_opposing_normal(r: &Ray, outward_normal: Vec3) -> (bool, Vec3) {
     (front_face, normal)
 }
 
+/// Returns an id for object that is unique for as long as the object is alive
+pub fn object_id<T: ?Sized>(object: &T) -> usize {
+    object as *const T as *const u8 as usize
+}
+
+#[derive(Clone)]
 pub struct HitRecord {
     p: Point3,
-    normal: Vec3,
+    normal: Vec3,            // Shading normal, which materials may perturb
+    geometric_normal: Vec3,  // Normal of the actual surface, on the same side as normal
     material: Rc<dyn Material>,
     pub t: f64,
+    pub u: f64,  // Surface coordinates of the hit point, in [0, 1]
+    pub v: f64,
+    pub dpdu: Vec3,  // Change of the hit point along u and v, the surface tangents
+    pub dpdv: Vec3,
     pub front_face: bool,
+    pub object_id: usize,  // Identifies the object that was hit, unique for as long as the object is alive
 }
 
 impl HitRecord {
     pub fn p(&self) -> &Point3 {&self.p}
     pub fn normal(&self) -> &Vec3 {&self.normal}
+    pub fn geometric_normal(&self) -> &Vec3 {&self.geometric_normal}
     pub fn material(&self) -> &Rc<dyn Material> {&self.material}
+
+    /// Copy of the hit record with a different shading normal
+    pub fn with_normal(&self, normal: Vec3) -> HitRecord {
+        HitRecord {normal, ..self.clone()}
+    }
+
+    /// Basis around the shading normal with u along dpdu where possible,
+    /// so anisotropic materials follow the surface parameterization
+    pub fn shading_frame(&self) -> Onb {
+        Onb::from_wu(&self.normal, &self.dpdu)
+    }
 }
 
 pub trait Hittable {
@@ -1,8 +1,10 @@
 use std::rc::Rc;
 
-use crate::hittable::{HitRecord, Hittable, into_opposing_normal};
+use crate::hittable::{HitRecord, Hittable, into_opposing_normal, object_id};
 use crate::material::Material;
+use crate::PI;
 use crate::ray::Ray;
+use crate::stats::{self, Counter};
 use crate::vec3::{Point3, Vec3};
 
 pub struct Sphere {
@@ -27,10 +29,35 @@ impl Sphere {
     pub fn radius(&self) -> f64 {
         self.radius
     }
+
+    /// Surface coordinates (u, v) of a point on the unit sphere
+    ///
+    /// u goes around the y-axis starting from -x, v from the bottom (-y) to the top (+y)
+    fn uv(p: &Point3) -> (f64, f64) {
+        let theta = (-p.y()).acos();
+        let phi = (-p.z()).atan2(p.x()) + PI;
+        (phi / (2.0*PI), theta / PI)
+    }
+
+    /// Derivatives of the hit point with respect to u and v at point p of
+    /// the unit sphere
+    fn tangents(&self, p: &Point3) -> (Vec3, Vec3) {
+        // Zero at the poles, where u is undefined
+        let sin_theta = (1.0 - p.y()*p.y()).max(0.0).sqrt();
+        let dpdu = 2.0*PI*self.radius*Vec3::new(p.z(), 0.0, -p.x());
+        let dpdv = if sin_theta > 0.0 {
+            PI*self.radius*Vec3::new(-p.x()*p.y() / sin_theta, sin_theta, -p.y()*p.z() / sin_theta)
+        } else {
+            Vec3::new(0.0, 0.0, 0.0)
+        };
+        (dpdu, dpdv)
+    }
 }
 
 impl Hittable for Sphere {
     fn hit(&self, r: &Ray, t_min: f64, t_max: f64) -> Option<HitRecord> {
+        stats::count(Counter::IntersectionTests);
+
         let oc = r.origin() - self.center;
 
         let a = r.dir().length_squared();
@@ -56,13 +83,21 @@ impl Hittable for Sphere {
             let hit_point = r.at(root);
             let outward_normal = (hit_point - self.center) / self.radius;
             let (front_face, normal) = into_opposing_normal(r, outward_normal);
+            let (u, v) = Self::uv(&outward_normal);
+            let (dpdu, dpdv) = self.tangents(&outward_normal);
 
             Some(HitRecord{
                 p: hit_point,
                 normal,
+                geometric_normal: normal,
                 material: Rc::clone(&self.material),
                 t: root,
+                u,
+                v,
+                dpdu,
+                dpdv,
                 front_face,
+                object_id: object_id(self),
             })
         }
     }
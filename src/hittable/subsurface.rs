@@ -0,0 +1,212 @@
+use std::rc::Rc;
+
+use crate::INFINITY;
+use crate::color::Color;
+use crate::hittable::{HitRecord, Hittable};
+use crate::material::{self, BsdfFlags, BsdfSample, Material};
+use crate::medium::Medium;
+use crate::random_f64;
+use crate::ray::Ray;
+use crate::spectrum;
+use crate::vec3::{Point3, Vec3};
+
+/// Fills a closed object with a scattering medium, for translucent
+/// materials like skin, wax, marble and milk
+///
+/// The material of the object becomes the interface between the medium and
+/// the outside, usually a Dielectric or RoughDielectric. Light it transmits
+/// does a random walk through the medium, bounded by hit tests against the
+/// object, until the interface lets it out again. The object must be closed
+/// and have nothing inside it.
+pub struct Subsurface {
+    object: Rc<dyn Hittable>,
+    walk: Rc<dyn Material>,
+}
+
+impl Subsurface {
+    pub fn new(object: &Rc<dyn Hittable>, medium: Medium) -> Self {
+        Subsurface {
+            object: Rc::clone(object),
+            walk: Rc::new(RandomWalk {object: Rc::clone(object), medium}),
+        }
+    }
+}
+
+impl Hittable for Subsurface {
+    fn hit(&self, r: &Ray, t_min: f64, t_max: f64) -> Option<HitRecord> {
+        let hit_record = self.object.hit(r, t_min, t_max)?;
+        Some(HitRecord {material: Rc::clone(&self.walk), ..hit_record})
+    }
+}
+
+/// Material of a Subsurface object, which follows light through the medium
+struct RandomWalk {
+    object: Rc<dyn Hittable>,
+    medium: Medium,
+}
+
+impl RandomWalk {
+    /// Scattering events before Russian roulette starts ending dim walks
+    const MIN_STEPS: u32 = 16;
+
+    /// Safety backstop on the steps of a walk, for walks Russian roulette
+    /// can't end, like inside an object that absorbs nothing and reflects
+    /// all. Walks reaching it are dropped, which real media never come near.
+    const MAX_STEPS: u32 = 1 << 20;
+
+    /// Offset so rays don't hit the point they leave from, like the t_min of trace
+    const EPSILON: f64 = 0.001;
+
+    /// Follows light that entered the medium at p along the unit vector dir
+    /// until it leaves through the interface. None if it was absorbed.
+    ///
+    /// Walks aren't bounded by the recursion depth, as a dense medium can
+    /// take far more steps than a path has bounces. Russian roulette ends
+    /// those that have been mostly absorbed, without changing their mean,
+    /// and MAX_STEPS catches the rest.
+    fn walk(&self, r: &Ray, p: Point3, dir: Vec3, weight: Color) -> Option<BsdfSample> {
+        let (sigma_s, sigma_t) = self.medium.coefficients(r.wavelength());
+        let mut ray = Ray::new(p, dir).with_wavelength(r.wavelength()).with_time(r.time());
+        let mut throughput = weight;
+        // Rays leaving the boundary are offset from it, those leaving a
+        // scattering event in the medium needn't be, and mustn't skip a
+        // boundary that is close by
+        let mut t_min = Self::EPSILON;
+
+        for step in 0..Self::MAX_STEPS {
+            if step >= Self::MIN_STEPS {
+                let survival = throughput.max_component().min(1.0);
+                if random_f64() >= survival {
+                    return None;
+                }
+                throughput /= survival;
+            }
+
+            // Nothing to hit means light got out through a gap in the object
+            let boundary = self.object.hit(&ray, t_min, INFINITY)?;
+
+            // Distances are sampled for a channel picked in proportion to
+            // the throughput, with the pdf averaged over all channels, which
+            // keeps paths from blowing up in channels that are hardly
+            // absorbed (Chiang et al. 2016)
+            let total = throughput.sum();
+            if total <= 0.0 {
+                return None;
+            }
+            let probability = throughput / total;
+            let u = random_f64();
+            let channel = if u < probability.r() {0} else if u < probability.r() + probability.g() {1} else {2};
+            let t = if sigma_t[channel] > 0.0 {-(1.0 - random_f64()).ln() / sigma_t[channel]} else {INFINITY};
+
+            if t < boundary.t {
+                let transmittance = sigma_t.map(|sigma| (-sigma*t).exp());
+                let pdf = (probability*sigma_t*transmittance).sum();
+                throughput = throughput*sigma_s*transmittance / pdf;
+
+                let wi = self.medium.phase().sample(ray.dir(), random_f64(), random_f64());
+                ray = Ray::new(ray.at(t), wi).with_wavelength(r.wavelength()).with_time(r.time());
+                t_min = 0.0;
+                continue;
+            }
+
+            let transmittance = sigma_t.map(|sigma| (-sigma*boundary.t).exp());
+            let pdf = (probability*transmittance).sum();
+            throughput = throughput*transmittance / pdf;
+
+            // The normal of the boundary faces back into the medium
+            let sample = material::sample(boundary.material().as_ref(), &ray, &boundary)?;
+            throughput *= spectral(r, sample.weight);
+            if Vec3::dot(&sample.wi, boundary.normal()) < 0.0 {
+                return Some(BsdfSample {
+                    wi: sample.wi,
+                    weight: throughput,
+                    pdf: 0.0,
+                    flags: BsdfFlags::SPECULAR | BsdfFlags::TRANSMISSION,
+                    origin: Some(*boundary.p()),
+                });
+            }
+            ray = Ray::new(*boundary.p(), sample.wi).with_wavelength(r.wavelength()).with_time(r.time());
+            t_min = Self::EPSILON;
+        }
+
+        None
+    }
+}
+
+impl Material for RandomWalk {
+    fn sample(&self, r: &Ray, hit_record: &HitRecord) -> Option<BsdfSample> {
+        // hit_record carries this material, the interface is the object's own
+        let entry = self.object.hit(r, Self::EPSILON, hit_record.t + Self::EPSILON)?;
+        let sample = material::sample(entry.material().as_ref(), r, &entry)?;
+
+        // Reflections off the outside, and anything seen from inside the
+        // object, are left to the interface
+        if !entry.front_face || Vec3::dot(&sample.wi, entry.normal()) > 0.0 {
+            return Some(sample);
+        }
+        self.walk(r, *entry.p(), sample.wi, spectral(r, sample.weight))
+    }
+
+    fn flags(&self) -> BsdfFlags {
+        BsdfFlags::SPECULAR | BsdfFlags::REFLECTION | BsdfFlags::TRANSMISSION
+    }
+
+    /// Albedo of single scattering events
+    fn albedo(&self, _hit_record: &HitRecord) -> Color {
+        let (sigma_s, sigma_t) = self.medium.coefficients(None);
+        sigma_s / sigma_t
+    }
+}
+
+/// Weight of an interface sample at the wavelength of r in spectral mode,
+/// as trace would apply it
+fn spectral(r: &Ray, weight: Color) -> Color {
+    match r.wavelength() {
+        Some(lambda) => Color::gray(spectrum::upsample(&weight, lambda)),
+        None => weight,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use approx::assert_relative_eq;
+    use super::*;
+    use crate::hittable::sphere::Sphere;
+    use crate::material::Dielectric;
+
+    #[test]
+    fn test_walk_conserves_energy() {
+        // A dense medium that doesn't absorb, behind an interface that
+        // doesn't bend light, must let out all of the light that enters,
+        // however many steps that takes
+        let interface: Rc<dyn Material> = Rc::new(Dielectric::new(1.0));
+        let sphere: Rc<dyn Hittable> = Rc::new(Sphere::new(Point3::new(0.0, 0.0, 0.0), 1.0, &interface));
+        let medium = Medium::new(Color::new(50.0, 50.0, 50.0), Color::new(0.0, 0.0, 0.0));
+        let object = Subsurface::new(&sphere, medium);
+
+        let r = Ray::new(Point3::new(0.0, 0.0, 5.0), Vec3::new(0.0, 0.0, -1.0));
+        let hit_record = object.hit(&r, 0.001, INFINITY).unwrap();
+        let n = 2000;
+        let total = (0..n)
+            .filter_map(|_| hit_record.material().sample(&r, &hit_record))
+            .fold(Color::new(0.0, 0.0, 0.0), |sum, sample| sum + sample.weight);
+        (0..3).for_each(|i| assert_relative_eq!(total[i] / n as f64, 1.0, epsilon = 1e-6));
+    }
+
+    #[test]
+    fn test_scatter_leaves_from_exit() {
+        // The older scatter API starts the scattered ray where the walk left the object
+        let interface: Rc<dyn Material> = Rc::new(Dielectric::new(1.0));
+        let sphere: Rc<dyn Hittable> = Rc::new(Sphere::new(Point3::new(0.0, 0.0, 0.0), 1.0, &interface));
+        let object = Subsurface::new(&sphere, Medium::new(Color::new(5.0, 5.0, 5.0), Color::new(0.0, 0.0, 0.0)));
+
+        let r = Ray::new(Point3::new(0.0, 0.0, 5.0), Vec3::new(0.0, 0.0, -1.0));
+        let hit_record = object.hit(&r, 0.001, INFINITY).unwrap();
+        let origins: Vec<Point3> = (0..100)
+            .filter_map(|_| hit_record.material().scatter(&r, &hit_record))
+            .map(|(_, scattered)| *scattered.origin())
+            .collect();
+        assert!(origins.iter().all(|p| (p.length() - 1.0).abs() < 1e-9));
+        assert!(origins.iter().any(|p| (p - hit_record.p()).length() > 0.1));
+    }
+}
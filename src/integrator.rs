@@ -0,0 +1,151 @@
+use std::rc::Rc;
+
+use crate::INFINITY;
+use crate::color::Color;
+use crate::hittable::Hittable;
+use crate::material;
+use crate::ray::Ray;
+use crate::spectrum;
+use crate::stats::{self, Counter};
+use crate::vec3::Vec3;
+
+/// Information about the first surface a camera ray hits
+#[derive(Debug, Clone, Copy)]
+pub struct FirstHit {
+    pub distance: f64,  // Distance from the ray origin to the hit point
+    pub normal: Vec3,
+    pub albedo: Color,  // Reflectance of the material, see Material::albedo
+    pub material_id: usize,
+    pub object_id: usize,
+}
+
+/// Radiance carried back along one camera ray, split by how many
+/// times the light bounced before reaching the camera
+#[derive(Debug, Clone, Copy)]
+pub struct PathSample {
+    pub emission: Color,  // Light seen directly: emitters and the sky
+    pub direct: Color,    // Light that bounced once
+    pub indirect: Color,  // Light that bounced two or more times
+    pub first_hit: Option<FirstHit>,
+}
+
+impl PathSample {
+    pub fn radiance(&self) -> Color {
+        self.emission + self.direct + self.indirect
+    }
+
+    /// Converts a sample traced at wavelength lambda, drawn with density
+    /// pdf, to its contribution to the RGB image
+    pub fn spectral_to_rgb(self, lambda: f64, pdf: f64) -> PathSample {
+        let to_rgb = |c: Color| spectrum::to_rgb(c.r(), lambda, pdf);
+        PathSample {
+            emission: to_rgb(self.emission),
+            direct: to_rgb(self.direct),
+            indirect: to_rgb(self.indirect),
+            ..self
+        }
+    }
+
+    /// The sample with all its light multiplied by k, eg. for exposure
+    pub fn scaled(self, k: f64) -> PathSample {
+        PathSample {
+            emission: k*self.emission,
+            direct: k*self.direct,
+            indirect: k*self.indirect,
+            ..self
+        }
+    }
+
+    /// The sample with its light multiplied by filter in every channel
+    pub fn filtered(self, filter: &Color) -> PathSample {
+        PathSample {
+            emission: self.emission*filter,
+            direct: self.direct*filter,
+            indirect: self.indirect*filter,
+            ..self
+        }
+    }
+
+    fn add(&mut self, bounce: u32, light: Color) {
+        match bounce {
+            0 => self.emission += light,
+            1 => self.direct += light,
+            _ => self.indirect += light,
+        }
+    }
+}
+
+impl Default for PathSample {
+    fn default() -> Self {
+        PathSample {
+            emission: Color::new(0.0, 0.0, 0.0),
+            direct: Color::new(0.0, 0.0, 0.0),
+            indirect: Color::new(0.0, 0.0, 0.0),
+            first_hit: None,
+        }
+    }
+}
+
+/// Color of the sky seen along r
+pub fn background(r: &Ray) -> Color {
+    let unit_direction = r.dir().unit_vector();
+    let t = 0.5*(unit_direction.y() + 1.0); // Normalize y to [0, 1]
+    (1.0 - t)*Color::new(1.0, 1.0, 1.0) + t*Color::new(0.5, 0.7, 1.0)
+}
+
+/// Follows r through world for at most recursion_depth bounces
+///
+/// If r carries a wavelength the path is traced at that wavelength only:
+/// colors of materials and lights are upsampled to their spectra, and every
+/// channel of the result holds the radiance at that wavelength.
+pub fn trace(r: &Ray, world: &Rc<dyn Hittable>, recursion_depth: u32) -> PathSample {
+    let mut sample = PathSample::default();
+    let mut throughput = Color::new(1.0, 1.0, 1.0);
+    let mut ray = *r;
+    let spectral = |c: Color| match r.wavelength() {
+        Some(lambda) => {
+            let value = spectrum::upsample(&c, lambda);
+            Color::new(value, value, value)
+        },
+        None => c,
+    };
+
+    for bounce in 0..recursion_depth {
+        stats::count(if bounce == 0 {Counter::CameraRays} else {Counter::ScatterRays});
+
+        // t_min is 0.001 to avoid floating point error causing a hit to be recorded at the ray origin
+        // fixes shadow acne
+        let hit_record = match world.hit(&ray, 0.001, INFINITY) {
+            Some(hit_record) => hit_record,
+            None => {
+                sample.add(bounce, throughput*spectral(background(&ray)));
+                break;
+            },
+        };
+
+        let material = hit_record.material();
+        sample.add(bounce, throughput*spectral(material.emitted(&hit_record)));
+        let scattered = material::sample(material.as_ref(), &ray, &hit_record);
+
+        if bounce == 0 {
+            sample.first_hit = Some(FirstHit {
+                distance: (hit_record.p() - ray.origin()).length(),
+                normal: *hit_record.normal(),
+                albedo: material.albedo(&hit_record),
+                material_id: Rc::as_ptr(material) as *const u8 as usize,
+                object_id: hit_record.object_id,
+            });
+        }
+
+        match scattered {
+            Some(sample) => {
+                throughput *= spectral(sample.weight);
+                let origin = sample.origin.unwrap_or(*hit_record.p());
+                ray = Ray::new(origin, sample.wi).with_wavelength(r.wavelength()).with_time(r.time());
+            },
+            None => break,
+        }
+    }
+
+    sample
+}
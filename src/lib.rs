@@ -1,34 +1,91 @@
+pub mod animation;
+pub mod aov;
 pub mod camera;
+pub mod checkpoint;
 pub mod color;
+pub mod denoise;
+pub mod film;
+pub mod grid;
 pub mod hittable;
+pub mod integrator;
 pub mod material;
+pub mod onb;
+pub mod medium;
+pub mod options;
+pub mod post;
 pub mod ray;
+pub mod render;
+pub mod rng;
 pub mod scene;
+pub mod spectrum;
+pub mod stats;
+pub mod texture;
 pub mod vec3;
 
 /* Re-exports */
-pub use std::f64::INFINITY;
 pub use std::f64::consts::PI;
+pub const INFINITY: f64 = f64::INFINITY;
 
 /* Utility functions */
-use rand::random;
+use std::fs::File;
+use std::io::{self, BufWriter};
+use std::path::Path;
 
 use image::RgbImage;
 
-use color::{Color, into_pixel};
+use color::{Color, Encoding, Transfer, into_pixel};
 
-/// Writes a pixel into img
-pub fn write_pixel(img: &mut RgbImage, pixel_color: &Color, x: u32, y: u32, n_samples: u32) {
+/// Writes a pixel into img, encoded for storage
+pub fn write_pixel(img: &mut RgbImage, pixel_color: &Color, x: u32, y: u32, n_samples: u32, encoding: &Encoding) {
     let pixel_color = pixel_color / (n_samples as f64);
+    img.put_pixel(x, y, into_pixel(&encoding.encode(&pixel_color)));
+}
+
+/// Saves img to path, format taken from the extension
+///
+/// PNGs are tagged with the color space and transfer function of encoding
+/// so viewers display them correctly. Other formats are written untagged.
+pub fn save_image<P: AsRef<Path>>(img: &RgbImage, path: P, encoding: &Encoding) -> io::Result<()> {
+    let path = path.as_ref();
+    let is_png = path.extension().is_some_and(|ext| ext.eq_ignore_ascii_case("png"));
+    if !is_png {
+        return img.save(path).map_err(io::Error::other);
+    }
+
+    let mut encoder = png::Encoder::new(BufWriter::new(File::create(path)?), img.width(), img.height());
+    encoder.set_color(png::ColorType::Rgb);
+    encoder.set_depth(png::BitDepth::Eight);
+    if *encoding == Encoding::SRGB {
+        encoder.set_srgb(png::SrgbRenderingIntent::Perceptual);
+    } else {
+        // gAMA can only describe a power law, so the others are approximated
+        let gamma = match encoding.transfer {
+            Transfer::Linear => 1.0,
+            Transfer::Srgb => 2.2,
+            Transfer::Rec709 => 1.0/0.45,
+            Transfer::Gamma(gamma) => gamma,
+        };
+        encoder.set_source_gamma(png::ScaledFloat::new((1.0/gamma) as f32));
+
+        let [r, g, b, white] = encoding.space.chromaticities();
+        let xy = |(x, y): (f64, f64)| (x as f32, y as f32);
+        encoder.set_source_chromaticities(png::SourceChromaticities::new(xy(white), xy(r), xy(g), xy(b)));
+    }
 
-    // Gamma correction (raise to the power of 1/gamma for gamma = 2.0)
-    let pixel_color = Color::new(
-        pixel_color.x().sqrt(),
-        pixel_color.y().sqrt(),
-        pixel_color.z().sqrt(),
-    );
+    let mut writer = encoder.write_header()?;
+    writer.write_image_data(img.as_raw())?;
+    writer.finish()?;
+    Ok(())
+}
+
+/// Error for a file whose contents aren't what they should be
+pub(crate) fn invalid_data(msg: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, msg.to_string())
+}
 
-    img.put_pixel(x, y, into_pixel(&pixel_color));
+/// Parses a number read from a file
+pub(crate) fn parse_number<T: std::str::FromStr>(s: &str) -> io::Result<T> {
+    s.parse().map_err(|_| invalid_data(&format!("invalid number '{}'", s)))
 }
 
 /// Clamps x in [min, max]
@@ -38,7 +95,7 @@ pub fn clamp(x: f64, min: f64, max: f64) -> f64 {
 
 /// Returns a random f64 in [0, 1)
 pub fn random_f64() -> f64 {
-    random()
+    rng::next_f64()
 }
 
 /// Returns a random f64 in [min, max)
@@ -1,42 +1,215 @@
-use image::{ImageFormat, RgbImage};
+use std::path::{Path, PathBuf};
+use std::process;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
-use rusty_raytracer::{write_pixel, random_f64};
-use rusty_raytracer::color::Color;
+use image::{imageops, RgbImage};
+
+use rusty_raytracer::{rng, save_image, scene, stats};
+use rusty_raytracer::aov::AovBuffers;
+use rusty_raytracer::checkpoint::Checkpoint;
+use rusty_raytracer::color::Encoding;
+use rusty_raytracer::denoise::{denoise, DenoiseSettings};
+use rusty_raytracer::film::{Film, Region};
+use rusty_raytracer::options::{CropOutput, Options, USAGE};
+use rusty_raytracer::post::{post_process, Bloom, Glare, Lut3d};
+use rusty_raytracer::render::{render, CheckpointSettings, Progress, RenderSettings};
+use rusty_raytracer::stats::Phase;
 
 fn main() {
     // Basic config
-    let output_file: &str = "output.png";
-    let output_file_format = ImageFormat::Png;
+    let options = Options::parse(std::env::args().skip(1)).unwrap_or_else(|e| exit_with_error(&e));
+    if options.help {
+        println!("{}", USAGE);
+        return;
+    }
+
+    // A resumed render must rebuild its scene with the seed it was started with
+    let resume = options.resume.as_ref().map(|path| {
+        Checkpoint::load(path).unwrap_or_else(|e| exit_with_error(&format!("could not load checkpoint {}: {}", path.display(), e)))
+    });
+    let seed = match (&resume, options.seed) {
+        (Some(checkpoint), _) => checkpoint.seed,
+        (None, Some(seed)) => seed,
+        (None, None) => SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_nanos() as u64).unwrap_or(0),
+    };
+    rng::seed(seed);
 
     // Load a scene
-    let scene = rusty_raytracer::scene::lots_of_random_spheres::get_scene();
-
-    // Pull relevant data out of the Scene object
-    let image_width = scene.image_width;
-    let image_height = scene.image_height;
-    let samples_per_pixel = scene.samples_per_pixel;
-    let recursion_depth = scene.recursion_depth;
-    let cam = scene.cam;
-    let world = scene.world;
-
-    // Render
-    let mut img = RgbImage::new(image_width, image_height);
-    for x in 0..image_width {
-        for y in 0..image_height {
-            let mut pixel_color = Color::new(0.0, 0.0, 0.0);
-            for _ in 0..samples_per_pixel {
-                let u = ((x as f64) + random_f64()) / (image_width as f64);  // Percentage of width for current pixel
-                let v = ((y as f64) + random_f64()) / (image_height as f64); // Precentage of height for current pixel
-                let r = cam.get_ray(u, v);
-                pixel_color += r.color(&world, recursion_depth);
+    let mut scene = stats::time(Phase::SceneBuild, || {
+        scene::by_name(&options.scene).unwrap_or_else(|| {
+            exit_with_error(&format!("unknown scene '{}', expected one of {:?}", options.scene, scene::NAMES))
+        })
+    });
+    if let Some(samples_per_pixel) = options.samples_per_pixel {
+        scene.samples_per_pixel = samples_per_pixel;
+    }
+    scene.spectral |= options.spectral;
+    let scene_hash = scene.hash();
+
+    // Post-processing options override the scene's
+    if let Some(intensity) = options.bloom {
+        scene.post.bloom = Some(Bloom {intensity, ..scene.post.bloom.unwrap_or_default()});
+    }
+    if let Some(intensity) = options.glare {
+        scene.post.glare = Some(Glare {intensity, ..scene.post.glare.unwrap_or_default()});
+    }
+    if options.white_balance.is_some() {
+        scene.post.white_balance = options.white_balance;
+    }
+    scene.post.contrast = options.contrast.unwrap_or(scene.post.contrast);
+    scene.post.saturation = options.saturation.unwrap_or(scene.post.saturation);
+    if let Some(path) = &options.lut {
+        let lut = Lut3d::open(path).unwrap_or_else(|e| exit_with_error(&format!("could not load LUT {}: {}", path.display(), e)));
+        scene.post.lut = Some(lut);
+    }
+
+    let full_frame = Region::new(0, 0, scene.image_width, scene.image_height);
+    if let Some(crop) = options.crop {
+        if !crop.fits_within(scene.image_width, scene.image_height) {
+            exit_with_error(&format!("crop {:?} doesn't fit in the {}x{} image", crop, scene.image_width, scene.image_height));
+        }
+    }
+
+    // Checked before rendering so a bad image doesn't waste a render
+    let composite_base = options.composite_into.as_ref().map(|path| {
+        let img = image::open(path)
+            .unwrap_or_else(|e| exit_with_error(&format!("could not open {}: {}", path.display(), e)))
+            .to_rgb8();
+        if img.dimensions() != (full_frame.width, full_frame.height) {
+            exit_with_error(&format!("{} isn't {}x{}", path.display(), full_frame.width, full_frame.height));
+        }
+        img
+    });
+
+    // Animations render every frame to its own numbered files, by default
+    // all of their timeline unless a single image is being checkpointed
+    let checkpointing = options.resume.is_some() || options.checkpoint.is_some();
+    let frames: Vec<Option<u32>> = match (options.frames, &scene.timeline) {
+        (Some(_), None) => exit_with_error(&format!("scene '{}' isn't animated", options.scene)),
+        (Some(_), Some(_)) if checkpointing => exit_with_error("--frames can't be combined with checkpoints"),
+        (Some((first, last)), Some(_)) => (first..=last).map(Some).collect(),
+        (None, Some(timeline)) if !checkpointing => (timeline.first_frame..=timeline.last_frame).map(Some).collect(),
+        (None, _) => vec![None],
+    };
+
+    let mut resumed_film = match resume {
+        Some(checkpoint) => {
+            if checkpoint.scene_hash != scene_hash {
+                exit_with_error("checkpoint was saved from a different scene");
+            }
+            if options.crop.is_some_and(|crop| crop != checkpoint.film.region()) {
+                exit_with_error(&format!("checkpoint covers {:?}, not the requested crop", checkpoint.film.region()));
             }
+            rng::set_state(checkpoint.rng_state);
+            println!("Resuming from {} samples per pixel", checkpoint.film.min_samples());
+            Some(checkpoint.film)
+        },
+        None => None,
+    };
 
-            // Our coordinate system is right-handed and defines +y as up
-            // While the Rust image crate is right-handed and defines +y as down
-            // So we need to flip the y coordinates.
-            write_pixel(&mut img, &pixel_color, x, image_height - y - 1, samples_per_pixel);
+    for frame in frames {
+        if let Some(frame) = frame {
+            scene.set_frame(frame);
+            println!("Rendering frame {}", frame);
         }
-        println!("{}/{} scanlines rendered", x + 1, image_width);
+        let mut film = resumed_film.take().unwrap_or_else(|| Film::new_region(options.crop.unwrap_or(full_frame)));
+
+        // Render
+        let settings = RenderSettings {
+            samples_per_pixel: scene.samples_per_pixel,
+            samples_per_pass: options.samples_per_pass,
+            checkpoint: options.checkpoint.as_ref().map(|path| CheckpointSettings {
+                path: path.clone(),
+                interval: Duration::from_secs(options.checkpoint_interval),
+                scene_hash,
+                seed,
+            }),
+        };
+        // The denoiser uses the normal, albedo and depth passes as guides
+        let gather_aovs = !options.aovs.is_empty() || options.denoise;
+        let mut aovs = if gather_aovs {Some(AovBuffers::new(film.width(), film.height()))} else {None};
+        stats::time(Phase::Render, || render(&scene, &mut film, aovs.as_mut(), &settings, report_progress))
+            .unwrap_or_else(|e| exit_with_error(&format!("could not save checkpoint: {}", e)));
+
+        if options.denoise {
+            let settings = DenoiseSettings {
+                iterations: options.denoise_iterations,
+                ..DenoiseSettings::default()
+            };
+            film = stats::time(Phase::Denoise, || denoise(&film, aovs.as_ref().unwrap(), &settings));
+        }
+
+        if !scene.post.is_identity() {
+            film = stats::time(Phase::PostProcess, || post_process(&film, &scene.post, scene.image_height));
+        }
+
+        stats::time(Phase::Write, || {
+            let encoding = Encoding::of(options.output_color_space);
+            let img = output_image(&film, &full_frame, options.crop_output, composite_base.clone(), &encoding);
+            save_image(&img, frame_path(&options.output, frame), &encoding).unwrap();
+
+            if !options.aovs.is_empty() {
+                let aovs = aovs.unwrap();
+                let aov_output = frame_path(&options.aov_output, frame);
+                let is_exr = aov_output.extension().is_some_and(|ext| ext.eq_ignore_ascii_case("exr"));
+                if is_exr {
+                    aovs.save_exr(&aov_output, &film, &options.aovs).unwrap();
+                } else {
+                    aovs.save_images(&aov_output, &film, &options.aovs).unwrap();
+                }
+            }
+        });
+    }
+
+    let stats = stats::snapshot();
+    println!("{}", stats);
+    if let Some(path) = &options.stats_json {
+        std::fs::write(path, stats.to_json())
+            .unwrap_or_else(|e| exit_with_error(&format!("could not write {}: {}", path.display(), e)));
+    }
+}
+
+/// Places the rendered pixels as requested by the crop options
+///
+/// Renders of a region are pasted into composite_base if given, otherwise
+/// crop_output decides whether the image is just the region or the full frame.
+fn output_image(film: &Film, full_frame: &Region, crop_output: CropOutput, composite_base: Option<RgbImage>, encoding: &Encoding) -> RgbImage {
+    let region = film.region();
+    let rendered = film.to_image(encoding);
+
+    let mut img = match (composite_base, crop_output) {
+        (Some(img), _) => img,
+        (None, _) if region == *full_frame => return rendered,
+        (None, CropOutput::Border) => RgbImage::new(full_frame.width, full_frame.height),
+        (None, CropOutput::Crop) => return rendered,
+    };
+    imageops::replace(&mut img, &rendered, region.x as i64, region.y as i64);
+    img
+}
+
+/// path with the frame number appended to its name, eg. output_0012.png,
+/// or path itself for a still image
+fn frame_path(path: &Path, frame: Option<u32>) -> PathBuf {
+    let frame = match frame {
+        Some(frame) => frame,
+        None => return path.to_path_buf(),
+    };
+    let stem = path.file_stem().map(|s| s.to_string_lossy().into_owned()).unwrap_or_default();
+    let file_name = match path.extension() {
+        Some(ext) => format!("{}_{:04}.{}", stem, frame, ext.to_string_lossy()),
+        None => format!("{}_{:04}", stem, frame),
+    };
+    path.with_file_name(file_name)
+}
+
+fn report_progress(progress: Progress) {
+    match progress {
+        Progress::Pass {samples, target} => println!("{}/{} samples per pixel rendered", samples, target),
+        Progress::Checkpoint(path) => println!("Checkpoint saved to {}", path.display()),
     }
-    img.save_with_format(output_file, output_file_format).unwrap();
+}
+
+fn exit_with_error(msg: &str) -> ! {
+    eprintln!("error: {}\n\n{}", msg, USAGE);
+    process::exit(1);
 }
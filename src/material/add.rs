@@ -0,0 +1,126 @@
+use std::rc::Rc;
+
+use crate::color::Color;
+use crate::hittable::HitRecord;
+use crate::material::{BsdfFlags, BsdfSample, Material};
+use crate::random_f64;
+use crate::ray::Ray;
+use crate::texture::{IntoTexture, Texture};
+use crate::vec3::Vec3;
+
+/// A layer added on top of a base material, eg. a glossy coat over a
+/// diffuse surface or a dusting of sheen
+///
+/// The BSDFs are summed, with the layer scaled by weight. Nothing stops
+/// the sum from reflecting more light than arrives, so keep weight low
+/// enough that it doesn't.
+pub struct AddMaterial {
+    base: Rc<dyn Material>,
+    layer: Rc<dyn Material>,
+    weight: Rc<dyn Texture>,
+}
+
+impl AddMaterial {
+    pub fn new<T: IntoTexture>(base: &Rc<dyn Material>, layer: &Rc<dyn Material>, weight: T) -> Self {
+        AddMaterial {
+            base: Rc::clone(base),
+            layer: Rc::clone(layer),
+            weight: weight.into_texture(),
+        }
+    }
+
+    fn weight(&self, hit_record: &HitRecord) -> f64 {
+        self.weight.scalar(hit_record.u, hit_record.v, hit_record.p()).max(0.0)
+    }
+}
+
+impl Material for AddMaterial {
+    fn sample(&self, r: &Ray, hit_record: &HitRecord) -> Option<BsdfSample> {
+        // Each material is sampled in proportion to its scale in the sum
+        let w = self.weight(hit_record);
+        let p_layer = w / (1.0 + w);
+        let (sample, scale, probability) = if random_f64() < p_layer {
+            (self.layer.sample(r, hit_record)?, w, p_layer)
+        } else {
+            (self.base.sample(r, hit_record)?, 1.0, 1.0 - p_layer)
+        };
+
+        if sample.flags.is_delta() {
+            return Some(BsdfSample {weight: sample.weight*(scale / probability), ..sample});
+        }
+        let pdf = (1.0 - p_layer)*self.base.pdf(r, hit_record, &sample.wi) + p_layer*self.layer.pdf(r, hit_record, &sample.wi);
+        if pdf <= 0.0 {
+            return None;
+        }
+        Some(BsdfSample {weight: self.eval(r, hit_record, &sample.wi) / pdf, pdf, ..sample})
+    }
+
+    fn eval(&self, r: &Ray, hit_record: &HitRecord, wi: &Vec3) -> Color {
+        self.base.eval(r, hit_record, wi) + self.weight(hit_record)*self.layer.eval(r, hit_record, wi)
+    }
+
+    fn pdf(&self, r: &Ray, hit_record: &HitRecord, wi: &Vec3) -> f64 {
+        let w = self.weight(hit_record);
+        let p_layer = w / (1.0 + w);
+        (1.0 - p_layer)*self.base.pdf(r, hit_record, wi) + p_layer*self.layer.pdf(r, hit_record, wi)
+    }
+
+    fn flags(&self) -> BsdfFlags {
+        self.base.flags() | self.layer.flags()
+    }
+
+    fn albedo(&self, hit_record: &HitRecord) -> Color {
+        self.base.albedo(hit_record) + self.weight(hit_record)*self.layer.albedo(hit_record)
+    }
+
+    fn emitted(&self, hit_record: &HitRecord) -> Color {
+        self.base.emitted(hit_record) + self.weight(hit_record)*self.layer.emitted(hit_record)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use approx::assert_relative_eq;
+    use super::*;
+    use crate::material::{Dielectric, Lambertian, Sheen};
+    use crate::material::test_util::hit;
+
+    #[test]
+    fn test_sum() {
+        // The layer is scaled in the sum, and picked in proportion to its scale
+        let base: Rc<dyn Material> = Rc::new(Lambertian::new(Color::new(0.5, 0.5, 0.5)));
+        let layer: Rc<dyn Material> = Rc::new(Sheen::new(Color::new(0.9, 0.2, 0.2), 0.5));
+        let (r, hit_record) = hit(Rc::new(AddMaterial::new(&base, &layer, 0.25)));
+        let sum = hit_record.material();
+        let p_layer = 0.25 / 1.25;
+
+        for _ in 0..100 {
+            let sample = match sum.sample(&r, &hit_record) {
+                Some(sample) => sample,
+                None => continue,
+            };
+            let wi = sample.wi;
+            let pdf = (1.0 - p_layer)*base.pdf(&r, &hit_record, &wi) + p_layer*layer.pdf(&r, &hit_record, &wi);
+            assert_relative_eq!(sample.pdf, pdf, max_relative = 1e-12);
+            let eval = base.eval(&r, &hit_record, &wi) + 0.25*layer.eval(&r, &hit_record, &wi);
+            (0..3).for_each(|i| assert_relative_eq!(sample.weight[i], eval[i] / pdf, max_relative = 1e-9));
+        }
+    }
+
+    #[test]
+    fn test_delta_lobes_pass_through() {
+        // Glass samples keep their direction, with the weight scaled for how
+        // rarely the layer is picked
+        let base: Rc<dyn Material> = Rc::new(Lambertian::new(Color::new(0.5, 0.5, 0.5)));
+        let layer: Rc<dyn Material> = Rc::new(Dielectric::new(1.5));
+        let (r, hit_record) = hit(Rc::new(AddMaterial::new(&base, &layer, 0.25)));
+
+        for _ in 0..1000 {
+            let sample = hit_record.material().sample(&r, &hit_record).unwrap();
+            if sample.flags.is_delta() {
+                assert_eq!(sample.pdf, 0.0);
+                (0..3).for_each(|i| assert_relative_eq!(sample.weight[i], 0.25 / (0.25 / 1.25), max_relative = 1e-12));
+            }
+        }
+    }
+}
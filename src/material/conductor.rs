@@ -0,0 +1,116 @@
+use crate::color::Color;
+use crate::hittable::HitRecord;
+use crate::material::{BsdfFlags, BsdfSample, Material};
+use crate::material::fresnel;
+use crate::material::microfacet::Ggx;
+use crate::random_f64;
+use crate::ray::Ray;
+use crate::vec3::Vec3;
+
+/// Rough metal modelled as a GGX microfacet conductor
+///
+/// Reflectance comes from the exact Fresnel equations for a complex
+/// refractive index eta + ik given per color channel, so metals tint their
+/// reflections and turn white at grazing angles without an albedo knob.
+pub struct Conductor {
+    eta: Color,
+    k: Color,
+    distribution: Ggx,
+}
+
+impl Conductor {
+    /// Isotropic conductor with perceptual roughness in [0, 1]
+    pub fn new(eta: Color, k: Color, roughness: f64) -> Self {
+        Self::anisotropic(eta, k, roughness, roughness)
+    }
+
+    /// Conductor whose roughness differs along the u and v directions of
+    /// the surface, for brushed metals
+    pub fn anisotropic(eta: Color, k: Color, roughness_u: f64, roughness_v: f64) -> Self {
+        Conductor {
+            eta,
+            k,
+            distribution: Ggx::from_roughness(roughness_u, roughness_v),
+        }
+    }
+
+    pub fn gold(roughness: f64) -> Self {
+        Self::new(Color::new(0.143, 0.374, 1.442), Color::new(3.983, 2.385, 1.603), roughness)
+    }
+
+    pub fn copper(roughness: f64) -> Self {
+        Self::new(Color::new(0.200, 0.924, 1.102), Color::new(3.912, 2.452, 2.142), roughness)
+    }
+
+    pub fn silver(roughness: f64) -> Self {
+        Self::new(Color::new(0.155, 0.117, 0.138), Color::new(4.828, 3.122, 2.147), roughness)
+    }
+
+    pub fn aluminium(roughness: f64) -> Self {
+        Self::new(Color::new(1.657, 0.880, 0.521), Color::new(9.224, 6.270, 4.837), roughness)
+    }
+}
+
+impl Material for Conductor {
+    fn sample(&self, r: &Ray, hit_record: &HitRecord) -> Option<BsdfSample> {
+        let frame = hit_record.shading_frame();
+        let wo = frame.to_local(&(-r.dir().unit_vector()));
+        if wo.z() <= 0.0 {
+            return None;
+        }
+
+        // Reflect about a microfacet normal visible from wo
+        let wm = self.distribution.sample_visible_normal(&wo, random_f64(), random_f64());
+        let wi = (-wo).reflect(&wm);
+        if wi.z() <= 0.0 {
+            return None;
+        }
+
+        // With visible normal sampling f*cos/pdf reduces to F*G2/G1
+        let fresnel = fresnel::conductor_rgb(Vec3::dot(&wo, &wm), &self.eta, &self.k);
+        let weight = fresnel*(self.distribution.g(&wo, &wi) / self.distribution.g1(&wo));
+
+        Some(BsdfSample {
+            wi: frame.to_world(&wi),
+            weight,
+            pdf: self.distribution.visible_normal_pdf(&wo, &wm) / (4.0*Vec3::dot(&wo, &wm)),
+            flags: BsdfFlags::GLOSSY | BsdfFlags::REFLECTION,
+            origin: None,
+        })
+    }
+
+    fn eval(&self, r: &Ray, hit_record: &HitRecord, wi: &Vec3) -> Color {
+        let frame = hit_record.shading_frame();
+        let wo = frame.to_local(&(-r.dir().unit_vector()));
+        let wi = frame.to_local(wi);
+        if wo.z() <= 0.0 || wi.z() <= 0.0 {
+            return Color::new(0.0, 0.0, 0.0);
+        }
+
+        // Torrance-Sparrow F*D*G / (4 cos_o cos_i), times cos_i
+        let wm = (wo + wi).unit_vector();
+        let fresnel = fresnel::conductor_rgb(Vec3::dot(&wo, &wm), &self.eta, &self.k);
+        fresnel*(self.distribution.d(&wm)*self.distribution.g(&wo, &wi) / (4.0*wo.z()))
+    }
+
+    fn pdf(&self, r: &Ray, hit_record: &HitRecord, wi: &Vec3) -> f64 {
+        let frame = hit_record.shading_frame();
+        let wo = frame.to_local(&(-r.dir().unit_vector()));
+        let wi = frame.to_local(wi);
+        if wo.z() <= 0.0 || wi.z() <= 0.0 {
+            return 0.0;
+        }
+
+        // Visible normal density times the Jacobian of reflection
+        let wm = (wo + wi).unit_vector();
+        self.distribution.visible_normal_pdf(&wo, &wm) / (4.0*Vec3::dot(&wo, &wm))
+    }
+
+    fn flags(&self) -> BsdfFlags {
+        BsdfFlags::GLOSSY | BsdfFlags::REFLECTION
+    }
+
+    fn albedo(&self, _hit_record: &HitRecord) -> Color {
+        fresnel::conductor_rgb(1.0, &self.eta, &self.k)
+    }
+}
@@ -1,17 +1,31 @@
 use crate::color::Color;
 use crate::hittable::HitRecord;
-use crate::material::Material;
+use crate::material::{BsdfFlags, BsdfSample, Ior, Material, ThinFilm};
 use crate::random_f64;
 use crate::ray::Ray;
 use crate::vec3::Vec3;
 
 pub struct Dielectric {
-    eta: f64,
+    ior: Ior,
+    film: Option<ThinFilm>,
 }
 
 impl Dielectric {
     pub fn new(refractive_index: f64) -> Self {
-        Dielectric {eta: refractive_index}
+        Dielectric {ior: Ior::Constant(refractive_index), film: None}
+    }
+
+    /// Dielectric whose refractive index varies with wavelength, which
+    /// splits light into a rainbow in spectral mode
+    pub fn dispersive(ior: Ior) -> Self {
+        Dielectric {ior, film: None}
+    }
+
+    /// Coats the surface with a thin film, for soap bubbles (with a
+    /// refractive index of 1) and coated lenses
+    pub fn thin_film(mut self, film: ThinFilm) -> Self {
+        self.film = Some(film);
+        self
     }
 
     /// Schlick reflectance approximation
@@ -23,25 +37,47 @@ impl Dielectric {
 }
 
 impl Material for Dielectric {
-    fn scatter(&self, r: &Ray, hit_record: &HitRecord) -> Option<(Color, Ray)> {
-        let eta_ratio = if hit_record.front_face {1.0/self.eta} else {self.eta};
+    fn sample(&self, r: &Ray, hit_record: &HitRecord) -> Option<BsdfSample> {
+        let eta = self.ior.at(r.wavelength());
+        let eta_ratio = if hit_record.front_face {1.0/eta} else {eta};
         let unit_direction = r.dir().unit_vector();
 
         let cos_theta = Vec3::dot(&(-(&unit_direction)), hit_record.normal()).min(1.0);
         let sin_theta = (1.0 - cos_theta*cos_theta).sqrt();
 
         let cannot_refract = eta_ratio * sin_theta > 1.0;
-        let direction = if cannot_refract || Self::reflectance(cos_theta, self.eta) > random_f64() {
-            // Snell's law has no solution; must reflect
-            unit_direction.reflect(hit_record.normal())
+
+        // A film reflects colors differently, so reflection is picked with
+        // the average reflectance and the weights make up for the rest
+        let reflectance = match &self.film {
+            _ if cannot_refract => Color::WHITE,
+            Some(film) => {
+                let (eta_i, eta_t) = if hit_record.front_face {(1.0, eta)} else {(eta, 1.0)};
+                film.dielectric(hit_record, cos_theta, eta_i, eta_t, r.wavelength())
+            },
+            None => Color::gray(Self::reflectance(cos_theta, eta)),
+        };
+        let probability = reflectance.sum() / 3.0;
+
+        let (direction, weight, lobe) = if probability > random_f64() {
+            // Always reflect when Snell's law has no solution
+            (unit_direction.reflect(hit_record.normal()), reflectance / probability, BsdfFlags::REFLECTION)
         } else {
-            // Snell's law has a solution; can refract
-            unit_direction.refract(hit_record.normal(), eta_ratio)
+            // Otherwise refract
+            let weight = (1.0 - reflectance) / (1.0 - probability);
+            (unit_direction.refract(hit_record.normal(), eta_ratio), weight, BsdfFlags::TRANSMISSION)
         };
-        
-        let scattered = Ray::new(hit_record.p().clone(), direction);
-        let attenuation = Color::new(1.0, 1.0, 1.0);
 
-        Some((attenuation, scattered))
+        Some(BsdfSample {
+            wi: direction.unit_vector(),
+            weight,
+            pdf: 0.0,
+            flags: BsdfFlags::SPECULAR | lobe,
+            origin: None,
+        })
+    }
+
+    fn flags(&self) -> BsdfFlags {
+        BsdfFlags::SPECULAR | BsdfFlags::REFLECTION | BsdfFlags::TRANSMISSION
     }
 }
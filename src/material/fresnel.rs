@@ -0,0 +1,84 @@
+use crate::color::Color;
+
+/// Exact Fresnel reflectance of a conductor for unpolarized light
+///
+/// cos_theta_i is the cosine between the incident direction and the normal,
+/// eta and k the real and imaginary parts of the conductor's refractive
+/// index relative to the outside medium.
+pub fn conductor(cos_theta_i: f64, eta: f64, k: f64) -> f64 {
+    let cos2 = cos_theta_i.clamp(0.0, 1.0).powi(2);
+    let sin2 = 1.0 - cos2;
+    let eta2 = eta*eta;
+    let k2 = k*k;
+
+    let t0 = eta2 - k2 - sin2;
+    let a2_plus_b2 = (t0*t0 + 4.0*eta2*k2).sqrt();
+    let t1 = a2_plus_b2 + cos2;
+    let a = (0.5*(a2_plus_b2 + t0)).max(0.0).sqrt();
+    let t2 = 2.0*cos_theta_i.clamp(0.0, 1.0)*a;
+    let r_s = (t1 - t2) / (t1 + t2);
+
+    let t3 = cos2*a2_plus_b2 + sin2*sin2;
+    let t4 = t2*sin2;
+    let r_p = r_s*(t3 - t4) / (t3 + t4);
+
+    0.5*(r_s + r_p)
+}
+
+/// Exact Fresnel reflectance of a dielectric interface for unpolarized light
+///
+/// eta is the refractive index on the far side of the interface relative to
+/// the side the light comes from. A negative cos_theta_i means the light
+/// arrives from the far side. Returns 1 on total internal reflection.
+pub fn dielectric(cos_theta_i: f64, eta: f64) -> f64 {
+    let (cos_theta_i, eta) = if cos_theta_i < 0.0 {(-cos_theta_i, 1.0/eta)} else {(cos_theta_i, eta)};
+    let cos_theta_i = cos_theta_i.min(1.0);
+
+    // Snell's law
+    let sin2_theta_t = (1.0 - cos_theta_i*cos_theta_i) / (eta*eta);
+    if sin2_theta_t >= 1.0 {
+        return 1.0;
+    }
+    let cos_theta_t = (1.0 - sin2_theta_t).sqrt();
+
+    let r_parallel = (eta*cos_theta_i - cos_theta_t) / (eta*cos_theta_i + cos_theta_t);
+    let r_perpendicular = (cos_theta_i - eta*cos_theta_t) / (cos_theta_i + eta*cos_theta_t);
+    0.5*(r_parallel*r_parallel + r_perpendicular*r_perpendicular)
+}
+
+/// conductor() evaluated per color channel
+pub fn conductor_rgb(cos_theta_i: f64, eta: &Color, k: &Color) -> Color {
+    Color::new(
+        conductor(cos_theta_i, eta.r(), k.r()),
+        conductor(cos_theta_i, eta.g(), k.g()),
+        conductor(cos_theta_i, eta.b(), k.b()),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use approx::assert_ulps_eq;
+    use super::*;
+
+    #[test]
+    fn test_conductor_normal_incidence() {
+        // At normal incidence R = ((n - 1)^2 + k^2) / ((n + 1)^2 + k^2)
+        let (eta, k) = (0.2, 3.9);
+        let expected = ((eta - 1.0)*(eta - 1.0) + k*k) / ((eta + 1.0)*(eta + 1.0) + k*k);
+        assert_ulps_eq!(conductor(1.0, eta, k), expected, max_ulps = 8);
+    }
+
+    #[test]
+    fn test_dielectric() {
+        // Glass at normal incidence reflects 4%
+        assert_ulps_eq!(dielectric(1.0, 1.5), 0.04, max_ulps = 8);
+        assert_ulps_eq!(dielectric(-1.0, 1.5), 0.04, max_ulps = 8);
+        // Total internal reflection from inside beyond the critical angle
+        assert_ulps_eq!(dielectric(-0.5, 1.5), 1.0);
+    }
+
+    #[test]
+    fn test_conductor_grazing() {
+        assert_ulps_eq!(conductor(0.0, 0.2, 3.9), 1.0);
+    }
+}
@@ -0,0 +1,56 @@
+use crate::spectrum::LAMBDA_D;
+
+/// Refractive index of a dielectric, possibly varying with wavelength
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Ior {
+    Constant(f64),
+    /// n = a + b / lambda^2 with lambda in um
+    Cauchy {a: f64, b: f64},
+    /// n^2 = 1 + sum of b[i]*lambda^2 / (lambda^2 - c[i]) with lambda in um
+    Sellmeier {b: [f64; 3], c: [f64; 3]},
+}
+
+impl Ior {
+    /// Schott N-BK7, common optical crown glass
+    pub const BK7: Ior = Ior::Sellmeier {
+        b: [1.03961212, 0.231792344, 1.01046945],
+        c: [0.00600069867, 0.0200179144, 103.560653],
+    };
+
+    /// Schott SF11, a dense flint glass with strong dispersion
+    pub const SF11: Ior = Ior::Sellmeier {
+        b: [1.73759695, 0.313747346, 1.89878101],
+        c: [0.013188707, 0.0623068142, 155.23629],
+    };
+
+    /// Refractive index at wavelength in nm, or at the d-line when
+    /// rendering in RGB
+    pub fn at(&self, wavelength: Option<f64>) -> f64 {
+        let lambda = wavelength.unwrap_or(LAMBDA_D) / 1000.0;
+        let lambda2 = lambda*lambda;
+        match *self {
+            Ior::Constant(n) => n,
+            Ior::Cauchy {a, b} => a + b/lambda2,
+            Ior::Sellmeier {b, c} => {
+                let sum: f64 = b.iter().zip(c.iter()).map(|(b, c)| b*lambda2 / (lambda2 - c)).sum();
+                (1.0 + sum).sqrt()
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use approx::assert_relative_eq;
+    use super::*;
+
+    #[test]
+    fn test_bk7() {
+        // Catalogue values at the d-line and F (486.1 nm) and C (656.3 nm) lines
+        assert_relative_eq!(Ior::BK7.at(None), 1.5168, epsilon = 1e-4);
+        assert_relative_eq!(Ior::BK7.at(Some(486.1)), 1.5224, epsilon = 1e-4);
+        assert_relative_eq!(Ior::BK7.at(Some(656.3)), 1.5143, epsilon = 1e-4);
+        // Matching Cauchy fit
+        assert_relative_eq!(Ior::Cauchy {a: 1.5046, b: 0.0042}.at(None), 1.5168, epsilon = 1e-3);
+    }
+}
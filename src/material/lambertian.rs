@@ -1,6 +1,7 @@
+use crate::PI;
 use crate::color::Color;
 use crate::hittable::HitRecord;
-use crate::material::Material;
+use crate::material::{BsdfFlags, BsdfSample, Material};
 use crate::ray::Ray;
 use crate::vec3::Vec3;
 
@@ -15,14 +16,40 @@ impl Lambertian {
 }
 
 impl Material for Lambertian {
-    fn scatter(&self, _: &Ray, hit_record: &HitRecord) -> Option<(Color, Ray)> {
+    fn sample(&self, r: &Ray, hit_record: &HitRecord) -> Option<BsdfSample> {
         let scatter_direction = hit_record.normal() + Vec3::random_unit_vector();
 
         // Handle case where scatter_direction ~= Vec::new(0.0, 0.0, 0.0)
-        let scatter_direction = if scatter_direction.is_near_zero() {hit_record.normal().clone()} else {scatter_direction};
+        let scatter_direction = if scatter_direction.is_near_zero() {*hit_record.normal()} else {scatter_direction};
 
-        let scattered = Ray::new(hit_record.p().clone(), scatter_direction);
-        let attenuation = self.albedo.clone();
-        Some((attenuation, scattered))
+        // Cosine weighted, so the weight is just the albedo
+        let wi = scatter_direction.unit_vector();
+        Some(BsdfSample {
+            wi,
+            weight: self.albedo,
+            pdf: self.pdf(r, hit_record, &wi),
+            flags: BsdfFlags::DIFFUSE | BsdfFlags::REFLECTION,
+            origin: None,
+        })
+    }
+
+    fn eval(&self, _: &Ray, hit_record: &HitRecord, wi: &Vec3) -> Color {
+        let cos_theta = Vec3::dot(wi, hit_record.normal());
+        if cos_theta <= 0.0 {
+            return Color::new(0.0, 0.0, 0.0);
+        }
+        self.albedo*(cos_theta / PI)
+    }
+
+    fn pdf(&self, _: &Ray, hit_record: &HitRecord, wi: &Vec3) -> f64 {
+        Vec3::dot(wi, hit_record.normal()).max(0.0) / PI
+    }
+
+    fn flags(&self) -> BsdfFlags {
+        BsdfFlags::DIFFUSE | BsdfFlags::REFLECTION
+    }
+
+    fn albedo(&self, _hit_record: &HitRecord) -> Color {
+        self.albedo
     }
 }
@@ -1,12 +1,14 @@
+use crate::PI;
 use crate::color::Color;
 use crate::hittable::HitRecord;
-use crate::material::Material;
+use crate::material::{BsdfFlags, BsdfSample, Material, ThinFilm};
 use crate::ray::Ray;
 use crate::vec3::Vec3;
 
 pub struct Metal {
     albedo: Color,
     fuzz: f64,
+    film: Option<ThinFilm>,
 }
 
 impl Metal {
@@ -14,20 +16,117 @@ impl Metal {
         Metal {
             albedo,
             fuzz: if fuzz < 1.0 {fuzz} else {1.0},
+            film: None,
         }
     }
+
+    /// Coats the metal with a thin film, for iridescent heat tints and coatings
+    pub fn thin_film(mut self, film: ThinFilm) -> Self {
+        self.film = Some(film);
+        self
+    }
+
+    /// Fraction of light reflected into wi, the albedo unless there's a film
+    fn reflectance(&self, r: &Ray, hit_record: &HitRecord, wi: &Vec3) -> Color {
+        match &self.film {
+            Some(film) => {
+                let half = (wi - r.dir().unit_vector()).unit_vector();
+                let cos_theta = Vec3::dot(wi, &half).clamp(0.0, 1.0);
+                film.conductor(hit_record, cos_theta, &self.albedo, r.wavelength())
+            },
+            None => self.albedo,
+        }
+    }
+
+    fn lobe(&self) -> BsdfFlags {
+        if self.fuzz > 0.0 {BsdfFlags::GLOSSY} else {BsdfFlags::SPECULAR}
+    }
+
+    /// Density of the direction of reflected + fuzz*p for p uniform in the unit ball
+    ///
+    /// reflected + fuzz*p is uniform in a ball of radius fuzz around reflected,
+    /// so the density of w is the volume of that ball along w, weighted by
+    /// the squared distance, over the volume of the ball.
+    fn fuzz_pdf(reflected: &Vec3, fuzz: f64, w: &Vec3) -> f64 {
+        // Solve |t*w - reflected| = fuzz with |reflected| = |w| = 1
+        let b = Vec3::dot(w, reflected);
+        let discriminant = b*b - (1.0 - fuzz*fuzz);
+        if discriminant <= 0.0 {
+            return 0.0;
+        }
+        let t_far = b + discriminant.sqrt();
+        let t_near = (b - discriminant.sqrt()).max(0.0);
+        if t_far <= 0.0 {
+            return 0.0;
+        }
+        (t_far.powi(3) - t_near.powi(3)) / (4.0*PI*fuzz.powi(3))
+    }
 }
 
 impl Material for Metal {
-    fn scatter(&self, r: &Ray, hit_record: &HitRecord) -> Option<(Color, Ray)> {
+    fn sample(&self, r: &Ray, hit_record: &HitRecord) -> Option<BsdfSample> {
         let reflected = r.dir().unit_vector().reflect(hit_record.normal());
-        let scattered = Ray::new(hit_record.p().clone(), reflected + self.fuzz*Vec3::random_in_unit_sphere());
-        let attenuation = self.albedo.clone();
+        let direction = reflected + self.fuzz*Vec3::random_in_unit_sphere();
         
-        if Vec3::dot(scattered.dir(), hit_record.normal()) > 0.0 {
-            Some((attenuation, scattered))
+        if Vec3::dot(&direction, hit_record.normal()) > 0.0 {
+            let wi = direction.unit_vector();
+            Some(BsdfSample {
+                wi,
+                weight: self.reflectance(r, hit_record, &wi),
+                pdf: self.pdf(r, hit_record, &wi),
+                flags: self.lobe() | BsdfFlags::REFLECTION,
+                origin: None,
+            })
         } else {
             None
         }
     }
+
+    // Fuzzed directions below the surface are absorbed, so f*cos is the
+    // reflectance times the density of the directions above it
+    fn eval(&self, r: &Ray, hit_record: &HitRecord, wi: &Vec3) -> Color {
+        self.reflectance(r, hit_record, wi)*self.pdf(r, hit_record, wi)
+    }
+
+    fn pdf(&self, r: &Ray, hit_record: &HitRecord, wi: &Vec3) -> f64 {
+        if self.fuzz <= 0.0 || Vec3::dot(wi, hit_record.normal()) <= 0.0 {
+            return 0.0;
+        }
+        let reflected = r.dir().unit_vector().reflect(hit_record.normal());
+        Self::fuzz_pdf(&reflected, self.fuzz, wi)
+    }
+
+    fn flags(&self) -> BsdfFlags {
+        self.lobe() | BsdfFlags::REFLECTION
+    }
+
+    fn albedo(&self, _hit_record: &HitRecord) -> Color {
+        self.albedo
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use approx::assert_relative_eq;
+    use super::*;
+
+    #[test]
+    fn test_fuzz_pdf_is_normalized() {
+        // Midpoint rule over the whole sphere of directions
+        let (n_theta, n_phi) = (2000, 200);
+        let (d_theta, d_phi) = (PI / n_theta as f64, 2.0*PI / n_phi as f64);
+        let reflected = Vec3::new(0.6, 0.0, 0.8);
+        for fuzz in [0.3, 1.0] {
+            let mut total = 0.0;
+            for i in 0..n_theta {
+                let theta = (i as f64 + 0.5)*d_theta;
+                for j in 0..n_phi {
+                    let phi = (j as f64 + 0.5)*d_phi;
+                    let w = Vec3::new(theta.sin()*phi.cos(), theta.sin()*phi.sin(), theta.cos());
+                    total += Metal::fuzz_pdf(&reflected, fuzz, &w)*theta.sin()*d_theta*d_phi;
+                }
+            }
+            assert_relative_eq!(total, 1.0, epsilon = 1e-3);
+        }
+    }
 }
@@ -0,0 +1,120 @@
+use crate::PI;
+use crate::vec3::Vec3;
+
+/// GGX / Trowbridge-Reitz microfacet distribution with Smith
+/// height-correlated masking-shadowing
+///
+/// All directions are in local shading space, where +z is the macro-surface
+/// normal and x, y are the tangents the two roughnesses are measured along.
+#[derive(Debug, Clone, Copy)]
+pub struct Ggx {
+    alpha_x: f64,
+    alpha_y: f64,
+}
+
+impl Ggx {
+    /// Smallest alpha that is sampled without numerical trouble, anything
+    /// smoother is indistinguishable from a perfect mirror anyway
+    const MIN_ALPHA: f64 = 1e-4;
+
+    pub fn new(alpha_x: f64, alpha_y: f64) -> Self {
+        Ggx {
+            alpha_x: alpha_x.max(Self::MIN_ALPHA),
+            alpha_y: alpha_y.max(Self::MIN_ALPHA),
+        }
+    }
+
+    /// Distribution from perceptual roughness in [0, 1] (alpha = roughness^2)
+    pub fn from_roughness(roughness_x: f64, roughness_y: f64) -> Self {
+        Self::new(roughness_x*roughness_x, roughness_y*roughness_y)
+    }
+
+    /// Density of microfacet normals wm
+    pub fn d(&self, wm: &Vec3) -> f64 {
+        if wm.z() <= 0.0 {
+            return 0.0;
+        }
+        let x = wm.x() / self.alpha_x;
+        let y = wm.y() / self.alpha_y;
+        let e = x*x + y*y + wm.z()*wm.z();
+        1.0 / (PI*self.alpha_x*self.alpha_y*e*e)
+    }
+
+    /// Smith lambda function for direction w
+    pub fn lambda(&self, w: &Vec3) -> f64 {
+        let cos2 = w.z()*w.z();
+        if cos2 == 0.0 {
+            return f64::INFINITY;
+        }
+        let x = w.x()*self.alpha_x;
+        let y = w.y()*self.alpha_y;
+        let alpha2_tan2 = (x*x + y*y) / cos2;
+        0.5*((1.0 + alpha2_tan2).sqrt() - 1.0)
+    }
+
+    /// Fraction of microfacets visible from w
+    pub fn g1(&self, w: &Vec3) -> f64 {
+        1.0 / (1.0 + self.lambda(w))
+    }
+
+    /// Fraction of microfacets visible from both wo and wi
+    pub fn g(&self, wo: &Vec3, wi: &Vec3) -> f64 {
+        1.0 / (1.0 + self.lambda(wo) + self.lambda(wi))
+    }
+
+    /// Samples a microfacet normal from the distribution of normals visible
+    /// from wo (Heitz 2018), u1 and u2 being uniform in [0, 1)
+    ///
+    /// wo must be in the upper hemisphere.
+    pub fn sample_visible_normal(&self, wo: &Vec3, u1: f64, u2: f64) -> Vec3 {
+        // Stretch wo to the configuration where alpha = 1
+        let vh = Vec3::new(self.alpha_x*wo.x(), self.alpha_y*wo.y(), wo.z()).unit_vector();
+
+        // Basis around vh
+        let len_sq = vh.x()*vh.x() + vh.y()*vh.y();
+        let t1 = if len_sq > 0.0 {Vec3::new(-vh.y(), vh.x(), 0.0) / len_sq.sqrt()} else {Vec3::new(1.0, 0.0, 0.0)};
+        let t2 = Vec3::cross(&vh, &t1);
+
+        // Uniform point on the projected half disc
+        let r = u1.sqrt();
+        let phi = 2.0*PI*u2;
+        let p1 = r*phi.cos();
+        let s = 0.5*(1.0 + vh.z());
+        let p2 = (1.0 - s)*(1.0 - p1*p1).sqrt() + s*r*phi.sin();
+
+        // Reproject onto the hemisphere and unstretch
+        let nh = p1*t1 + p2*t2 + (1.0 - p1*p1 - p2*p2).max(0.0).sqrt()*vh;
+        Vec3::new(self.alpha_x*nh.x(), self.alpha_y*nh.y(), nh.z().max(1e-6)).unit_vector()
+    }
+
+    /// Density of sample_visible_normal returning wm
+    pub fn visible_normal_pdf(&self, wo: &Vec3, wm: &Vec3) -> f64 {
+        if wo.z() == 0.0 {
+            return 0.0;
+        }
+        self.g1(wo)*Vec3::dot(wo, wm).max(0.0)*self.d(wm) / wo.z().abs()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use approx::assert_relative_eq;
+    use super::*;
+    use crate::material::test_util::integrate;
+
+    #[test]
+    fn test_projected_area_is_one() {
+        // The projected area of the microfacets must equal that of the macro-surface
+        let ggx = Ggx::new(0.3, 0.6);
+        let area = integrate(0.5*PI, |wm| ggx.d(wm)*wm.z());
+        assert_relative_eq!(area, 1.0, epsilon = 1e-3);
+    }
+
+    #[test]
+    fn test_visible_normal_pdf_is_normalized() {
+        let ggx = Ggx::new(0.5, 0.5);
+        let wo = Vec3::new(0.6, 0.0, 0.8);
+        let total = integrate(0.5*PI, |wm| ggx.visible_normal_pdf(&wo, wm));
+        assert_relative_eq!(total, 1.0, epsilon = 1e-3);
+    }
+}
@@ -0,0 +1,125 @@
+use std::rc::Rc;
+
+use crate::clamp;
+use crate::color::Color;
+use crate::hittable::HitRecord;
+use crate::material::{BsdfFlags, BsdfSample, Material};
+use crate::random_f64;
+use crate::ray::Ray;
+use crate::texture::{IntoTexture, Texture};
+use crate::vec3::Vec3;
+
+/// Blend of two materials, eg. rust patches on a metal
+///
+/// weight is the fraction of b at each point, from a constant or a mask
+/// texture. Each scatter picks one of the materials at random with that
+/// probability.
+pub struct MixMaterial {
+    a: Rc<dyn Material>,
+    b: Rc<dyn Material>,
+    weight: Rc<dyn Texture>,
+}
+
+impl MixMaterial {
+    pub fn new<T: IntoTexture>(a: &Rc<dyn Material>, b: &Rc<dyn Material>, weight: T) -> Self {
+        MixMaterial {
+            a: Rc::clone(a),
+            b: Rc::clone(b),
+            weight: weight.into_texture(),
+        }
+    }
+
+    fn weight(&self, hit_record: &HitRecord) -> f64 {
+        clamp(self.weight.scalar(hit_record.u, hit_record.v, hit_record.p()), 0.0, 1.0)
+    }
+}
+
+impl Material for MixMaterial {
+    fn sample(&self, r: &Ray, hit_record: &HitRecord) -> Option<BsdfSample> {
+        let w = self.weight(hit_record);
+        let chosen = if random_f64() < w {&self.b} else {&self.a};
+        let sample = chosen.sample(r, hit_record)?;
+
+        // The probability of choosing a delta lobe cancels with its weight in the mix
+        if sample.flags.is_delta() {
+            return Some(sample);
+        }
+        let pdf = self.pdf(r, hit_record, &sample.wi);
+        if pdf <= 0.0 {
+            return None;
+        }
+        Some(BsdfSample {weight: self.eval(r, hit_record, &sample.wi) / pdf, pdf, ..sample})
+    }
+
+    fn eval(&self, r: &Ray, hit_record: &HitRecord, wi: &Vec3) -> Color {
+        let w = self.weight(hit_record);
+        (1.0 - w)*self.a.eval(r, hit_record, wi) + w*self.b.eval(r, hit_record, wi)
+    }
+
+    fn pdf(&self, r: &Ray, hit_record: &HitRecord, wi: &Vec3) -> f64 {
+        let w = self.weight(hit_record);
+        (1.0 - w)*self.a.pdf(r, hit_record, wi) + w*self.b.pdf(r, hit_record, wi)
+    }
+
+    fn flags(&self) -> BsdfFlags {
+        self.a.flags() | self.b.flags()
+    }
+
+    fn albedo(&self, hit_record: &HitRecord) -> Color {
+        let w = self.weight(hit_record);
+        (1.0 - w)*self.a.albedo(hit_record) + w*self.b.albedo(hit_record)
+    }
+
+    fn emitted(&self, hit_record: &HitRecord) -> Color {
+        let w = self.weight(hit_record);
+        (1.0 - w)*self.a.emitted(hit_record) + w*self.b.emitted(hit_record)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use approx::assert_relative_eq;
+    use super::*;
+    use crate::material::{Conductor, Dielectric, Lambertian};
+    use crate::material::test_util::hit;
+
+    #[test]
+    fn test_weighted_combination() {
+        let a: Rc<dyn Material> = Rc::new(Lambertian::new(Color::new(0.8, 0.4, 0.2)));
+        let b: Rc<dyn Material> = Rc::new(Conductor::gold(0.4));
+        let (r, hit_record) = hit(Rc::new(MixMaterial::new(&a, &b, 0.3)));
+        let mix = hit_record.material();
+
+        for _ in 0..100 {
+            let wi = match mix.sample(&r, &hit_record) {
+                Some(sample) => sample.wi,
+                None => continue,
+            };
+            let pdf = 0.7*a.pdf(&r, &hit_record, &wi) + 0.3*b.pdf(&r, &hit_record, &wi);
+            assert_relative_eq!(mix.pdf(&r, &hit_record, &wi), pdf, max_relative = 1e-12);
+            let eval = 0.7*a.eval(&r, &hit_record, &wi) + 0.3*b.eval(&r, &hit_record, &wi);
+            let mixed = mix.eval(&r, &hit_record, &wi);
+            (0..3).for_each(|i| assert_relative_eq!(mixed[i], eval[i], max_relative = 1e-12));
+        }
+    }
+
+    #[test]
+    fn test_delta_lobes_pass_through() {
+        // Glass samples come out as the glass made them, as often as it's picked
+        let a: Rc<dyn Material> = Rc::new(Lambertian::new(Color::new(0.8, 0.4, 0.2)));
+        let b: Rc<dyn Material> = Rc::new(Dielectric::new(1.5));
+        let (r, hit_record) = hit(Rc::new(MixMaterial::new(&a, &b, 0.3)));
+
+        let n = 10_000;
+        let mut delta = 0;
+        for _ in 0..n {
+            let sample = hit_record.material().sample(&r, &hit_record).unwrap();
+            if sample.flags.is_delta() {
+                delta += 1;
+                assert_eq!(sample.pdf, 0.0);
+                (0..3).for_each(|i| assert_relative_eq!(sample.weight[i], 1.0, max_relative = 1e-12));
+            }
+        }
+        assert_relative_eq!(delta as f64 / n as f64, 0.3, epsilon = 0.02);
+    }
+}
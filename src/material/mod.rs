@@ -1,18 +1,198 @@
+pub mod add;
+pub mod conductor;
 pub mod dielectric;
+pub mod fresnel;
+pub mod ior;
 pub mod lambertian;
 pub mod metal;
+pub mod microfacet;
+pub mod mix;
+pub mod normal_map;
+pub mod principled;
+pub mod rough_dielectric;
+pub mod sheen;
+pub mod thin_film;
+
+#[cfg(test)]
+mod test_util;
 
 // Re-export structs that implement Material
+pub use add::AddMaterial;
+pub use conductor::Conductor;
 pub use dielectric::Dielectric;
+pub use ior::Ior;
 pub use lambertian::Lambertian;
 pub use metal::Metal;
+pub use mix::MixMaterial;
+pub use normal_map::{BumpMap, NormalMap};
+pub use principled::Principled;
+pub use rough_dielectric::RoughDielectric;
+pub use sheen::Sheen;
+pub use thin_film::ThinFilm;
+
+use std::ops;
 
 use crate::color::Color;
 use crate::hittable::HitRecord;
 use crate::ray::Ray;
+use crate::vec3::{Point3, Vec3};
+
+/// Kinds of scattering a material or one of its lobes does
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct BsdfFlags(u8);
+
+impl BsdfFlags {
+    pub const NONE: BsdfFlags = BsdfFlags(0);
+    pub const REFLECTION: BsdfFlags = BsdfFlags(1);
+    pub const TRANSMISSION: BsdfFlags = BsdfFlags(2);
+    pub const DIFFUSE: BsdfFlags = BsdfFlags(4);
+    pub const GLOSSY: BsdfFlags = BsdfFlags(8);
+    pub const SPECULAR: BsdfFlags = BsdfFlags(16);  // Delta distribution, eval and pdf are always 0
+
+    /// Whether all of the flags in other are set
+    pub fn contains(self, other: BsdfFlags) -> bool {
+        self.0 & other.0 == other.0
+    }
+
+    /// Whether the only way to scatter is through delta lobes, which
+    /// light sampling can never hit
+    pub fn is_delta(self) -> bool {
+        self.contains(BsdfFlags::SPECULAR) && !self.contains(BsdfFlags::DIFFUSE) && !self.contains(BsdfFlags::GLOSSY)
+    }
+}
+
+impl ops::BitOr for BsdfFlags {
+    type Output = BsdfFlags;
 
+    fn bitor(self, other: BsdfFlags) -> BsdfFlags {
+        BsdfFlags(self.0 | other.0)
+    }
+}
+
+/// Direction sampled by Material::sample
+#[derive(Debug, Clone, Copy)]
+pub struct BsdfSample {
+    pub wi: Vec3,         // Unit direction the light arrives from, pointing away from the surface
+    pub weight: Color,    // eval / pdf, the factor the path throughput is multiplied by
+    pub pdf: f64,         // Solid angle density of wi, 0 for delta lobes
+    pub flags: BsdfFlags, // Lobe wi was sampled from
+    pub origin: Option<Point3>,  // Where the path continues from if not the hit point, eg. after subsurface scattering
+}
+
+/// Surface scattering, described as a BSDF
+///
+/// Directions are unit vectors in world space pointing away from the hit
+/// point. wo is towards the viewer, ie. -r.dir() for the ray r that hit the
+/// surface, and wi is where the light comes from. The ray is passed rather
+/// than wo so materials can tell how far it travelled.
+///
+/// Materials shade with hit_record.normal(), which wrappers like BumpMap
+/// may tilt away from hit_record.geometric_normal(). Callers go through
+/// the sample, eval and pdf functions of this module, which drop the
+/// directions where the two normals disagree.
+///
+/// Materials implement sample, and eval and pdf unless they are delta.
+/// scatter is derived from sample for code written against the older API.
 pub trait Material {
+    /// Samples wi for light leaving towards -r.dir(), None if the ray is absorbed
+    fn sample(&self, r: &Ray, hit_record: &HitRecord) -> Option<BsdfSample>;
+
+    /// BSDF times |cos| of wi with the normal, for light arriving along wi
+    /// and leaving towards -r.dir()
+    fn eval(&self, _r: &Ray, _hit_record: &HitRecord, _wi: &Vec3) -> Color {
+        Color::new(0.0, 0.0, 0.0)
+    }
+
+    /// Solid angle density of sample returning wi
+    fn pdf(&self, _r: &Ray, _hit_record: &HitRecord, _wi: &Vec3) -> f64 {
+        0.0
+    }
+
+    /// Union of the flags of every lobe of the material
+    fn flags(&self) -> BsdfFlags {
+        BsdfFlags::SPECULAR | BsdfFlags::REFLECTION | BsdfFlags::TRANSMISSION
+    }
+
+    /// Reflectance of the surface at the hit point, used as the albedo pass
+    /// and to guide the denoiser. White for materials that don't tint the
+    /// light, like clear glass.
+    fn albedo(&self, _hit_record: &HitRecord) -> Color {
+        Color::new(1.0, 1.0, 1.0)
+    }
+
     /// Takes an incident ray r and a hit record
     /// Returns an Option<(attenuation, scattered ray)>
-    fn scatter(&self, r: &Ray, hit_record: &HitRecord) -> Option<(Color, Ray)>;
+    ///
+    /// Derived from sample through the module's sample function, like
+    /// integrator::trace. Materials shouldn't override it.
+    fn scatter(&self, r: &Ray, hit_record: &HitRecord) -> Option<(Color, Ray)> {
+        let sample = sample(self, r, hit_record)?;
+        let origin = sample.origin.unwrap_or(*hit_record.p());
+        Some((sample.weight, Ray::new(origin, sample.wi).with_wavelength(r.wavelength()).with_time(r.time())))
+    }
+
+    /// Light emitted by the surface at the hit point
+    fn emitted(&self, _hit_record: &HitRecord) -> Color {
+        Color::new(0.0, 0.0, 0.0)
+    }
+}
+
+/// material.sample, rejecting wi on opposite sides of the surface by the
+/// shading and geometric normals
+///
+/// Following the shading normal there would let light leak through the
+/// surface or reflect from inside it. Samples leaving from another point,
+/// like after subsurface scattering, aren't checked.
+pub fn sample<M: Material + ?Sized>(material: &M, r: &Ray, hit_record: &HitRecord) -> Option<BsdfSample> {
+    material.sample(r, hit_record).filter(|sample| sample.origin.is_some() || consistent(hit_record, &sample.wi))
+}
+
+/// material.eval, black where wi is on opposite sides of the surface by the
+/// shading and geometric normals
+pub fn eval<M: Material + ?Sized>(material: &M, r: &Ray, hit_record: &HitRecord, wi: &Vec3) -> Color {
+    if consistent(hit_record, wi) {material.eval(r, hit_record, wi)} else {Color::new(0.0, 0.0, 0.0)}
+}
+
+/// material.pdf, 0 where wi is on opposite sides of the surface by the
+/// shading and geometric normals, matching sample
+pub fn pdf<M: Material + ?Sized>(material: &M, r: &Ray, hit_record: &HitRecord, wi: &Vec3) -> f64 {
+    if consistent(hit_record, wi) {material.pdf(r, hit_record, wi)} else {0.0}
+}
+
+/// Whether wi is on the same side of the surface by both the geometric
+/// and shading normals
+fn consistent(hit_record: &HitRecord, wi: &Vec3) -> bool {
+    Vec3::dot(wi, hit_record.geometric_normal())*Vec3::dot(wi, hit_record.normal()) >= 0.0
+}
+
+#[cfg(test)]
+mod tests {
+    use std::rc::Rc;
+
+    use super::*;
+    use crate::hittable::Hittable;
+    use crate::hittable::sphere::Sphere;
+    use crate::INFINITY;
+
+    #[test]
+    fn test_shading_normal_doesnt_leak() {
+        // A shading normal tilted 60 degrees from the surface of a sphere
+        let diffuse: Rc<dyn Material> = Rc::new(Lambertian::new(Color::new(0.8, 0.8, 0.8)));
+        let sphere = Sphere::new(Point3::new(0.0, 0.0, 0.0), 1.0, &diffuse);
+        let r = Ray::new(Point3::new(0.0, 0.0, 5.0), Vec3::new(0.0, 0.0, -1.0));
+        let hit_record = sphere.hit(&r, 0.001, INFINITY).unwrap();
+        let tilted = Vec3::new(60.0_f64.to_radians().sin(), 0.0, 60.0_f64.to_radians().cos());
+        let hit_record = hit_record.with_normal(tilted);
+        let below = |wi: &Vec3| Vec3::dot(wi, hit_record.geometric_normal()) < 0.0;
+
+        // Following the shading normal alone sends some light into the sphere
+        assert!((0..1000).filter_map(|_| diffuse.sample(&r, &hit_record)).any(|sample| below(&sample.wi)));
+        assert!((0..1000).filter_map(|_| sample(diffuse.as_ref(), &r, &hit_record)).all(|sample| !below(&sample.wi)));
+
+        // eval and pdf agree with sample below the surface
+        let wi = Vec3::new(1.0, 0.0, -0.2).unit_vector();
+        assert!(diffuse.eval(&r, &hit_record, &wi).r() > 0.0);
+        assert_eq!(eval(diffuse.as_ref(), &r, &hit_record, &wi).r(), 0.0);
+        assert_eq!(pdf(diffuse.as_ref(), &r, &hit_record, &wi), 0.0);
+    }
 }
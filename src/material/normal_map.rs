@@ -0,0 +1,194 @@
+use std::rc::Rc;
+
+use crate::color::Color;
+use crate::hittable::HitRecord;
+use crate::material::{self, BsdfFlags, BsdfSample, Material};
+use crate::ray::Ray;
+use crate::texture::Texture;
+use crate::vec3::Vec3;
+
+/// Perturbs the shading normals of a material with a tangent-space normal map
+///
+/// Texels are read as (x, y, z) = 2*color - 1, with x along dpdu, y along
+/// dpdv and z along the surface normal. Load image maps with
+/// ImageTexture::open_data so they aren't gamma decoded.
+pub struct NormalMap {
+    material: Rc<dyn Material>,
+    map: Rc<dyn Texture>,
+    strength: f64,  // Scales the tilt of the normals, 1 is the map as is
+}
+
+impl NormalMap {
+    pub fn new(material: &Rc<dyn Material>, map: &Rc<dyn Texture>, strength: f64) -> Self {
+        NormalMap {
+            material: Rc::clone(material),
+            map: Rc::clone(map),
+            strength,
+        }
+    }
+
+    fn shading_normal(&self, hit_record: &HitRecord) -> Vec3 {
+        let c = self.map.value(hit_record.u, hit_record.v, hit_record.p());
+        let x = (2.0*c.r() - 1.0)*self.strength;
+        let y = (2.0*c.g() - 1.0)*self.strength;
+        let z = 2.0*c.b() - 1.0;
+
+        // The bitangent follows dpdv so maps aren't mirrored on back faces
+        let frame = hit_record.shading_frame();
+        let bitangent = if Vec3::dot(frame.v(), &hit_record.dpdv) < 0.0 {-frame.v()} else {*frame.v()};
+        let normal = x*frame.u() + y*bitangent + z*frame.w();
+        if normal.is_near_zero() {*hit_record.normal()} else {normal.unit_vector()}
+    }
+}
+
+/// Perturbs the shading normals of a material as if its surface was
+/// displaced along the normal by a grayscale height map
+pub struct BumpMap {
+    material: Rc<dyn Material>,
+    height: Rc<dyn Texture>,
+    scale: f64,  // Displacement in world units for a height of 1
+}
+
+impl BumpMap {
+    /// Step in u and v used to take finite differences of the height
+    const DELTA: f64 = 5e-4;
+
+    pub fn new(material: &Rc<dyn Material>, height: &Rc<dyn Texture>, scale: f64) -> Self {
+        BumpMap {
+            material: Rc::clone(material),
+            height: Rc::clone(height),
+            scale,
+        }
+    }
+
+    fn shading_normal(&self, hit_record: &HitRecord) -> Vec3 {
+        let (u, v, p) = (hit_record.u, hit_record.v, hit_record.p());
+        let height = |du: f64, dv: f64| {
+            let p = p + du*hit_record.dpdu + dv*hit_record.dpdv;
+            self.scale*self.height.scalar(u + du, v + dv, &p)
+        };
+        let h = height(0.0, 0.0);
+        let dhdu = (height(Self::DELTA, 0.0) - h) / Self::DELTA;
+        let dhdv = (height(0.0, Self::DELTA) - h) / Self::DELTA;
+
+        // Bumps rise out of the front face. The tangents of the displaced
+        // surface p + h*n are dpdu + dhdu*n and dpdv + dhdv*n, neglecting
+        // the change of n itself.
+        let normal = *hit_record.normal();
+        let outward = if hit_record.front_face {normal} else {-normal};
+        let dpdu = hit_record.dpdu + dhdu*outward;
+        let dpdv = hit_record.dpdv + dhdv*outward;
+        let bumped = Vec3::cross(&dpdu, &dpdv);
+        if bumped.is_near_zero() {
+            return normal;
+        }
+
+        // The cross product follows the parameterization, not the side r is on
+        let bumped = bumped.unit_vector();
+        if Vec3::dot(&bumped, &normal) < 0.0 {-bumped} else {bumped}
+    }
+}
+
+/// Replaces the shading normal of hit_record, unless r would then see the
+/// back of the shading surface
+fn shade(r: &Ray, hit_record: &HitRecord, normal: Vec3) -> HitRecord {
+    if Vec3::dot(r.dir(), &normal) < 0.0 {hit_record.with_normal(normal)} else {hit_record.clone()}
+}
+
+/// Implements Material for a wrapper with a shading_normal method by
+/// forwarding to its material with the shading normal replaced
+macro_rules! impl_shading_normal_material {
+    ($Wrapper:ident) => {
+        impl Material for $Wrapper {
+            fn sample(&self, r: &Ray, hit_record: &HitRecord) -> Option<BsdfSample> {
+                let hit_record = shade(r, hit_record, self.shading_normal(hit_record));
+                material::sample(self.material.as_ref(), r, &hit_record)
+            }
+
+            fn eval(&self, r: &Ray, hit_record: &HitRecord, wi: &Vec3) -> Color {
+                let hit_record = shade(r, hit_record, self.shading_normal(hit_record));
+                material::eval(self.material.as_ref(), r, &hit_record, wi)
+            }
+
+            fn pdf(&self, r: &Ray, hit_record: &HitRecord, wi: &Vec3) -> f64 {
+                let hit_record = shade(r, hit_record, self.shading_normal(hit_record));
+                material::pdf(self.material.as_ref(), r, &hit_record, wi)
+            }
+
+            fn flags(&self) -> BsdfFlags {
+                self.material.flags()
+            }
+
+            fn albedo(&self, hit_record: &HitRecord) -> Color {
+                self.material.albedo(hit_record)
+            }
+
+            fn emitted(&self, hit_record: &HitRecord) -> Color {
+                self.material.emitted(hit_record)
+            }
+        }
+    };
+}
+
+impl_shading_normal_material!(NormalMap);
+impl_shading_normal_material!(BumpMap);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::material::Lambertian;
+    use crate::material::test_util::hit;
+    use crate::texture::SolidColor;
+    use crate::vec3::Point3;
+
+    /// Height rising linearly with u
+    struct Ramp;
+
+    impl Texture for Ramp {
+        fn value(&self, u: f64, _v: f64, _p: &Point3) -> Color {
+            Color::gray(u)
+        }
+    }
+
+    fn base() -> Rc<dyn Material> {
+        Rc::new(Lambertian::new(Color::gray(0.5)))
+    }
+
+    fn solid(c: Color) -> Rc<dyn Texture> {
+        Rc::new(SolidColor::new(c))
+    }
+
+    #[test]
+    fn test_normal_map() {
+        let (_, hit_record) = hit(base());
+        let normal = *hit_record.normal();
+        let tangent = hit_record.dpdu.unit_vector();
+
+        // A flat texel keeps the normal
+        let flat = NormalMap::new(&base(), &solid(Color::new(0.5, 0.5, 1.0)), 1.0);
+        assert!((flat.shading_normal(&hit_record) - normal).length() < 1e-12);
+
+        // Tilting the texel along +x turns the normal towards dpdu
+        let tilted = NormalMap::new(&base(), &solid(Color::new(0.75, 0.5, 1.0)), 1.0).shading_normal(&hit_record);
+        assert!((tilted.length() - 1.0).abs() < 1e-12);
+        assert!(Vec3::dot(&tilted, &tangent) > Vec3::dot(&normal, &tangent) + 0.1);
+        assert!(Vec3::dot(&tilted, &normal) > 0.0);
+    }
+
+    #[test]
+    fn test_bump_map() {
+        let (_, hit_record) = hit(base());
+        let normal = *hit_record.normal();
+
+        // A constant height is a flat surface
+        let flat = BumpMap::new(&base(), &solid(Color::gray(0.7)), 0.1);
+        assert!((flat.shading_normal(&hit_record) - normal).length() < 1e-9);
+
+        // A slope rising along u tilts the normal back against it
+        let ramp: Rc<dyn Texture> = Rc::new(Ramp);
+        let bumped = BumpMap::new(&base(), &ramp, 0.5).shading_normal(&hit_record);
+        assert!((bumped.length() - 1.0).abs() < 1e-12);
+        assert!(Vec3::dot(&bumped, &hit_record.dpdu) < -1e-3);
+        assert!(Vec3::dot(&bumped, &normal) > 0.0);
+    }
+}
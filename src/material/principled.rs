@@ -0,0 +1,363 @@
+use std::rc::Rc;
+
+use crate::color::Color;
+use crate::hittable::HitRecord;
+use crate::material::{BsdfFlags, BsdfSample, Material};
+use crate::material::fresnel;
+use crate::material::microfacet::Ggx;
+use crate::{clamp, random_f64, PI};
+use crate::ray::Ray;
+use crate::texture::{IntoTexture, Texture};
+use crate::vec3::Vec3;
+
+/// Refractive index of the clearcoat layer (polyurethane)
+const CLEARCOAT_IOR: f64 = 1.5;
+
+/// Layered "uber" material with the familiar knobs of the Disney / OpenPBR
+/// principled BSDFs
+///
+/// From top to bottom the layers are a clearcoat, then either a metal or a
+/// dielectric base. The dielectric base has a specular layer over either a
+/// diffuse surface with sheen, or a transmissive one. Every parameter is a
+/// texture; scalar parameters read the texture's first channel.
+///
+/// Built with a chain of setters, eg.
+/// Principled::new(Color::new(0.8, 0.1, 0.1)).metallic(1.0).roughness(0.3)
+pub struct Principled {
+    base_color: Rc<dyn Texture>,
+    metallic: Rc<dyn Texture>,
+    roughness: Rc<dyn Texture>,
+    specular: Rc<dyn Texture>,       // Reflectance of the dielectric base, 0.5 is 4% at normal incidence
+    specular_tint: Rc<dyn Texture>,  // How much the dielectric specular is tinted by the base color
+    sheen: Rc<dyn Texture>,
+    sheen_tint: Rc<dyn Texture>,
+    clearcoat: Rc<dyn Texture>,
+    clearcoat_roughness: Rc<dyn Texture>,
+    transmission: Rc<dyn Texture>,
+    ior: Rc<dyn Texture>,
+    emission: Rc<dyn Texture>,
+}
+
+/// Parameters of a Principled material evaluated at a hit point
+struct Parameters {
+    base_color: Color,
+    metallic: f64,
+    roughness: f64,
+    specular: f64,
+    specular_tint: f64,
+    sheen: f64,
+    sheen_tint: f64,
+    clearcoat: f64,
+    clearcoat_roughness: f64,
+    transmission: f64,
+    ior: f64,
+}
+
+impl Principled {
+    /// Rough white plastic-like material with the given base color
+    pub fn new<T: IntoTexture>(base_color: T) -> Self {
+        Principled {
+            base_color: base_color.into_texture(),
+            metallic: 0.0.into_texture(),
+            roughness: 0.5.into_texture(),
+            specular: 0.5.into_texture(),
+            specular_tint: 0.0.into_texture(),
+            sheen: 0.0.into_texture(),
+            sheen_tint: 0.5.into_texture(),
+            clearcoat: 0.0.into_texture(),
+            clearcoat_roughness: 0.03.into_texture(),
+            transmission: 0.0.into_texture(),
+            ior: 1.5.into_texture(),
+            emission: Color::new(0.0, 0.0, 0.0).into_texture(),
+        }
+    }
+
+    pub fn base_color<T: IntoTexture>(mut self, t: T) -> Self {self.base_color = t.into_texture(); self}
+    pub fn metallic<T: IntoTexture>(mut self, t: T) -> Self {self.metallic = t.into_texture(); self}
+    pub fn roughness<T: IntoTexture>(mut self, t: T) -> Self {self.roughness = t.into_texture(); self}
+    pub fn specular<T: IntoTexture>(mut self, t: T) -> Self {self.specular = t.into_texture(); self}
+    pub fn specular_tint<T: IntoTexture>(mut self, t: T) -> Self {self.specular_tint = t.into_texture(); self}
+    pub fn sheen<T: IntoTexture>(mut self, t: T) -> Self {self.sheen = t.into_texture(); self}
+    pub fn sheen_tint<T: IntoTexture>(mut self, t: T) -> Self {self.sheen_tint = t.into_texture(); self}
+    pub fn clearcoat<T: IntoTexture>(mut self, t: T) -> Self {self.clearcoat = t.into_texture(); self}
+    pub fn clearcoat_roughness<T: IntoTexture>(mut self, t: T) -> Self {self.clearcoat_roughness = t.into_texture(); self}
+    pub fn transmission<T: IntoTexture>(mut self, t: T) -> Self {self.transmission = t.into_texture(); self}
+    pub fn ior<T: IntoTexture>(mut self, t: T) -> Self {self.ior = t.into_texture(); self}
+    pub fn emission<T: IntoTexture>(mut self, t: T) -> Self {self.emission = t.into_texture(); self}
+
+    fn parameters(&self, hit_record: &HitRecord) -> Parameters {
+        let (u, v, p) = (hit_record.u, hit_record.v, hit_record.p());
+        let unit = |t: &Rc<dyn Texture>| clamp(t.scalar(u, v, p), 0.0, 1.0);
+        Parameters {
+            base_color: self.base_color.value(u, v, p),
+            metallic: unit(&self.metallic),
+            roughness: unit(&self.roughness),
+            specular: self.specular.scalar(u, v, p).max(0.0),
+            specular_tint: unit(&self.specular_tint),
+            sheen: self.sheen.scalar(u, v, p).max(0.0),
+            sheen_tint: unit(&self.sheen_tint),
+            clearcoat: unit(&self.clearcoat),
+            clearcoat_roughness: unit(&self.clearcoat_roughness),
+            transmission: unit(&self.transmission),
+            ior: self.ior.scalar(u, v, p).max(1.0 + 1e-4),
+        }
+    }
+}
+
+/// Hue and saturation of c at unit luminance
+fn tint(c: &Color) -> Color {
+    let l = c.luminance();
+    if l > 0.0 {c / l} else {Color::new(1.0, 1.0, 1.0)}
+}
+
+fn mix(a: &Color, b: &Color, t: f64) -> Color {
+    (1.0 - t)*a + t*b
+}
+
+fn schlick(f0: &Color, cos_theta: f64) -> Color {
+    f0 + (Color::new(1.0, 1.0, 1.0) - f0)*(1.0 - cos_theta).clamp(0.0, 1.0).powi(5)
+}
+
+/// Everything about a Principled material at a hit point that depends on wo only
+struct Lobes {
+    params: Parameters,
+    eta: f64,  // Refractive index beyond the surface relative to the side wo is on
+    specular_color: Color,
+    base: Ggx,
+    coat: Ggx,
+    // Probabilities of sampling each lobe. Except for the clearcoat and the
+    // specular/diffuse split these are also the weights of the layers.
+    clearcoat: f64,
+    metal: f64,
+    transmission: f64,
+    dielectric: f64,  // Weight of the opaque dielectric, split between the next two
+    specular: f64,
+    diffuse: f64,
+}
+
+impl Principled {
+    fn lobes(&self, hit_record: &HitRecord, wo: &Vec3) -> Lobes {
+        let params = self.parameters(hit_record);
+
+        // The dielectric specular strength sets an equivalent refractive index
+        let eta = if params.transmission > 0.0 {
+            params.ior
+        } else {
+            let s = (0.08*params.specular).sqrt().min(0.999);
+            ((1.0 + s) / (1.0 - s)).max(1.0 + 1e-4)
+        };
+        let eta = if hit_record.front_face {eta} else {1.0/eta};
+
+        // Each layer gets the light that the ones above didn't take
+        let clearcoat = params.clearcoat*fresnel::dielectric(wo.z(), CLEARCOAT_IOR);
+        let metal = (1.0 - clearcoat)*params.metallic;
+        let transmission = (1.0 - clearcoat)*(1.0 - params.metallic)*params.transmission;
+        let dielectric = (1.0 - clearcoat)*(1.0 - params.metallic)*(1.0 - params.transmission);
+        let p_specular = fresnel::dielectric(wo.z(), eta);
+
+        Lobes {
+            eta,
+            specular_color: mix(&Color::new(1.0, 1.0, 1.0), &tint(&params.base_color), params.specular_tint),
+            base: Ggx::from_roughness(params.roughness, params.roughness),
+            coat: Ggx::from_roughness(params.clearcoat_roughness, params.clearcoat_roughness),
+            clearcoat,
+            metal,
+            transmission,
+            dielectric,
+            specular: dielectric*p_specular,
+            diffuse: dielectric*(1.0 - p_specular),
+            params,
+        }
+    }
+}
+
+/// f*|cos_i| and pdf of all lobes together for local directions wo, wi
+fn eval_pdf(lobes: &Lobes, wo: &Vec3, wi: &Vec3) -> (Color, f64) {
+    let params = &lobes.params;
+    let black = Color::new(0.0, 0.0, 0.0);
+    if wo.z() <= 0.0 || wi.z() == 0.0 {
+        return (black, 0.0);
+    }
+
+    if wi.z() < 0.0 {
+        // Refraction through a generalized half vector, as in RoughDielectric
+        let wm = (wo + lobes.eta*wi).unit_vector();
+        let wm = if wm.z() < 0.0 {-wm} else {wm};
+        let (cos_o, cos_i) = (Vec3::dot(wo, &wm), Vec3::dot(wi, &wm));
+        if cos_o <= 0.0 || cos_i >= 0.0 || lobes.transmission == 0.0 {
+            return (black, 0.0);
+        }
+        let transmittance = 1.0 - fresnel::dielectric(cos_o, lobes.eta);
+        let denominator = (cos_i + cos_o/lobes.eta).powi(2);
+        let microfacet = lobes.base.d(&wm)*lobes.base.g(wo, wi)*(cos_i*cos_o).abs() / (wo.z()*denominator);
+        let pdf = lobes.base.visible_normal_pdf(wo, &wm)*cos_i.abs() / denominator;
+        let weight = lobes.transmission*transmittance;
+        return (params.base_color*(weight*microfacet), weight*pdf);
+    }
+
+    let wm = (wo + wi).unit_vector();
+    let cos_m = Vec3::dot(wo, &wm);
+    // Torrance-Sparrow D*G / (4 cos_o cos_i) times cos_i, and the pdf of reflecting about a visible normal
+    let microfacet = |ggx: &Ggx| ggx.d(&wm)*ggx.g(wo, wi) / (4.0*wo.z());
+    let reflection_pdf = |ggx: &Ggx| ggx.visible_normal_pdf(wo, &wm) / (4.0*cos_m);
+    let (base_f, base_pdf) = (microfacet(&lobes.base), reflection_pdf(&lobes.base));
+    let mut f = black;
+    let mut pdf = 0.0;
+
+    if lobes.clearcoat > 0.0 {
+        let reflectance = params.clearcoat*fresnel::dielectric(cos_m, CLEARCOAT_IOR);
+        f += Color::new(1.0, 1.0, 1.0)*(reflectance*microfacet(&lobes.coat));
+        pdf += lobes.clearcoat*reflection_pdf(&lobes.coat);
+    }
+    if lobes.metal > 0.0 {
+        f += schlick(&params.base_color, cos_m)*(lobes.metal*base_f);
+        pdf += lobes.metal*base_pdf;
+    }
+    if lobes.transmission > 0.0 {
+        // Reflected part of the transmissive layer, chosen with probability F
+        let reflectance = fresnel::dielectric(cos_m, lobes.eta);
+        f += lobes.specular_color*(lobes.transmission*reflectance*base_f);
+        pdf += lobes.transmission*reflectance*base_pdf;
+    }
+    if lobes.dielectric > 0.0 {
+        let reflectance = fresnel::dielectric(cos_m, lobes.eta);
+        f += lobes.specular_color*(lobes.dielectric*reflectance*base_f);
+        pdf += lobes.specular*base_pdf;
+
+        // Diffuse with sheen, the specular takes the light it reflects at wo
+        let sheen_color = params.sheen*mix(&Color::new(1.0, 1.0, 1.0), &tint(&params.base_color), params.sheen_tint);
+        let sheen = sheen_color*(1.0 - Vec3::dot(wi, &wm)).clamp(0.0, 1.0).powi(5);
+        f += (params.base_color + PI*sheen)*(lobes.diffuse*wi.z() / PI);
+        pdf += lobes.diffuse*wi.z() / PI;
+    }
+
+    (f, pdf)
+}
+
+impl Material for Principled {
+    fn sample(&self, r: &Ray, hit_record: &HitRecord) -> Option<BsdfSample> {
+        let frame = hit_record.shading_frame();
+        let wo = frame.to_local(&(-r.dir().unit_vector()));
+        if wo.z() <= 0.0 {
+            return None;
+        }
+        let lobes = self.lobes(hit_record, &wo);
+        let reflect = |ggx: &Ggx| (-wo).reflect(&ggx.sample_visible_normal(&wo, random_f64(), random_f64()));
+
+        // Pick a lobe in proportion to the light it scatters
+        let u = random_f64();
+        let metal_end = lobes.clearcoat + lobes.metal;
+        let transmission_end = metal_end + lobes.transmission;
+        let (wi, flags) = if u < lobes.clearcoat {
+            (reflect(&lobes.coat), BsdfFlags::GLOSSY | BsdfFlags::REFLECTION)
+        } else if u < metal_end {
+            (reflect(&lobes.base), BsdfFlags::GLOSSY | BsdfFlags::REFLECTION)
+        } else if u < transmission_end {
+            let wm = lobes.base.sample_visible_normal(&wo, random_f64(), random_f64());
+            if random_f64() < fresnel::dielectric(Vec3::dot(&wo, &wm), lobes.eta) {
+                ((-wo).reflect(&wm), BsdfFlags::GLOSSY | BsdfFlags::REFLECTION)
+            } else {
+                ((-wo).refract(&wm, 1.0/lobes.eta), BsdfFlags::GLOSSY | BsdfFlags::TRANSMISSION)
+            }
+        } else if u < transmission_end + lobes.specular {
+            (reflect(&lobes.base), BsdfFlags::GLOSSY | BsdfFlags::REFLECTION)
+        } else {
+            // Cosine weighted
+            let wi = (Vec3::new(0.0, 0.0, 1.0) + Vec3::random_unit_vector()).unit_vector();
+            let wi = if wi.is_near_zero() || wi.z() <= 0.0 {Vec3::new(0.0, 0.0, 1.0)} else {wi};
+            (wi, BsdfFlags::DIFFUSE | BsdfFlags::REFLECTION)
+        };
+
+        // A reflection that ends up below the surface, or the reverse, is absorbed
+        if flags.contains(BsdfFlags::REFLECTION) != (wi.z() > 0.0) {
+            return None;
+        }
+        let (f, pdf) = eval_pdf(&lobes, &wo, &wi);
+        if pdf <= 0.0 {
+            return None;
+        }
+        Some(BsdfSample {wi: frame.to_world(&wi), weight: f / pdf, pdf, flags, origin: None})
+    }
+
+    fn eval(&self, r: &Ray, hit_record: &HitRecord, wi: &Vec3) -> Color {
+        let frame = hit_record.shading_frame();
+        let wo = frame.to_local(&(-r.dir().unit_vector()));
+        eval_pdf(&self.lobes(hit_record, &wo), &wo, &frame.to_local(wi)).0
+    }
+
+    fn pdf(&self, r: &Ray, hit_record: &HitRecord, wi: &Vec3) -> f64 {
+        let frame = hit_record.shading_frame();
+        let wo = frame.to_local(&(-r.dir().unit_vector()));
+        eval_pdf(&self.lobes(hit_record, &wo), &wo, &frame.to_local(wi)).1
+    }
+
+    fn flags(&self) -> BsdfFlags {
+        BsdfFlags::DIFFUSE | BsdfFlags::GLOSSY | BsdfFlags::REFLECTION | BsdfFlags::TRANSMISSION
+    }
+
+    fn albedo(&self, hit_record: &HitRecord) -> Color {
+        self.base_color.value(hit_record.u, hit_record.v, hit_record.p())
+    }
+
+    fn emitted(&self, hit_record: &HitRecord) -> Color {
+        self.emission.value(hit_record.u, hit_record.v, hit_record.p())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use approx::assert_relative_eq;
+    use super::*;
+    use crate::material::test_util::{hit, integrate};
+
+    fn white() -> Principled {
+        Principled::new(Color::new(1.0, 1.0, 1.0))
+    }
+
+    #[test]
+    fn test_white_furnace() {
+        // A white diffuse surface reflects all the light, and no layering loses more than that
+        let (r, hit_record) = hit(Rc::new(white().specular(0.0).roughness(1.0)));
+        let albedo = integrate(PI, |wi| hit_record.material().eval(&r, &hit_record, wi).g());
+        assert_relative_eq!(albedo, 1.0, epsilon = 1e-2);
+
+        for material in [
+            white(),
+            white().metallic(1.0),
+            white().clearcoat(1.0).clearcoat_roughness(0.3),
+            white().sheen(1.0).roughness(0.8),
+            white().transmission(1.0).roughness(0.6),
+        ] {
+            let (r, hit_record) = hit(Rc::new(material));
+            let albedo = integrate(PI, |wi| hit_record.material().eval(&r, &hit_record, wi).g());
+            assert!(albedo > 0.5 && albedo <= 1.0 + 1e-2, "albedo {}", albedo);
+        }
+    }
+
+    #[test]
+    fn test_sample_matches_eval_pdf() {
+        for material in [
+            Principled::new(Color::new(0.8, 0.3, 0.1)).roughness(0.4),
+            white().metallic(0.5).clearcoat(0.7).clearcoat_roughness(0.2),
+            white().transmission(0.8).roughness(0.3),
+        ] {
+            let (r, hit_record) = hit(Rc::new(material));
+            let material = hit_record.material();
+            let n = 20_000;
+            let mut mean_weight = 0.0;
+            for _ in 0..n {
+                if let Some(sample) = material.sample(&r, &hit_record) {
+                    let pdf = material.pdf(&r, &hit_record, &sample.wi);
+                    assert_relative_eq!(sample.pdf, pdf, max_relative = 1e-9);
+                    let eval = material.eval(&r, &hit_record, &sample.wi);
+                    (0..3).for_each(|i| assert_relative_eq!(sample.weight[i], eval[i] / pdf, max_relative = 1e-9));
+                    mean_weight += sample.weight.g() / n as f64;
+                }
+            }
+
+            // So the mean weight estimates the albedo
+            let albedo = integrate(PI, |wi| material.eval(&r, &hit_record, wi).g());
+            assert_relative_eq!(mean_weight, albedo, epsilon = 2e-2);
+        }
+    }
+}
@@ -0,0 +1,168 @@
+use crate::color::Color;
+use crate::hittable::HitRecord;
+use crate::material::{BsdfFlags, BsdfSample, Material};
+use crate::material::fresnel;
+use crate::material::microfacet::Ggx;
+use crate::onb::Onb;
+use crate::random_f64;
+use crate::ray::Ray;
+use crate::vec3::Vec3;
+
+/// Frosted or tinted glass
+///
+/// Reflection and refraction through a GGX distribution of microfacets
+/// (Walter et al. 2007) with exact dielectric Fresnel. Light travelling
+/// inside the medium is optionally absorbed following the Beer-Lambert law.
+pub struct RoughDielectric {
+    eta: f64,
+    distribution: Ggx,
+    absorption: Color,  // Absorption coefficient per unit distance for each channel
+}
+
+impl RoughDielectric {
+    /// Clear dielectric with perceptual roughness in [0, 1]
+    pub fn new(refractive_index: f64, roughness: f64) -> Self {
+        RoughDielectric {
+            eta: refractive_index,
+            distribution: Ggx::from_roughness(roughness, roughness),
+            absorption: Color::new(0.0, 0.0, 0.0),
+        }
+    }
+
+    /// Tints the medium so that light travelling distance through it
+    /// keeps transmittance of its color
+    pub fn with_absorption(mut self, transmittance: Color, distance: f64) -> Self {
+        let sigma = |t: f64| -t.clamp(1e-6, 1.0).ln() / distance;
+        self.absorption = transmittance.map(sigma);
+        self
+    }
+
+    /// Local shading frame, wo, the refractive index beyond the surface
+    /// relative to the side wo is on and the transmittance of the medium
+    /// along r
+    fn setup(&self, r: &Ray, hit_record: &HitRecord) -> (Onb, Vec3, f64, Color) {
+        // hit_record.normal() always opposes r, so wo is in the upper hemisphere
+        let frame = hit_record.shading_frame();
+        let wo = frame.to_local(&(-r.dir().unit_vector()));
+
+        // Rays hitting the back face have travelled through the medium
+        let transmittance = if hit_record.front_face {
+            Color::new(1.0, 1.0, 1.0)
+        } else {
+            let distance = (hit_record.p() - r.origin()).length();
+            let t = |sigma: f64| (-sigma*distance).exp();
+            self.absorption.map(t)
+        };
+
+        let eta = if hit_record.front_face {self.eta} else {1.0/self.eta};
+        (frame, wo, eta, transmittance)
+    }
+
+    /// f*|cos_i| and pdf for local directions wo, wi (Walter et al. 2007)
+    fn eval_pdf(&self, wo: &Vec3, wi: &Vec3, eta: f64) -> (f64, f64) {
+        if wo.z() <= 0.0 || wi.z() == 0.0 {
+            return (0.0, 0.0);
+        }
+
+        if wi.z() > 0.0 {
+            let wm = (wo + wi).unit_vector();
+            let cos_theta_m = Vec3::dot(wo, &wm);
+            let reflectance = fresnel::dielectric(cos_theta_m, eta);
+            let f = reflectance*self.distribution.d(&wm)*self.distribution.g(wo, wi) / (4.0*wo.z());
+            let pdf = reflectance*self.distribution.visible_normal_pdf(wo, &wm) / (4.0*cos_theta_m);
+            return (f, pdf);
+        }
+
+        // Generalized half vector of refraction, facing wo
+        let wm = (wo + eta*wi).unit_vector();
+        let wm = if wm.z() < 0.0 {-wm} else {wm};
+        let (cos_o, cos_i) = (Vec3::dot(wo, &wm), Vec3::dot(wi, &wm));
+        if cos_o <= 0.0 || cos_i >= 0.0 {
+            return (0.0, 0.0);
+        }
+        let transmittance = 1.0 - fresnel::dielectric(cos_o, eta);
+        let denominator = (cos_i + cos_o/eta).powi(2);
+        let f = transmittance*self.distribution.d(&wm)*self.distribution.g(wo, wi)*(cos_i*cos_o).abs() / (wo.z()*denominator);
+        let pdf = transmittance*self.distribution.visible_normal_pdf(wo, &wm)*cos_i.abs() / denominator;
+        (f, pdf)
+    }
+}
+
+impl Material for RoughDielectric {
+    fn sample(&self, r: &Ray, hit_record: &HitRecord) -> Option<BsdfSample> {
+        let (frame, wo, eta, transmittance) = self.setup(r, hit_record);
+        if wo.z() <= 0.0 {
+            return None;
+        }
+
+        let wm = self.distribution.sample_visible_normal(&wo, random_f64(), random_f64());
+        let cos_theta_m = Vec3::dot(&wo, &wm);
+        let reflectance = fresnel::dielectric(cos_theta_m, eta);
+
+        // Choosing between reflection and refraction with probability F
+        // cancels F out of the weight, leaving G2/G1 in both cases
+        let (wi, lobe) = if random_f64() < reflectance {
+            let wi = (-wo).reflect(&wm);
+            if wi.z() <= 0.0 {
+                return None;
+            }
+            (wi, BsdfFlags::REFLECTION)
+        } else {
+            let wi = (-wo).refract(&wm, 1.0/eta);
+            if wi.z() >= 0.0 {
+                return None;
+            }
+            (wi, BsdfFlags::TRANSMISSION)
+        };
+
+        Some(BsdfSample {
+            wi: frame.to_world(&wi),
+            weight: transmittance*(self.distribution.g(&wo, &wi) / self.distribution.g1(&wo)),
+            pdf: self.eval_pdf(&wo, &wi, eta).1,
+            flags: BsdfFlags::GLOSSY | lobe,
+            origin: None,
+        })
+    }
+
+    fn eval(&self, r: &Ray, hit_record: &HitRecord, wi: &Vec3) -> Color {
+        let (frame, wo, eta, transmittance) = self.setup(r, hit_record);
+        transmittance*self.eval_pdf(&wo, &frame.to_local(wi), eta).0
+    }
+
+    fn pdf(&self, r: &Ray, hit_record: &HitRecord, wi: &Vec3) -> f64 {
+        let (frame, wo, eta, _) = self.setup(r, hit_record);
+        self.eval_pdf(&wo, &frame.to_local(wi), eta).1
+    }
+
+    fn flags(&self) -> BsdfFlags {
+        BsdfFlags::GLOSSY | BsdfFlags::REFLECTION | BsdfFlags::TRANSMISSION
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use approx::assert_relative_eq;
+    use super::*;
+
+    #[test]
+    fn test_eval_over_pdf_matches_sampling_weight() {
+        // sample() weighs both lobes by G2/G1, eval/pdf must agree with it
+        let material = RoughDielectric::new(1.5, 0.5);
+        let wo = Vec3::new(0.3, -0.2, 0.9).unit_vector();
+        for eta in [1.5, 1.0/1.5] {
+            for (u1, u2) in [(0.1, 0.2), (0.5, 0.7), (0.9, 0.4)] {
+                let wm = material.distribution.sample_visible_normal(&wo, u1, u2);
+                let g1 = material.distribution.g1(&wo);
+                for wi in [(-wo).reflect(&wm), (-wo).refract(&wm, 1.0/eta)] {
+                    if wi.z() == 0.0 || wi.length_squared() < 0.5 {
+                        continue;
+                    }
+                    let (f, pdf) = material.eval_pdf(&wo, &wi, eta);
+                    if pdf > 0.0 {
+                        assert_relative_eq!(f / pdf, material.distribution.g(&wo, &wi) / g1, max_relative = 1e-9);
+                    }
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,125 @@
+use std::rc::Rc;
+
+use crate::PI;
+use crate::color::Color;
+use crate::hittable::HitRecord;
+use crate::material::{BsdfFlags, BsdfSample, Material};
+use crate::ray::Ray;
+use crate::texture::{IntoTexture, Texture};
+use crate::vec3::Vec3;
+
+/// Cloth and velvet: light caught by fibres standing up from the surface,
+/// which glows at grazing angles
+///
+/// Uses the "Charlie" fibre distribution of Estevez and Kulla 2017 with the
+/// visibility term of Neubelt and Pettineo 2013. On its own it only gives
+/// the rim, so fabrics usually add it onto a diffuse base with AddMaterial.
+pub struct Sheen {
+    color: Rc<dyn Texture>,
+    roughness: f64,
+}
+
+impl Sheen {
+    /// roughness in (0, 1], low values keep the sheen close to the silhouette
+    pub fn new<T: IntoTexture>(color: T, roughness: f64) -> Self {
+        Sheen {
+            color: color.into_texture(),
+            roughness: roughness.clamp(0.05, 1.0),
+        }
+    }
+
+    /// BSDF (without the cosine) for light arriving along wi and leaving along wo
+    fn f(&self, hit_record: &HitRecord, wo: &Vec3, wi: &Vec3) -> Color {
+        let n = hit_record.normal();
+        let (cos_o, cos_i) = (Vec3::dot(wo, n), Vec3::dot(wi, n));
+        if cos_o <= 0.0 || cos_i <= 0.0 {
+            return Color::BLACK;
+        }
+
+        let half = (wo + wi).unit_vector();
+        let cos_h = Vec3::dot(&half, n).clamp(0.0, 1.0);
+        let sin_h = (1.0 - cos_h*cos_h).sqrt();
+        let inv_alpha = 1.0 / (self.roughness*self.roughness);
+        let d = (2.0 + inv_alpha)*sin_h.powf(inv_alpha) / (2.0*PI);
+        let visibility = 1.0 / (4.0*(cos_i + cos_o - cos_i*cos_o));
+
+        self.color.value(hit_record.u, hit_record.v, hit_record.p())*(d*visibility)
+    }
+}
+
+impl Material for Sheen {
+    fn sample(&self, r: &Ray, hit_record: &HitRecord) -> Option<BsdfSample> {
+        // Cosine weighted like Lambertian, the distribution is too broad to be worth importance sampling
+        let scatter_direction = hit_record.normal() + Vec3::random_unit_vector();
+        let scatter_direction = if scatter_direction.is_near_zero() {*hit_record.normal()} else {scatter_direction};
+        let wi = scatter_direction.unit_vector();
+
+        let pdf = self.pdf(r, hit_record, &wi);
+        if pdf <= 0.0 {
+            return None;
+        }
+        Some(BsdfSample {
+            wi,
+            weight: self.eval(r, hit_record, &wi) / pdf,
+            pdf,
+            flags: BsdfFlags::DIFFUSE | BsdfFlags::REFLECTION,
+            origin: None,
+        })
+    }
+
+    fn eval(&self, r: &Ray, hit_record: &HitRecord, wi: &Vec3) -> Color {
+        let wo = -r.dir().unit_vector();
+        self.f(hit_record, &wo, wi)*Vec3::dot(wi, hit_record.normal()).max(0.0)
+    }
+
+    fn pdf(&self, _: &Ray, hit_record: &HitRecord, wi: &Vec3) -> f64 {
+        Vec3::dot(wi, hit_record.normal()).max(0.0) / PI
+    }
+
+    fn flags(&self) -> BsdfFlags {
+        BsdfFlags::DIFFUSE | BsdfFlags::REFLECTION
+    }
+
+    fn albedo(&self, hit_record: &HitRecord) -> Color {
+        self.color.value(hit_record.u, hit_record.v, hit_record.p())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use approx::assert_relative_eq;
+    use super::*;
+    use crate::INFINITY;
+    use crate::hittable::Hittable;
+    use crate::hittable::sphere::Sphere;
+    use crate::material::test_util::integrate;
+    use crate::vec3::Point3;
+
+    #[test]
+    fn test_sheen() {
+        let color = Color::new(0.9, 0.5, 0.2);
+        for roughness in [0.2, 0.6, 1.0] {
+            let material: Rc<dyn Material> = Rc::new(Sheen::new(color, roughness));
+            let sphere = Sphere::new(Point3::new(0.0, 0.0, 0.0), 1.0, &material);
+
+            // Straight on, and close to the silhouette where the sheen is brightest
+            for x in [0.0, 0.95] {
+                let r = Ray::new(Point3::new(x, 0.0, 5.0), Vec3::new(0.0, 0.0, -1.0));
+                let hit_record = sphere.hit(&r, 0.001, INFINITY).unwrap();
+
+                for _ in 0..100 {
+                    let sample = material.sample(&r, &hit_record).unwrap();
+                    assert_relative_eq!(sample.pdf, material.pdf(&r, &hit_record, &sample.wi), max_relative = 1e-12);
+                    let eval = material.eval(&r, &hit_record, &sample.wi);
+                    (0..3).for_each(|i| assert_relative_eq!(sample.weight[i], eval[i] / sample.pdf, max_relative = 1e-12));
+                }
+
+                // Never reflects more than the sheen color
+                for i in 0..3 {
+                    let albedo = integrate(PI, |wi| material.eval(&r, &hit_record, wi)[i]);
+                    assert!(albedo > 0.0 && albedo <= color[i], "albedo {} for roughness {} at x = {}", albedo, roughness, x);
+                }
+            }
+        }
+    }
+}
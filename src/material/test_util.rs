@@ -0,0 +1,35 @@
+//! Fixtures shared by the material tests
+
+use std::rc::Rc;
+
+use crate::{INFINITY, PI};
+use crate::hittable::{HitRecord, Hittable};
+use crate::hittable::sphere::Sphere;
+use crate::material::Material;
+use crate::ray::Ray;
+use crate::vec3::{Point3, Vec3};
+
+/// A ray hitting a unit sphere of material off centre, at 30 degrees from the normal
+pub(super) fn hit(material: Rc<dyn Material>) -> (Ray, HitRecord) {
+    let sphere = Sphere::new(Point3::new(0.0, 0.0, 0.0), 1.0, &material);
+    let r = Ray::new(Point3::new(0.5, 0.0, 5.0), Vec3::new(0.0, 0.0, -1.0));
+    let hit_record = sphere.hit(&r, 0.001, INFINITY).unwrap();
+    (r, hit_record)
+}
+
+/// Integrates f over the directions within max_theta of +z with the
+/// midpoint rule, so PI covers the whole sphere and PI / 2 the upper hemisphere
+pub(super) fn integrate<F: Fn(&Vec3) -> f64>(max_theta: f64, f: F) -> f64 {
+    let (n_theta, n_phi) = (1000, 200);
+    let (d_theta, d_phi) = (max_theta / n_theta as f64, 2.0*PI / n_phi as f64);
+    let mut sum = 0.0;
+    for i in 0..n_theta {
+        let theta = (i as f64 + 0.5)*d_theta;
+        for j in 0..n_phi {
+            let phi = (j as f64 + 0.5)*d_phi;
+            let w = Vec3::new(theta.sin()*phi.cos(), theta.sin()*phi.sin(), theta.cos());
+            sum += f(&w)*theta.sin()*d_theta*d_phi;
+        }
+    }
+    sum
+}
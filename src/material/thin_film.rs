@@ -0,0 +1,208 @@
+use std::ops;
+use std::rc::Rc;
+use std::sync::OnceLock;
+
+use crate::PI;
+use crate::color::{Color, ColorSpace};
+use crate::hittable::HitRecord;
+use crate::spectrum;
+use crate::texture::{IntoTexture, Texture};
+
+/// Thin transparent coating on a surface, like a soap film, an oil slick or
+/// the anti-reflective coating of a lens
+///
+/// Light reflected off the top and the bottom of the film interferes, so
+/// how much is reflected depends on wavelength, angle and thickness. In RGB
+/// mode the reflectance is integrated over the visible spectrum into a color.
+pub struct ThinFilm {
+    thickness: f64,  // In nm
+    thickness_map: Option<Rc<dyn Texture>>,
+    ior: f64,
+}
+
+impl ThinFilm {
+    pub fn new(thickness: f64, ior: f64) -> Self {
+        ThinFilm {thickness, thickness_map: None, ior}
+    }
+
+    /// Scales the thickness by a texture, for films that thin out or swirl
+    pub fn thickness_map<T: IntoTexture>(mut self, t: T) -> Self {
+        self.thickness_map = Some(t.into_texture());
+        self
+    }
+
+    fn thickness_at(&self, hit_record: &HitRecord) -> f64 {
+        match &self.thickness_map {
+            Some(map) => self.thickness*map.scalar(hit_record.u, hit_record.v, hit_record.p()).max(0.0),
+            None => self.thickness,
+        }
+    }
+
+    /// Reflectance of the film on a dielectric, for light arriving at
+    /// cos_theta to the normal from a medium of index eta_i onto a
+    /// substrate of index eta_t
+    pub fn dielectric(&self, hit_record: &HitRecord, cos_theta: f64, eta_i: f64, eta_t: f64, wavelength: Option<f64>) -> Color {
+        let d = self.thickness_at(hit_record);
+        reflectance(wavelength, |lambda| airy(cos_theta, eta_i, self.ior, Complex::new(eta_t, 0.0), d, lambda))
+    }
+
+    /// Reflectance of the film on a conductor whose reflectance at normal
+    /// incidence (without the film) is albedo, for light arriving from air
+    pub fn conductor(&self, hit_record: &HitRecord, cos_theta: f64, albedo: &Color, wavelength: Option<f64>) -> Color {
+        let d = self.thickness_at(hit_record);
+        reflectance(wavelength, |lambda| {
+            let eta = conductor_ior(spectrum::upsample(albedo, lambda));
+            airy(cos_theta, 1.0, self.ior, eta, d, lambda)
+        })
+    }
+}
+
+/// The reflectance r at the wavelength in spectral mode, otherwise its color
+fn reflectance<F: Fn(f64) -> f64>(wavelength: Option<f64>, r: F) -> Color {
+    match wavelength {
+        Some(lambda) => Color::gray(r(lambda)),
+        None => color_weights().iter().fold(Color::BLACK, |sum, (lambda, weight)| sum + weight*r(*lambda)),
+    }
+}
+
+/// Wavelengths reflectances are sampled at in RGB mode, with the color each
+/// contributes, white balanced so a constant reflectance keeps its value
+fn color_weights() -> &'static [(f64, Color)] {
+    static WEIGHTS: OnceLock<Vec<(f64, Color)>> = OnceLock::new();
+    WEIGHTS.get_or_init(|| {
+        let steps = 40;
+        let d_lambda = 400.0 / steps as f64;
+        let weights: Vec<(f64, Color)> = (0..steps)
+            .map(|i| 380.0 + (i as f64 + 0.5)*d_lambda)
+            .map(|lambda| (lambda, ColorSpace::Srgb.from_xyz(&spectrum::cie_xyz(lambda))))
+            .collect();
+        let white = weights.iter().fold(Color::BLACK, |sum, (_, c)| sum + c);
+        weights.into_iter().map(|(lambda, c)| (lambda, c / white)).collect()
+    })
+}
+
+/// Complex refractive index of a metal reflecting r at normal incidence,
+/// using r as its edge tint too (Gulbrandsen 2014)
+fn conductor_ior(r: f64) -> Complex {
+    let r = r.clamp(0.0, 0.99);
+    let n = r*(1.0 - r) / (1.0 + r) + (1.0 - r)*(1.0 + r.sqrt()) / (1.0 - r.sqrt());
+    let k2 = (r*(n + 1.0)*(n + 1.0) - (n - 1.0)*(n - 1.0)) / (1.0 - r);
+    Complex::new(n, k2.max(0.0).sqrt())
+}
+
+/// Reflectance of a film of index n1 and thickness d (in nm) between a
+/// medium of index n0 and a substrate of index n2, for light of wavelength
+/// lambda arriving at cos_theta to the normal, summed over all the internal
+/// reflections (Airy). Unpolarized, so the average of s and p polarizations.
+fn airy(cos_theta: f64, n0: f64, n1: f64, n2: Complex, d: f64, lambda: f64) -> f64 {
+    let n0 = Complex::new(n0, 0.0);
+    let n1 = Complex::new(n1, 0.0);
+
+    // Cosines of the angles in each layer by Snell's law, imaginary past
+    // total internal reflection
+    let sin2_theta = Complex::new(1.0 - cos_theta*cos_theta, 0.0);
+    let cos_in = |n: Complex| (Complex::new(1.0, 0.0) - n0*n0*sin2_theta / (n*n)).sqrt();
+    let (c0, c1, c2) = (Complex::new(cos_theta, 0.0), cos_in(n1), cos_in(n2));
+
+    // Fresnel amplitude coefficients of each interface
+    let r_s = |ni: Complex, ci: Complex, nt: Complex, ct: Complex| (ni*ci - nt*ct) / (ni*ci + nt*ct);
+    let r_p = |ni: Complex, ci: Complex, nt: Complex, ct: Complex| (nt*ci - ni*ct) / (nt*ci + ni*ct);
+
+    // Phase difference of a round trip through the film
+    let phase = (Complex::new(0.0, 4.0*PI*d / lambda)*n1*c1).exp();
+    let total = |r01: Complex, r12: Complex| {
+        let r12 = r12*phase;
+        ((r01 + r12) / (Complex::new(1.0, 0.0) + r01*r12)).norm_squared()
+    };
+
+    let s = total(r_s(n0, c0, n1, c1), r_s(n1, c1, n2, c2));
+    let p = total(r_p(n0, c0, n1, c1), r_p(n1, c1, n2, c2));
+    (0.5*(s + p)).clamp(0.0, 1.0)
+}
+
+/// Complex number, just enough of one for the Fresnel equations
+#[derive(Debug, Clone, Copy)]
+struct Complex {
+    re: f64,
+    im: f64,
+}
+
+impl Complex {
+    fn new(re: f64, im: f64) -> Self {
+        Complex {re, im}
+    }
+
+    fn norm_squared(self) -> f64 {
+        self.re*self.re + self.im*self.im
+    }
+
+    fn exp(self) -> Complex {
+        let m = self.re.exp();
+        Complex::new(m*self.im.cos(), m*self.im.sin())
+    }
+
+    /// Principal square root, which has a non-negative real part
+    fn sqrt(self) -> Complex {
+        let m = self.norm_squared().sqrt();
+        let re = (0.5*(m + self.re)).max(0.0).sqrt();
+        let im = (0.5*(m - self.re)).max(0.0).sqrt();
+        Complex::new(re, if self.im < 0.0 {-im} else {im})
+    }
+}
+
+impl ops::Add for Complex {
+    type Output = Complex;
+
+    fn add(self, other: Complex) -> Complex {
+        Complex::new(self.re + other.re, self.im + other.im)
+    }
+}
+
+impl ops::Sub for Complex {
+    type Output = Complex;
+
+    fn sub(self, other: Complex) -> Complex {
+        Complex::new(self.re - other.re, self.im - other.im)
+    }
+}
+
+impl ops::Mul for Complex {
+    type Output = Complex;
+
+    fn mul(self, other: Complex) -> Complex {
+        Complex::new(self.re*other.re - self.im*other.im, self.re*other.im + self.im*other.re)
+    }
+}
+
+impl ops::Div for Complex {
+    type Output = Complex;
+
+    fn div(self, other: Complex) -> Complex {
+        let d = other.norm_squared();
+        Complex::new((self.re*other.re + self.im*other.im) / d, (self.im*other.re - self.re*other.im) / d)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use approx::assert_relative_eq;
+    use super::*;
+    use crate::material::fresnel;
+
+    #[test]
+    fn test_airy() {
+        // Without a film, or with a film matching the substrate, it's plain Fresnel
+        for cos_theta in [1.0, 0.7, 0.2] {
+            let expected = fresnel::dielectric(cos_theta, 1.5);
+            assert_relative_eq!(airy(cos_theta, 1.0, 1.38, Complex::new(1.5, 0.0), 0.0, 550.0), expected, epsilon = 1e-9);
+            assert_relative_eq!(airy(cos_theta, 1.0, 1.5, Complex::new(1.5, 0.0), 300.0, 550.0), expected, epsilon = 1e-9);
+        }
+
+        // A quarter-wave layer of index sqrt(1.5) cancels reflection off glass at normal incidence
+        let n1 = 1.5_f64.sqrt();
+        assert_relative_eq!(airy(1.0, 1.0, n1, Complex::new(1.5, 0.0), 550.0 / (4.0*n1), 550.0), 0.0, epsilon = 1e-12);
+
+        // Total internal reflection stays total with a film
+        assert_relative_eq!(airy(0.3, 1.5, 1.38, Complex::new(1.0, 0.0), 200.0, 550.0), 1.0, epsilon = 1e-9);
+    }
+}
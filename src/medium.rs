@@ -0,0 +1,132 @@
+use crate::PI;
+use crate::color::Color;
+use crate::onb::Onb;
+use crate::spectrum;
+use crate::vec3::Vec3;
+
+/// Uniform participating medium: how much light it scatters and absorbs
+/// per unit length, and in which directions it scatters
+#[derive(Debug, Clone, Copy)]
+pub struct Medium {
+    sigma_s: Color,  // Scattering coefficient
+    sigma_a: Color,  // Absorption coefficient
+    phase: HenyeyGreenstein,
+}
+
+impl Medium {
+    /// Medium given by its scattering and absorption coefficients, which
+    /// scatters isotropically
+    pub fn new(sigma_s: Color, sigma_a: Color) -> Self {
+        Medium {sigma_s, sigma_a, phase: HenyeyGreenstein::new(0.0)}
+    }
+
+    /// Medium that looks roughly color when light scatters around in it a
+    /// lot, with light travelling mean_free_path (per channel) between
+    /// scattering events
+    ///
+    /// color is turned into the albedo of single scattering events with the
+    /// fit of Chiang et al. 2016, since multiple scattering brightens it.
+    pub fn from_albedo(color: Color, mean_free_path: Color) -> Self {
+        let single_scattering_albedo = color.map(|a| {
+            let a = a.clamp(0.0, 0.999);
+            let s = 4.09712 + 4.20863*a - (9.59217 + 41.6808*a + 17.7126*a*a).sqrt();
+            1.0 - s*s
+        });
+        let sigma_t = mean_free_path.map(|d| 1.0 / d.max(1e-6));
+        Self::new(single_scattering_albedo*sigma_t, (1.0 - single_scattering_albedo)*sigma_t)
+    }
+
+    /// Mean cosine of the scattering angle, in (-1, 1). Skin and milk
+    /// scatter forward (around 0.8), the default is isotropic.
+    pub fn anisotropy(mut self, g: f64) -> Self {
+        self.phase = HenyeyGreenstein::new(g);
+        self
+    }
+
+    pub fn phase(&self) -> &HenyeyGreenstein {
+        &self.phase
+    }
+
+    /// Scattering and extinction (scattering + absorption) coefficients,
+    /// upsampled to the given wavelength in spectral mode
+    pub fn coefficients(&self, wavelength: Option<f64>) -> (Color, Color) {
+        let sigma_t = self.sigma_s + self.sigma_a;
+        match wavelength {
+            Some(lambda) => (
+                Color::gray(spectrum::upsample(&self.sigma_s, lambda)),
+                Color::gray(spectrum::upsample(&sigma_t, lambda)),
+            ),
+            None => (self.sigma_s, sigma_t),
+        }
+    }
+}
+
+/// Henyey-Greenstein phase function, the angular distribution of light
+/// scattered inside a participating medium
+///
+/// g in (-1, 1) is the mean cosine of the scattering angle: positive values
+/// scatter forward, negative values backward and 0 is isotropic.
+#[derive(Debug, Clone, Copy)]
+pub struct HenyeyGreenstein {
+    g: f64,
+}
+
+impl HenyeyGreenstein {
+    pub fn new(g: f64) -> Self {
+        HenyeyGreenstein {g: g.clamp(-0.99, 0.99)}
+    }
+
+    /// Density, per steradian, of turning by an angle whose cosine is cos_theta
+    pub fn eval(&self, cos_theta: f64) -> f64 {
+        let g = self.g;
+        let denom = 1.0 + g*g - 2.0*g*cos_theta;
+        (1.0 - g*g) / (4.0*PI*denom*denom.sqrt())
+    }
+
+    /// Samples the direction light travelling along the unit vector dir
+    /// continues in, from u1 and u2 uniform in [0, 1). The phase function is
+    /// its own pdf, so scattered light keeps its weight.
+    pub fn sample(&self, dir: &Vec3, u1: f64, u2: f64) -> Vec3 {
+        let g = self.g;
+        let cos_theta = if g.abs() < 1e-3 {
+            1.0 - 2.0*u1
+        } else {
+            let s = (1.0 - g*g) / (1.0 - g + 2.0*g*u1);
+            (1.0 + g*g - s*s) / (2.0*g)
+        };
+        let cos_theta = cos_theta.clamp(-1.0, 1.0);
+        let sin_theta = (1.0 - cos_theta*cos_theta).sqrt();
+        let phi = 2.0*PI*u2;
+        Onb::from_w(dir).to_world(&Vec3::new(sin_theta*phi.cos(), sin_theta*phi.sin(), cos_theta))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use approx::assert_relative_eq;
+    use super::*;
+
+    #[test]
+    fn test_henyey_greenstein() {
+        for g in [-0.7, 0.0, 0.3, 0.9] {
+            let phase = HenyeyGreenstein::new(g);
+
+            // Integrates to 1 over the sphere
+            let steps = 100000;
+            let integral: f64 = (0..steps)
+                .map(|i| -1.0 + 2.0*(i as f64 + 0.5) / steps as f64)
+                .map(|cos_theta| 2.0*PI*phase.eval(cos_theta)*(2.0 / steps as f64))
+                .sum();
+            assert_relative_eq!(integral, 1.0, epsilon = 1e-4);
+
+            // Sampled directions have a mean cosine of g
+            let dir = Vec3::new(0.0, 0.6, 0.8);
+            let n = 64;
+            let mean: f64 = (0..n*n)
+                .map(|i| phase.sample(&dir, ((i / n) as f64 + 0.5) / n as f64, ((i % n) as f64 + 0.5) / n as f64))
+                .map(|wi| Vec3::dot(&wi, &dir))
+                .sum::<f64>() / (n*n) as f64;
+            assert_relative_eq!(mean, g, epsilon = 1e-2);
+        }
+    }
+}
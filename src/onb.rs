@@ -0,0 +1,52 @@
+use crate::vec3::Vec3;
+
+/// Orthonormal basis
+///
+/// Used to move directions between world space and a local shading space
+/// where w is the surface normal and u, v are tangents.
+#[derive(Debug, Clone, Copy)]
+pub struct Onb {
+    u: Vec3,
+    v: Vec3,
+    w: Vec3,
+}
+
+impl Onb {
+    /// Builds a basis around the unit vector w with arbitrary tangents
+    ///
+    /// Uses the branchless construction of Duff et al. 2017, so the tangents
+    /// vary continuously over a surface.
+    pub fn from_w(w: &Vec3) -> Self {
+        let sign = 1.0_f64.copysign(w.z());
+        let a = -1.0 / (sign + w.z());
+        let b = w.x()*w.y()*a;
+        let u = Vec3::new(1.0 + sign*w.x()*w.x()*a, sign*b, -sign*w.x());
+        let v = Vec3::new(b, sign + w.y()*w.y()*a, -w.y());
+        Onb {u, v, w: *w}
+    }
+
+    /// Builds a basis around the unit vector w with u as close to the
+    /// given tangent as possible, falling back to from_w if it is parallel to w
+    pub fn from_wu(w: &Vec3, tangent: &Vec3) -> Self {
+        let u = tangent - Vec3::dot(tangent, w)*w;
+        if u.is_near_zero() {
+            return Self::from_w(w);
+        }
+        let u = u.unit_vector();
+        Onb {u, v: Vec3::cross(w, &u), w: *w}
+    }
+
+    pub fn u(&self) -> &Vec3 {&self.u}
+    pub fn v(&self) -> &Vec3 {&self.v}
+    pub fn w(&self) -> &Vec3 {&self.w}
+
+    /// Converts a direction in local coordinates to world space
+    pub fn to_world(&self, a: &Vec3) -> Vec3 {
+        a.x()*self.u + a.y()*self.v + a.z()*self.w
+    }
+
+    /// Converts a world space direction to local coordinates
+    pub fn to_local(&self, a: &Vec3) -> Vec3 {
+        Vec3::new(Vec3::dot(a, &self.u), Vec3::dot(a, &self.v), Vec3::dot(a, &self.w))
+    }
+}
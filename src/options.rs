@@ -0,0 +1,218 @@
+use std::path::PathBuf;
+use std::str::FromStr;
+
+use crate::aov::Aov;
+use crate::color::ColorSpace;
+use crate::film::Region;
+
+pub const USAGE: &str = "\
+Usage: rusty-raytracer [options]
+
+Options:
+    --scene <name>                  Scene to render (default: lots_of_random_spheres)
+    --output <file>                 Output image, format taken from the extension (default: output.png)
+    --output-color-space <space>    Color space of the output image: srgb, rec2020, acescg or xyz,
+                                    stored with its usual transfer function (default: srgb)
+    --seed <n>                      Seed for the random number generator (default: from the clock)
+    --spp <n>                       Override the scene's samples per pixel
+    --spectral                      Trace single wavelengths instead of RGB, for dispersion
+    --samples-per-pass <n>          Samples added to every pixel per progressive pass (default: 10)
+    --checkpoint <file>             Periodically save the render to this checkpoint file
+    --checkpoint-interval <secs>    Seconds between checkpoints (default: 300)
+    --resume <file>                 Continue the render saved in this checkpoint file
+    --aovs <list>                   Comma separated render passes to write, or 'all':
+                                    depth, normal, albedo, material_id, object_id,
+                                    direct, indirect, emission, sample_count
+    --aov-output <file>             Where to write the passes (default: aovs.exr). An .exr file gets
+                                    every pass as a layer, other formats get one image per pass
+    --denoise                       Denoise the image after rendering, guided by first-hit normals,
+                                    albedo and depth
+    --denoise-iterations <n>        Number of denoising filter passes (default: 5)
+    --stats-json <file>             Also write the render statistics to this file as JSON
+    --crop <x,y,width,height>       Only render this rectangle of pixels (origin at the top left)
+    --crop-output <crop|border>     Write just the cropped pixels, or the full frame with everything
+                                    outside the crop black for stitching distributed renders (default: crop)
+    --composite-into <file>         Paste the cropped pixels into this existing full frame image instead
+    --bloom <intensity>             Add a glow around overexposed pixels, eg. 0.1
+    --glare <intensity>             Add star shaped streaks to overexposed pixels, eg. 0.05
+    --white-balance <kelvin>        Make light of this color temperature look white (6500 is neutral)
+    --contrast <x>                  Contrast around middle grey, 1 leaves it unchanged
+    --saturation <x>                Color saturation, 0 for black and white, 1 leaves it unchanged
+    --lut <file.cube>               Apply a 3D color lookup table to the sRGB encoded image
+    --frames <first..last>          Render these frames (inclusive) of an animated scene instead of
+                                    its whole timeline, numbering the output files, eg. output_0001.png
+    --help                          Print this message";
+
+/// What the output image holds when only a region was rendered
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CropOutput {
+    Crop,    // Just the rendered region
+    Border,  // The full frame, black outside the rendered region
+}
+
+/// Command line options of the renderer
+#[derive(Debug, Clone)]
+pub struct Options {
+    pub scene: String,
+    pub output: PathBuf,
+    pub output_color_space: ColorSpace,
+    pub seed: Option<u64>,
+    pub samples_per_pixel: Option<u32>,
+    pub samples_per_pass: u32,
+    pub checkpoint: Option<PathBuf>,
+    pub checkpoint_interval: u64,
+    pub resume: Option<PathBuf>,
+    pub aovs: Vec<Aov>,
+    pub aov_output: PathBuf,
+    pub spectral: bool,
+    pub denoise: bool,
+    pub denoise_iterations: u32,
+    pub stats_json: Option<PathBuf>,
+    pub crop: Option<Region>,
+    pub crop_output: CropOutput,
+    pub composite_into: Option<PathBuf>,
+    pub bloom: Option<f64>,
+    pub glare: Option<f64>,
+    pub white_balance: Option<f64>,
+    pub contrast: Option<f64>,
+    pub saturation: Option<f64>,
+    pub lut: Option<PathBuf>,
+    pub frames: Option<(u32, u32)>,  // First and last frame, inclusive
+    pub help: bool,
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Options {
+            scene: String::from("lots_of_random_spheres"),
+            output: PathBuf::from("output.png"),
+            output_color_space: ColorSpace::Srgb,
+            seed: None,
+            samples_per_pixel: None,
+            samples_per_pass: 10,
+            checkpoint: None,
+            checkpoint_interval: 300,
+            resume: None,
+            aovs: vec![],
+            aov_output: PathBuf::from("aovs.exr"),
+            spectral: false,
+            denoise: false,
+            denoise_iterations: 5,
+            stats_json: None,
+            crop: None,
+            crop_output: CropOutput::Crop,
+            composite_into: None,
+            bloom: None,
+            glare: None,
+            white_balance: None,
+            contrast: None,
+            saturation: None,
+            lut: None,
+            frames: None,
+            help: false,
+        }
+    }
+}
+
+impl Options {
+    /// Parses options from command line arguments (excluding the program name)
+    pub fn parse<I: IntoIterator<Item = String>>(args: I) -> Result<Self, String> {
+        let mut options = Options::default();
+        let mut args = args.into_iter();
+
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--scene" => options.scene = value(&arg, args.next())?,
+                "--output" => options.output = PathBuf::from(value(&arg, args.next())?),
+                "--output-color-space" => {
+                    let v = value(&arg, args.next())?;
+                    options.output_color_space = ColorSpace::from_name(&v)
+                        .ok_or_else(|| format!("invalid value '{}' for {}", v, arg))?;
+                },
+                "--seed" => options.seed = Some(parse_value(&arg, args.next())?),
+                "--spp" => options.samples_per_pixel = Some(parse_value(&arg, args.next())?),
+                "--samples-per-pass" => options.samples_per_pass = parse_value(&arg, args.next())?,
+                "--checkpoint" => options.checkpoint = Some(PathBuf::from(value(&arg, args.next())?)),
+                "--checkpoint-interval" => options.checkpoint_interval = parse_value(&arg, args.next())?,
+                "--resume" => options.resume = Some(PathBuf::from(value(&arg, args.next())?)),
+                "--aovs" => options.aovs = parse_aovs(&value(&arg, args.next())?)?,
+                "--aov-output" => options.aov_output = PathBuf::from(value(&arg, args.next())?),
+                "--spectral" => options.spectral = true,
+                "--denoise" => options.denoise = true,
+                "--denoise-iterations" => options.denoise_iterations = parse_value(&arg, args.next())?,
+                "--stats-json" => options.stats_json = Some(PathBuf::from(value(&arg, args.next())?)),
+                "--crop" => options.crop = Some(parse_region(&value(&arg, args.next())?)?),
+                "--crop-output" => options.crop_output = match value(&arg, args.next())?.as_str() {
+                    "crop" => CropOutput::Crop,
+                    "border" => CropOutput::Border,
+                    v => return Err(format!("invalid value '{}' for {}", v, arg)),
+                },
+                "--composite-into" => options.composite_into = Some(PathBuf::from(value(&arg, args.next())?)),
+                "--bloom" => options.bloom = Some(parse_value(&arg, args.next())?),
+                "--glare" => options.glare = Some(parse_value(&arg, args.next())?),
+                "--white-balance" => options.white_balance = Some(parse_value(&arg, args.next())?),
+                "--contrast" => options.contrast = Some(parse_value(&arg, args.next())?),
+                "--saturation" => options.saturation = Some(parse_value(&arg, args.next())?),
+                "--lut" => options.lut = Some(PathBuf::from(value(&arg, args.next())?)),
+                "--frames" => options.frames = Some(parse_frames(&value(&arg, args.next())?)?),
+                "--help" | "-h" => options.help = true,
+                _ => return Err(format!("unknown option '{}'", arg)),
+            }
+        }
+
+        Ok(options)
+    }
+}
+
+fn value(option: &str, value: Option<String>) -> Result<String, String> {
+    value.ok_or_else(|| format!("missing value for {}", option))
+}
+
+fn parse_value<T: FromStr>(option: &str, v: Option<String>) -> Result<T, String> {
+    let v = value(option, v)?;
+    v.parse().map_err(|_| format!("invalid value '{}' for {}", v, option))
+}
+
+fn parse_aovs(list: &str) -> Result<Vec<Aov>, String> {
+    if list == "all" {
+        return Ok(Aov::ALL.to_vec());
+    }
+    list.split(',')
+        .map(|name| Aov::from_name(name.trim()).ok_or_else(|| format!("unknown render pass '{}'", name)))
+        .collect()
+}
+
+/// Parses "first..last", or a single frame
+fn parse_frames(s: &str) -> Result<(u32, u32), String> {
+    let invalid = || format!("invalid frames '{}', expected first..last", s);
+    let (first, last) = s.split_once("..").unwrap_or((s, s));
+    let first: u32 = first.trim().parse().map_err(|_| invalid())?;
+    let last: u32 = last.trim().parse().map_err(|_| invalid())?;
+    if last < first {
+        return Err(invalid());
+    }
+    Ok((first, last))
+}
+
+fn parse_region(s: &str) -> Result<Region, String> {
+    let values: Vec<u32> = s.split(',')
+        .map(|v| v.trim().parse().map_err(|_| format!("invalid crop '{}'", s)))
+        .collect::<Result<_, _>>()?;
+    match values[..] {
+        [x, y, width, height] => Ok(Region::new(x, y, width, height)),
+        _ => Err(format!("invalid crop '{}', expected x,y,width,height", s)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_region() {
+        assert_eq!(parse_region("10, 20,30,40"), Ok(Region::new(10, 20, 30, 40)));
+        assert!(parse_region("10,20,30").is_err());
+        assert!(parse_region("10,20,30,-4").is_err());
+        assert!(parse_region("").is_err());
+    }
+}
@@ -0,0 +1,223 @@
+use crate::PI;
+use crate::color::Color;
+
+/// Soft glow spreading around overexposed pixels, like light scattering
+/// in a lens or in the eye
+#[derive(Debug, Clone, Copy)]
+pub struct Bloom {
+    /// Luminance above which pixels start to glow
+    pub threshold: f64,
+    /// Strength of the glow relative to the light above the threshold
+    pub intensity: f64,
+    /// Spread of the glow as a fraction of the image height
+    pub radius: f64,
+}
+
+impl Default for Bloom {
+    fn default() -> Self {
+        Bloom {
+            threshold: 1.0,
+            intensity: 0.1,
+            radius: 0.02,
+        }
+    }
+}
+
+/// Star shaped streaks from overexposed pixels, like the diffraction
+/// spikes of a bladed aperture
+#[derive(Debug, Clone, Copy)]
+pub struct Glare {
+    /// Luminance above which pixels start to streak
+    pub threshold: f64,
+    /// Strength of the streaks relative to the light above the threshold
+    pub intensity: f64,
+    /// Number of streaks around every bright pixel
+    pub streaks: u32,
+    /// Angle of the first streak in degrees, counter-clockwise from the right
+    pub rotation: f64,
+    /// Distance over which a streak fades to 1% as a fraction of the image height
+    pub length: f64,
+}
+
+impl Default for Glare {
+    fn default() -> Self {
+        Glare {
+            threshold: 1.0,
+            intensity: 0.05,
+            streaks: 6,
+            rotation: 15.0,
+            length: 0.15,
+        }
+    }
+}
+
+impl Bloom {
+    /// Adds the glow to image, stored row by row, sized for a full frame
+    /// frame_height pixels high
+    pub(super) fn apply(&self, image: &mut [Color], width: u32, height: u32, frame_height: u32) {
+        let sigma = self.radius*frame_height as f64;
+        if self.intensity <= 0.0 || sigma <= 0.0 {
+            return;
+        }
+
+        // Separable gaussian blur of the light above the threshold
+        let radius = (3.0*sigma).ceil() as i64;
+        let kernel: Vec<f64> = (-radius..=radius).map(|i| (-((i*i) as f64) / (2.0*sigma*sigma)).exp()).collect();
+        let total: f64 = kernel.iter().sum();
+        let kernel: Vec<f64> = kernel.iter().map(|k| k / total).collect();
+
+        let bright = bright_pass(image, self.threshold);
+        let horizontal = convolve(&bright, width, height, &kernel, (1, 0));
+        let glow = convolve(&horizontal, width, height, &kernel, (0, 1));
+        for (c, g) in image.iter_mut().zip(glow) {
+            *c += self.intensity*g;
+        }
+    }
+}
+
+impl Glare {
+    /// Number of 4 tap passes that make up each streak
+    const MAX_PASSES: u32 = 6;
+
+    /// Adds the streaks to image, stored row by row, sized for a full frame
+    /// frame_height pixels high
+    pub(super) fn apply(&self, image: &mut [Color], width: u32, height: u32, frame_height: u32) {
+        let length = self.length*frame_height as f64;
+        if self.intensity <= 0.0 || self.streaks == 0 || length <= 0.0 {
+            return;
+        }
+
+        // Every pass takes 4 taps, 4 times further apart than the previous
+        // pass, so p passes add up to a streak of 4^p pixels (Kawase 2003)
+        let passes = ((3.0*length + 1.0).log(4.0).ceil() as u32).clamp(1, Self::MAX_PASSES);
+        let attenuation = 0.01_f64.powf(1.0 / length);
+
+        let bright = bright_pass(image, self.threshold);
+        let weight = self.intensity / self.streaks as f64;
+        for i in 0..self.streaks {
+            // Image rows go down, so the angle is measured against -y
+            let angle = self.rotation.to_radians() + 2.0*PI*(i as f64) / (self.streaks as f64);
+            let dir = (angle.cos(), -angle.sin());
+
+            let mut streak = bright.clone();
+            let mut total = 1.0;
+            for pass in 0..passes {
+                let step = 4.0_f64.powi(pass as i32);
+                let taps: Vec<f64> = (0..4).map(|n| attenuation.powf(step*n as f64)).collect();
+                total *= taps.iter().sum::<f64>();
+                streak = (0..height).flat_map(|y| (0..width).map(move |x| (x, y))).map(|(x, y)| {
+                    taps.iter().enumerate().fold(Color::new(0.0, 0.0, 0.0), |sum, (n, tap)| {
+                        let offset = step*n as f64;
+                        sum + *tap*sample(&streak, width, height, x as f64 - offset*dir.0, y as f64 - offset*dir.1)
+                    })
+                }).collect();
+            }
+
+            for (c, s) in image.iter_mut().zip(streak) {
+                *c += (weight / total)*s;
+            }
+        }
+    }
+}
+
+/// Light of each pixel above the luminance threshold, keeping its color
+fn bright_pass(image: &[Color], threshold: f64) -> Vec<Color> {
+    image.iter().map(|c| {
+        let luminance = c.luminance();
+        if luminance <= threshold {
+            Color::new(0.0, 0.0, 0.0)
+        } else {
+            c*((luminance - threshold) / luminance)
+        }
+    }).collect()
+}
+
+/// image convolved with kernel (of odd length) along direction, taking
+/// everything outside the image as black
+fn convolve(image: &[Color], width: u32, height: u32, kernel: &[f64], (dx, dy): (i64, i64)) -> Vec<Color> {
+    let radius = (kernel.len() / 2) as i64;
+    (0..height).flat_map(|y| (0..width).map(move |x| (x, y))).map(|(x, y)| {
+        let mut sum = Color::new(0.0, 0.0, 0.0);
+        for (k, weight) in kernel.iter().enumerate() {
+            let offset = k as i64 - radius;
+            let (qx, qy) = (x as i64 + offset*dx, y as i64 + offset*dy);
+            if qx >= 0 && qy >= 0 && qx < width as i64 && qy < height as i64 {
+                sum += *weight*image[(qy as usize)*(width as usize) + qx as usize];
+            }
+        }
+        sum
+    }).collect()
+}
+
+/// Bilinearly interpolated pixel at (x, y), black outside the image
+fn sample(image: &[Color], width: u32, height: u32, x: f64, y: f64) -> Color {
+    let (x0, y0) = (x.floor(), y.floor());
+    let (fx, fy) = (x - x0, y - y0);
+    let pixel = |x: i64, y: i64| {
+        if x < 0 || y < 0 || x >= width as i64 || y >= height as i64 {
+            Color::new(0.0, 0.0, 0.0)
+        } else {
+            image[(y as usize)*(width as usize) + x as usize]
+        }
+    };
+    let (x0, y0) = (x0 as i64, y0 as i64);
+    (1.0 - fy)*((1.0 - fx)*pixel(x0, y0) + fx*pixel(x0 + 1, y0))
+        + fy*((1.0 - fx)*pixel(x0, y0 + 1) + fx*pixel(x0 + 1, y0 + 1))
+}
+
+#[cfg(test)]
+mod tests {
+    use approx::assert_relative_eq;
+    use super::*;
+
+    /// Black width x height image with c at (x, y)
+    fn image_with(width: u32, height: u32, (x, y): (u32, u32), c: Color) -> Vec<Color> {
+        let mut image = vec![Color::BLACK; (width*height) as usize];
+        image[(y*width + x) as usize] = c;
+        image
+    }
+
+    #[test]
+    fn test_bloom_energy() {
+        // Away from the edges the glow adds up to the light above the threshold, scaled by the intensity
+        let bloom = Bloom {threshold: 1.0, intensity: 0.1, radius: 0.05};
+        let c = Color::gray(2.0);
+        let mut image = image_with(41, 41, (20, 20), c);
+        bloom.apply(&mut image, 41, 41, 41);
+
+        let total = image.iter().fold(Color::BLACK, |sum, p| sum + *p) - c;
+        let expected = bloom.intensity*(c.luminance() - bloom.threshold) / c.luminance()*c;
+        (0..3).for_each(|i| assert_relative_eq!(total[i], expected[i], epsilon = 1e-12));
+    }
+
+    #[test]
+    fn test_below_threshold_unchanged() {
+        let image: Vec<Color> = (0..100).map(|_| 0.9*Color::random()).collect();
+        let mut glowing = image.clone();
+        Bloom::default().apply(&mut glowing, 10, 10, 10);
+        Glare::default().apply(&mut glowing, 10, 10, 10);
+        for (a, b) in image.iter().zip(&glowing) {
+            (0..3).for_each(|i| assert_eq!(a[i], b[i]));
+        }
+    }
+
+    #[test]
+    fn test_horizontal_glare() {
+        // Two streaks at 0 and 180 degrees light up the pixel's row and nothing else
+        let glare = Glare {threshold: 1.0, intensity: 0.05, streaks: 2, rotation: 0.0, length: 0.5};
+        let (width, height) = (41, 11);
+        let mut image = image_with(width, height, (20, 5), Color::gray(2.0));
+        glare.apply(&mut image, width, height, height);
+
+        for y in 0..height {
+            for x in 0..width {
+                let p = image[(y*width + x) as usize];
+                if y != 5 {
+                    assert!(p.max_component() < 1e-9);
+                } else if x != 20 && (x as i64 - 20).abs() <= 4 {
+                    assert!(p.g() > 0.0);
+                }
+            }
+        }
+    }
+}
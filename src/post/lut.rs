@@ -0,0 +1,115 @@
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use crate::{invalid_data, parse_number};
+use crate::color::Color;
+
+/// 3D color lookup table, as made by grading tools to give images a look
+#[derive(Debug, Clone)]
+pub struct Lut3d {
+    size: usize,
+    table: Vec<Color>,  // size^3 entries, red changing fastest, then green, then blue
+    domain_min: Color,
+    domain_max: Color,
+}
+
+impl Lut3d {
+    /// Loads a LUT from an Adobe/Resolve .cube file
+    pub fn open<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        Self::parse(&fs::read_to_string(path)?)
+    }
+
+    /// Parses the contents of a .cube file. Only 3D LUTs are supported.
+    pub fn parse(text: &str) -> io::Result<Self> {
+        let mut size = None;
+        let mut domain_min = Color::new(0.0, 0.0, 0.0);
+        let mut domain_max = Color::new(1.0, 1.0, 1.0);
+        let mut table = vec![];
+
+        for line in text.lines().map(|line| line.trim()).filter(|line| !line.is_empty() && !line.starts_with('#')) {
+            let mut words = line.split_whitespace();
+            let keyword = words.next().unwrap_or_default();
+            match keyword {
+                "TITLE" => (),
+                "LUT_1D_SIZE" => return Err(invalid_data("1D LUTs aren't supported")),
+                "LUT_3D_SIZE" => {
+                    let n: usize = parse_number(words.next().unwrap_or_default())?;
+                    if n < 2 {
+                        return Err(invalid_data(&format!("invalid LUT size {}", n)));
+                    }
+                    size = Some(n);
+                },
+                "DOMAIN_MIN" => domain_min = parse_color(line, words)?,
+                "DOMAIN_MAX" => domain_max = parse_color(line, words)?,
+                _ => table.push(parse_color(line, line.split_whitespace())?),
+            }
+        }
+
+        let size = size.ok_or_else(|| invalid_data("missing LUT_3D_SIZE"))?;
+        if table.len() != size*size*size {
+            return Err(invalid_data(&format!("expected {} entries for a LUT of size {}, got {}", size*size*size, size, table.len())));
+        }
+        Ok(Lut3d {size, table, domain_min, domain_max})
+    }
+
+    /// Number of entries along each axis
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    /// Looks c up, interpolating trilinearly between entries. Colors outside
+    /// the domain are clamped to it.
+    pub fn lookup(&self, c: &Color) -> Color {
+        let n = self.size - 1;
+        let mut index = [0; 3];
+        let mut frac = [0.0; 3];
+        for i in 0..3 {
+            let x = (c[i] - self.domain_min[i]) / (self.domain_max[i] - self.domain_min[i]);
+            let x = x.clamp(0.0, 1.0)*(n as f64);
+            index[i] = (x.floor() as usize).min(n - 1);
+            frac[i] = x - index[i] as f64;
+        }
+
+        let entry = |r: usize, g: usize, b: usize| self.table[((index[2] + b)*self.size + index[1] + g)*self.size + index[0] + r];
+        let lerp = |a: Color, b: Color, t: f64| (1.0 - t)*a + t*b;
+        let along_r = |g: usize, b: usize| lerp(entry(0, g, b), entry(1, g, b), frac[0]);
+        let along_g = |b: usize| lerp(along_r(0, b), along_r(1, b), frac[1]);
+        lerp(along_g(0), along_g(1), frac[2])
+    }
+}
+
+fn parse_color<'a, I: Iterator<Item = &'a str>>(line: &str, words: I) -> io::Result<Color> {
+    let values = words.map(parse_number).collect::<io::Result<Vec<f64>>>()?;
+    match values[..] {
+        [r, g, b] => Ok(Color::new(r, g, b)),
+        _ => Err(invalid_data(&format!("expected three numbers, got '{}'", line))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use approx::assert_relative_eq;
+    use super::*;
+
+    #[test]
+    fn test_lut_3d() {
+        // Identity LUT of size 2 with the red and blue channels swapped
+        let text = "TITLE \"swap\"\n# comment\nLUT_3D_SIZE 2\n\
+            0 0 0\n0 0 1\n0 1 0\n0 1 1\n1 0 0\n1 0 1\n1 1 0\n1 1 1\n";
+        let lut = Lut3d::parse(text).unwrap();
+        assert_eq!(lut.size(), 2);
+        let c = lut.lookup(&Color::new(0.2, 0.5, 0.7));
+        assert_relative_eq!(c.r(), 0.7, epsilon = 1e-12);
+        assert_relative_eq!(c.g(), 0.5, epsilon = 1e-12);
+        assert_relative_eq!(c.b(), 0.2, epsilon = 1e-12);
+
+        // Outside the domain is clamped
+        let c = lut.lookup(&Color::new(2.0, -1.0, 0.5));
+        assert_relative_eq!(c.b(), 1.0, epsilon = 1e-12);
+        assert_relative_eq!(c.g(), 0.0, epsilon = 1e-12);
+
+        assert!(Lut3d::parse("LUT_3D_SIZE 2\n0 0 0\n").is_err());
+        assert!(Lut3d::parse("LUT_1D_SIZE 2\n0 0 0\n1 1 1\n").is_err());
+    }
+}
@@ -0,0 +1,118 @@
+pub mod glow;
+pub mod lut;
+
+pub use glow::{Bloom, Glare};
+pub use lut::Lut3d;
+
+use crate::color::{self, Color, Transfer};
+use crate::film::Film;
+
+/// Middle grey, the pivot of the contrast curve
+const MIDDLE_GREY: f64 = 0.18;
+
+/// Effects applied to the rendered image before it is stored, in the
+/// order of the fields. All of them are off by default.
+#[derive(Debug, Clone)]
+pub struct PostSettings {
+    pub bloom: Option<Bloom>,
+    pub glare: Option<Glare>,
+    /// Color temperature in K of the light that should look white
+    pub white_balance: Option<f64>,
+    /// Power of the contrast curve around middle grey, above 1 for more contrast
+    pub contrast: f64,
+    /// 0 for black and white, above 1 for more saturated colors
+    pub saturation: f64,
+    /// Look applied last, to sRGB encoded colors like most LUTs expect
+    pub lut: Option<Lut3d>,
+}
+
+impl Default for PostSettings {
+    fn default() -> Self {
+        PostSettings {
+            bloom: None,
+            glare: None,
+            white_balance: None,
+            contrast: 1.0,
+            saturation: 1.0,
+            lut: None,
+        }
+    }
+}
+
+impl PostSettings {
+    /// Whether the settings leave the image as it is
+    pub fn is_identity(&self) -> bool {
+        self.bloom.is_none() && self.glare.is_none() && self.white_balance.is_none()
+            && self.contrast == 1.0 && self.saturation == 1.0 && self.lut.is_none()
+    }
+}
+
+/// Applies the effects in settings to the mean radiance of film
+///
+/// Glows are sized relative to frame_height, the height of the full image
+/// film is a region of, so a cropped render glows like the full frame.
+///
+/// The returned film keeps the sample counts of the input, so it can be
+/// quantized like any other.
+pub fn post_process(film: &Film, settings: &PostSettings, frame_height: u32) -> Film {
+    let (width, height) = (film.width(), film.height());
+    let mut image: Vec<Color> = (0..height).flat_map(|y| (0..width).map(move |x| (x, y)))
+        .map(|(x, y)| film.pixel(x, y))
+        .collect();
+
+    if let Some(bloom) = &settings.bloom {
+        bloom.apply(&mut image, width, height, frame_height);
+    }
+    if let Some(glare) = &settings.glare {
+        glare.apply(&mut image, width, height, frame_height);
+    }
+
+    let white_balance = settings.white_balance.map(color::white_balance);
+    for c in image.iter_mut() {
+        if let Some(white_balance) = &white_balance {
+            *c = white_balance(c);
+        }
+        *c = grade(c, settings.contrast, settings.saturation);
+        if let Some(lut) = &settings.lut {
+            let encoded = c.map(|x| Transfer::Srgb.encode(x));
+            *c = lut.lookup(&encoded).map(|v| Transfer::Srgb.decode(v));
+        }
+    }
+
+    // Scale back up to sums of samples
+    let sum = image.iter().zip(film.samples()).map(|(c, n)| c*(*n as f64)).collect();
+    Film::from_raw(film.region(), sum, film.samples().to_vec()).unwrap()
+}
+
+/// Contrast curve pivoting around middle grey in log space, then saturation
+/// around the luminance
+fn grade(c: &Color, contrast: f64, saturation: f64) -> Color {
+    let c = if contrast == 1.0 {*c} else {c.map(|x| MIDDLE_GREY*(x.max(0.0) / MIDDLE_GREY).powf(contrast))};
+    if saturation == 1.0 {
+        return c;
+    }
+    let luminance = c.luminance();
+    (luminance + saturation*(c - luminance)).map(|x| x.max(0.0))
+}
+
+#[cfg(test)]
+mod tests {
+    use approx::assert_relative_eq;
+    use super::*;
+
+    #[test]
+    fn test_grade() {
+        // Middle grey stays put whatever the contrast, and greys keep their saturation
+        let grey = grade(&Color::gray(MIDDLE_GREY), 1.5, 1.3);
+        (0..3).for_each(|i| assert_relative_eq!(grey[i], MIDDLE_GREY, epsilon = 1e-12));
+
+        // More contrast darkens below middle grey and brightens above it
+        assert!(grade(&Color::gray(0.05), 1.5, 1.0).r() < 0.05);
+        assert!(grade(&Color::gray(0.5), 1.5, 1.0).r() > 0.5);
+
+        // No saturation leaves the luminance as grey
+        let c = Color::new(0.8, 0.2, 0.1);
+        let bw = grade(&c, 1.0, 0.0);
+        (0..3).for_each(|i| assert_relative_eq!(bw[i], c.luminance(), epsilon = 1e-12));
+    }
+}
@@ -1,14 +1,16 @@
 use std::rc::Rc;
 
-use crate::INFINITY;
 use crate::color::Color;
 use crate::vec3::{Point3, Vec3};
 use crate::hittable::Hittable;
+use crate::integrator::trace;
 
 #[derive(Debug, Clone, Copy)]
 pub struct Ray {
     origin: Point3,
-    dir: Vec3
+    dir: Vec3,
+    wavelength: Option<f64>,  // In nm, the single wavelength carried in spectral mode
+    time: f64,  // In seconds, when the ray was cast in an animation
 }
 
 impl Ray {
@@ -16,9 +18,23 @@ impl Ray {
         Ray {
             origin,
             dir,
+            wavelength: None,
+            time: 0.0,
         }
     }
 
+    /// Copy of the ray carrying the given wavelength
+    pub fn with_wavelength(mut self, wavelength: Option<f64>) -> Self {
+        self.wavelength = wavelength;
+        self
+    }
+
+    /// Copy of the ray cast at the given time
+    pub fn with_time(mut self, time: f64) -> Self {
+        self.time = time;
+        self
+    }
+
     pub fn origin(&self) -> &Point3 {
         &self.origin
     }
@@ -27,30 +43,21 @@ impl Ray {
         &self.dir
     }
 
+    pub fn wavelength(&self) -> Option<f64> {
+        self.wavelength
+    }
+
+    pub fn time(&self) -> f64 {
+        self.time
+    }
+
     pub fn at(&self, t: f64) -> Point3 {
         self.origin + t * self.dir
     }
 
+    /// Light arriving along the ray. In spectral mode (when the ray has
+    /// a wavelength) every channel holds the radiance at that wavelength.
     pub fn color(&self, world: &Rc<dyn Hittable>, recursion_depth: u32) -> Color {
-        if recursion_depth <= 0 {
-            return Color::new(0.0, 0.0, 0.0);
-        }
-
-        // t_min is 0.001 to avoid floating point error causing a hit to be recorded at the ray origin
-        // fixes shadow acne
-        if let Some(hit_record) = world.hit(self, 0.001, INFINITY) {
-            match hit_record.material().scatter(self, &hit_record) {
-                Some((attenuation, scattered)) => {
-                    return attenuation*scattered.color(world, recursion_depth - 1);
-                },
-                None => {
-                    return Color::new(0.0, 0.0, 0.0);
-                },
-            }
-        }
-
-        let unit_direction = self.dir.unit_vector();
-        let t = 0.5*(unit_direction.y() + 1.0); // Normalize y to [0, 1]
-        (1.0 - t)*Color::new(1.0, 1.0, 1.0) + t*Color::new(0.5, 0.7, 1.0)
+        trace(self, world, recursion_depth).radiance()
     }
 }
@@ -0,0 +1,118 @@
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+use crate::{random_f64, rng, spectrum};
+use crate::aov::AovBuffers;
+use crate::checkpoint::Checkpoint;
+use crate::film::Film;
+use crate::integrator::{trace, PathSample};
+use crate::scene::Scene;
+
+/// Where and how often a render saves checkpoints
+pub struct CheckpointSettings {
+    pub path: PathBuf,
+    pub interval: Duration,
+    pub scene_hash: u64,
+    pub seed: u64,  // Seed the scene was built with
+}
+
+pub struct RenderSettings {
+    /// Number of samples every pixel should end up with
+    pub samples_per_pixel: u32,
+    /// Number of samples added to every pixel before checking whether to checkpoint
+    pub samples_per_pass: u32,
+    pub checkpoint: Option<CheckpointSettings>,
+}
+
+/// What render reports as it goes
+pub enum Progress<'a> {
+    /// Every pixel has at least samples of the target samples
+    Pass {samples: u32, target: u32},
+    /// A checkpoint was saved to the path
+    Checkpoint(&'a Path),
+}
+
+/// Traces one camera sample through pixel (x, y) of the image
+pub fn render_sample(scene: &Scene, x: u32, y: u32) -> PathSample {
+    // Our coordinate system is right-handed and defines +y as up
+    // While the Rust image crate is right-handed and defines +y as down
+    // So we need to flip the y coordinates.
+    let y = scene.image_height - y - 1;
+
+    let u = ((x as f64) + random_f64()) / (scene.image_width as f64);  // Percentage of width for current pixel
+    let v = ((y as f64) + random_f64()) / (scene.image_height as f64); // Precentage of height for current pixel
+    let wavelength = if scene.spectral {Some(spectrum::sample_wavelength(random_f64()))} else {None};
+    let (r, weight) = match scene.cam.sample_ray(u, v, wavelength.map(|(lambda, _)| lambda)) {
+        Some(sample) => sample,
+        None => return PathSample::default(),
+    };
+    // Still images don't draw a time, so their random numbers are unchanged
+    let shutter_time = scene.shutter_time();
+    let time = if shutter_time > 0.0 {scene.time + random_f64()*shutter_time} else {scene.time};
+    let r = r.with_time(time);
+    let sample = match wavelength {
+        Some((lambda, pdf)) => trace(&r.with_wavelength(Some(lambda)), &scene.world, scene.recursion_depth).spectral_to_rgb(lambda, pdf),
+        None => trace(&r, &scene.world, scene.recursion_depth),
+    };
+    sample.filtered(&weight).scaled(scene.cam.exposure_scale())
+}
+
+/// Progressively renders scene into film until every pixel has
+/// settings.samples_per_pixel samples
+///
+/// Only the region of the image covered by film is rendered.
+/// The film may already hold samples (eg. when resuming from a checkpoint),
+/// in which case only the missing samples are rendered. If given, aovs
+/// gathers render passes from the samples rendered by this call.
+/// progress is called after every pass and checkpoint.
+pub fn render<F: FnMut(Progress)>(
+    scene: &Scene,
+    film: &mut Film,
+    mut aovs: Option<&mut AovBuffers>,
+    settings: &RenderSettings,
+    mut progress: F,
+) -> io::Result<()> {
+    let target = settings.samples_per_pixel;
+    let samples_per_pass = settings.samples_per_pass.max(1);
+    let mut last_checkpoint = Instant::now();
+
+    let region = film.region();
+
+    while film.min_samples() < target {
+        let pass_target = (film.min_samples() + samples_per_pass).min(target);
+        for y in 0..film.height() {
+            for x in 0..film.width() {
+                for _ in film.samples_at(x, y)..pass_target {
+                    let sample = render_sample(scene, region.x + x, region.y + y);
+                    film.add_sample(x, y, &sample.radiance());
+                    if let Some(aovs) = aovs.as_deref_mut() {
+                        aovs.add_sample(x, y, &sample);
+                    }
+                }
+            }
+        }
+        progress(Progress::Pass {samples: pass_target, target});
+
+        if let Some(checkpoint) = &settings.checkpoint {
+            let finished = pass_target >= target;
+            if finished || last_checkpoint.elapsed() >= checkpoint.interval {
+                save_checkpoint(checkpoint, film)?;
+                progress(Progress::Checkpoint(&checkpoint.path));
+                last_checkpoint = Instant::now();
+            }
+        }
+    }
+
+    Ok(())
+}
+
+fn save_checkpoint(settings: &CheckpointSettings, film: &Film) -> io::Result<()> {
+    let checkpoint = Checkpoint {
+        scene_hash: settings.scene_hash,
+        seed: settings.seed,
+        rng_state: rng::state(),
+        film: film.clone(),
+    };
+    checkpoint.save(&settings.path)
+}
@@ -0,0 +1,63 @@
+use std::cell::Cell;
+
+/// SplitMix64 generator
+///
+/// The whole state is a single u64, which lets a render save and restore
+/// its position in the random sequence (see checkpoint.rs).
+#[derive(Debug, Clone, Copy)]
+pub struct Rng {
+    state: u64,
+}
+
+impl Rng {
+    pub fn new(seed: u64) -> Self {
+        Rng {state: seed}
+    }
+
+    pub fn state(&self) -> u64 {
+        self.state
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9e3779b97f4a7c15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d049bb133111eb);
+        z ^ (z >> 31)
+    }
+
+    /// Returns a random f64 in [0, 1)
+    pub fn next_f64(&mut self) -> f64 {
+        // Keep the top 53 bits, which is all the precision an f64 mantissa has
+        (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64
+    }
+}
+
+thread_local! {
+    static THREAD_RNG: Cell<Rng> = Cell::new(Rng::new(0x853c49e6748fea9b));
+}
+
+/// Reseeds the random number generator of the current thread
+pub fn seed(seed: u64) {
+    set_state(seed);
+}
+
+/// Returns the state of the random number generator of the current thread
+pub fn state() -> u64 {
+    THREAD_RNG.with(|rng| rng.get().state())
+}
+
+/// Restores a state previously returned by state()
+pub fn set_state(state: u64) {
+    THREAD_RNG.with(|rng| rng.set(Rng::new(state)));
+}
+
+/// Returns a random f64 in [0, 1) from the generator of the current thread
+pub fn next_f64() -> f64 {
+    THREAD_RNG.with(|cell| {
+        let mut rng = cell.get();
+        let x = rng.next_f64();
+        cell.set(rng);
+        x
+    })
+}
@@ -0,0 +1,65 @@
+use std::rc::Rc;
+
+use crate::color::Color;
+use crate::hittable::{AlphaMask, AlphaMode, Hittable, HittableList, Sphere};
+use crate::material::{Conductor, Lambertian, Material};
+use crate::texture::{Checker, IntoTexture, Texture};
+use crate::vec3::Point3;
+use crate::scene::Scene;
+
+pub fn get_scene() -> Scene {
+    // Image config
+    let aspect_ratio: f64 = 16.0/9.0;
+    let image_width: u32 = 600;
+
+    // Camera config
+    let lookfrom: Point3 = Point3::new(0.0, 1.0, 4.0);
+    let lookat: Point3 = Point3::new(0.0, 0.0, -1.0);
+    let vfov: f64 = 30.0;
+    let aperture_width: f64 = 0.0;
+    let focus_distance: f64 = (lookfrom - lookat).length();
+
+    // Raytracer config
+    let samples_per_pixel: u32 = 200;
+    let recursion_depth: u32 = 50;
+
+    // Textures
+    let lattice:        Rc<dyn Texture> = Rc::new(Checker::new(&0.0.into_texture(), &1.0.into_texture(), 0.15));
+    let half_opaque:    Rc<dyn Texture> = 0.5.into_texture();
+
+    // Materials
+    let material_ground:    Rc<dyn Material> = Rc::new(Lambertian::new(Color::new(0.5, 0.5, 0.5)));
+    let material_cage:      Rc<dyn Material> = Rc::new(Lambertian::new(Color::new(0.8, 0.6, 0.2)));
+    let material_gold:      Rc<dyn Material> = Rc::new(Conductor::gold(0.2));
+    let material_ghost:     Rc<dyn Material> = Rc::new(Lambertian::new(Color::new(0.2, 0.4, 0.8)));
+
+    // World creation
+    let mut world = HittableList::new();
+    let ground_sphere:  Rc<dyn Hittable> = Rc::new(Sphere::new(Point3::new( 0.0, -100.5, -1.0), 100.0, &material_ground));
+    let cage_sphere:    Rc<dyn Hittable> = Rc::new(Sphere::new(Point3::new(-0.6,    0.0, -1.0),   0.5, &material_cage));
+    let cage:           Rc<dyn Hittable> = Rc::new(AlphaMask::new(&cage_sphere, &lattice, AlphaMode::Threshold(0.5)));
+    let caged:          Rc<dyn Hittable> = Rc::new(Sphere::new(Point3::new(-0.6,    0.0, -1.0),   0.25, &material_gold));
+    let ghost_sphere:   Rc<dyn Hittable> = Rc::new(Sphere::new(Point3::new( 0.6,    0.0, -1.0),   0.5, &material_ghost));
+    let ghost:          Rc<dyn Hittable> = Rc::new(AlphaMask::new(&ghost_sphere, &half_opaque, AlphaMode::Stochastic));
+
+    world.add(&ground_sphere);
+    world.add(&cage);
+    world.add(&caged);
+    world.add(&ghost);
+
+    // world is frozen as an Rc<dyn Hittable> until the render loop is over
+    let world: Rc<dyn Hittable> = Rc::new(world);
+
+    Scene::new(
+        aspect_ratio,
+        image_width,
+        samples_per_pixel,
+        recursion_depth,
+        lookfrom,
+        lookat,
+        vfov,
+        aperture_width,
+        focus_distance,
+        world,
+    )
+}
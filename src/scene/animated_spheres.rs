@@ -0,0 +1,98 @@
+use std::rc::Rc;
+
+use crate::animation::{CameraKeyframes, Interpolation, Keyframes, Timeline};
+use crate::color::Color;
+use crate::hittable::{Animated, Hittable, HittableList, Sphere};
+use crate::material::{Material, Lambertian, Dielectric, Metal};
+use crate::vec3::{Point3, Vec3};
+use crate::scene::Scene;
+
+pub fn get_scene() -> Scene {
+    // Image config
+    let aspect_ratio: f64 = 16.0/9.0;
+    let image_width: u32 = 400;
+
+    // Animation config, two seconds at 24 frames per second with a 180 degree shutter
+    let fps: f64 = 24.0;
+    let first_frame: u32 = 0;
+    let last_frame: u32 = 47;
+    let shutter: f64 = 0.5;
+
+    // Camera config, swinging around the spheres
+    let camera = CameraKeyframes {
+        lookfrom: Keyframes::new(Interpolation::Spline)
+            .key(0.0, Point3::new(-3.0, 1.5, 3.0))
+            .key(1.0, Point3::new( 0.0, 1.0, 4.0))
+            .key(2.0, Point3::new( 3.0, 1.5, 3.0)),
+        lookat: Keyframes::constant(Point3::new(0.0, 0.0, -1.0)),
+        vfov: Keyframes::new(Interpolation::Linear).key(0.0, 35.0).key(2.0, 28.0),
+        focus_distance: Keyframes::constant(4.5),
+        roll: Keyframes::constant(0.0),
+        aperture_width: 0.0,
+    };
+
+    // Raytracer config
+    let samples_per_pixel: u32 = 100;
+    let recursion_depth: u32 = 50;
+
+    // Materials
+    let material_ground: Rc<dyn Material> = Rc::new(Lambertian::new(Color::new(0.5, 0.5, 0.5)));
+    let material_center: Rc<dyn Material> = Rc::new(Dielectric::new(1.5));
+    let material_moon:   Rc<dyn Material> = Rc::new(Lambertian::new(Color::new(0.8, 0.3, 0.1)));
+    let material_ball:   Rc<dyn Material> = Rc::new(Metal::new(Color::new(0.8, 0.8, 0.9), 0.05));
+
+    // World creation
+    let mut world = HittableList::new();
+    let ground_sphere:  Rc<dyn Hittable> = Rc::new(Sphere::new(Point3::new( 0.0, -100.5, -1.0), 100.0, &material_ground));
+    let center:         Rc<dyn Hittable> = Rc::new(Sphere::new(Point3::new( 0.0,    0.0, -1.0),   0.5, &material_center));
+    world.add(&ground_sphere);
+    world.add(&center);
+
+    // Two moons circling the centre sphere once, built around the origin and moved into place
+    let mut moons = HittableList::new();
+    let moon1:          Rc<dyn Hittable> = Rc::new(Sphere::new(Point3::new( 1.0,  0.0, 0.0), 0.2, &material_moon));
+    let moon2:          Rc<dyn Hittable> = Rc::new(Sphere::new(Point3::new(-1.0,  0.0, 0.0), 0.2, &material_moon));
+    moons.add(&moon1);
+    moons.add(&moon2);
+    let moons: Rc<dyn Hittable> = Rc::new(moons);
+    let orbit: Rc<dyn Hittable> = Rc::new(Animated::new(&moons)
+        .translation(Keyframes::constant(Vec3::new(0.0, -0.3, -1.0)))
+        .rotation(Keyframes::new(Interpolation::Linear)
+            .key(0.0, Vec3::new(0.0, 0.0, 0.0))
+            .key(2.0, Vec3::new(0.0, 360.0, 0.0))));
+    world.add(&orbit);
+
+    // A ball bouncing twice across the front, squashing a little as it lands
+    let ball: Rc<dyn Hittable> = Rc::new(Sphere::new(Point3::new(0.0, 0.0, 0.0), 0.25, &material_ball));
+    let bouncing: Rc<dyn Hittable> = Rc::new(Animated::new(&ball)
+        .translation(Keyframes::new(Interpolation::Spline)
+            .key(0.0, Vec3::new(-1.6, 0.9, 0.2))
+            .key(0.5, Vec3::new(-0.8, -0.25, 0.3))
+            .key(1.0, Vec3::new( 0.0, 0.7, 0.4))
+            .key(1.5, Vec3::new( 0.8, -0.25, 0.3))
+            .key(2.0, Vec3::new( 1.6, 0.9, 0.2)))
+        .scale(Keyframes::new(Interpolation::Linear)
+            .key(0.4, 1.0).key(0.5, 0.9).key(0.6, 1.0)
+            .key(1.4, 1.0).key(1.5, 0.9).key(1.6, 1.0)));
+    world.add(&bouncing);
+
+    // world is frozen as an Rc<dyn Hittable> until the render loop is over
+    let world: Rc<dyn Hittable> = Rc::new(world);
+
+    let mut scene = Scene::new(
+        aspect_ratio,
+        image_width,
+        samples_per_pixel,
+        recursion_depth,
+        camera.lookfrom.at(0.0),
+        camera.lookat.at(0.0),
+        camera.vfov.at(0.0),
+        camera.aperture_width,
+        camera.focus_distance.at(0.0),
+        world,
+    );
+    let timeline = Timeline::new(fps, first_frame, last_frame, move |time| camera.camera(time, aspect_ratio)).shutter(shutter);
+    scene.timeline = Some(timeline);
+    scene.set_frame(first_frame);
+    scene
+}
@@ -0,0 +1,65 @@
+use std::rc::Rc;
+
+use crate::{random_f64, random_f64_in};
+use crate::camera::Aperture;
+use crate::color::Color;
+use crate::hittable::{Hittable, HittableList, Sphere};
+use crate::material::{Lambertian, Material, Metal, Principled};
+use crate::vec3::Point3;
+use crate::scene::Scene;
+
+pub fn get_scene() -> Scene {
+    // Image config
+    let aspect_ratio: f64 = 16.0/9.0;
+    let image_width: u32 = 600;
+
+    // Camera config
+    let lookfrom: Point3 = Point3::new(0.0, 0.3, 3.0);
+    let lookat: Point3 = Point3::new(0.0, -0.1, -1.0);
+    let vfov: f64 = 30.0;
+    let aperture_width: f64 = 0.3;
+    let focus_distance: f64 = (lookfrom - lookat).length();
+
+    // Raytracer config
+    let samples_per_pixel: u32 = 200;
+    let recursion_depth: u32 = 50;
+
+    // Materials
+    let material_ground:    Rc<dyn Material> = Rc::new(Lambertian::new(Color::new(0.2, 0.2, 0.25)));
+    let material_subject:   Rc<dyn Material> = Rc::new(Metal::new(Color::new(0.9, 0.75, 0.5), 0.1));
+
+    // World creation
+    let mut world = HittableList::new();
+    let ground_sphere:  Rc<dyn Hittable> = Rc::new(Sphere::new(Point3::new(0.0, -100.5, -1.0), 100.0, &material_ground));
+    let subject:        Rc<dyn Hittable> = Rc::new(Sphere::new(Point3::new(0.0,    0.0, -1.0),   0.5, &material_subject));
+    world.add(&ground_sphere);
+    world.add(&subject);
+
+    // Small lights far behind the subject, well out of focus
+    for _ in 0..60 {
+        let color = Color::new(random_f64_in(0.5, 1.0), random_f64_in(0.3, 0.8), random_f64_in(0.1, 0.6));
+        let material: Rc<dyn Material> = Rc::new(Principled::new(Color::new(0.0, 0.0, 0.0)).emission(40.0*color));
+        let center = Point3::new(random_f64_in(-7.0, 7.0), random_f64_in(-0.45, 3.0), -14.0 - 6.0*random_f64());
+        let light: Rc<dyn Hittable> = Rc::new(Sphere::new(center, 0.04, &material));
+        world.add(&light);
+    }
+
+    // world is frozen as an Rc<dyn Hittable> until the render loop is over
+    let world: Rc<dyn Hittable> = Rc::new(world);
+
+    // Six blades give hexagonal highlights, squashed into cat's eyes towards the corners
+    let mut scene = Scene::new(
+        aspect_ratio,
+        image_width,
+        samples_per_pixel,
+        recursion_depth,
+        lookfrom,
+        lookat,
+        vfov,
+        aperture_width,
+        focus_distance,
+        world,
+    );
+    scene.cam = scene.cam.aperture(Aperture::polygon(6, 15.0)).cat_eye(0.6);
+    scene
+}
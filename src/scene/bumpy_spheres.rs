@@ -0,0 +1,96 @@
+use std::rc::Rc;
+
+use image::{Rgb, RgbImage};
+
+use crate::PI;
+use crate::color::Color;
+use crate::hittable::{Hittable, HittableList, Sphere};
+use crate::material::{BumpMap, Conductor, Lambertian, Material, NormalMap, Principled};
+use crate::texture::{ImageTexture, Texture};
+use crate::vec3::{Point3, Vec3};
+use crate::scene::Scene;
+
+/// Egg crate pattern of bumps over (u, v), in [0, 1]
+fn egg_crate(u: f64, v: f64) -> f64 {
+    0.5 + 0.5*(2.0*PI*16.0*u).sin()*(2.0*PI*8.0*v).sin()
+}
+
+/// Height map of egg_crate as an image
+fn height_map(width: u32, height: u32) -> RgbImage {
+    RgbImage::from_fn(width, height, |x, y| {
+        let h = egg_crate((x as f64 + 0.5) / width as f64, 1.0 - (y as f64 + 0.5) / height as f64);
+        let h = (255.0*h).round() as u8;
+        Rgb([h, h, h])
+    })
+}
+
+/// Tangent-space normal map of egg_crate as an image
+fn normal_map(width: u32, height: u32) -> RgbImage {
+    RgbImage::from_fn(width, height, |x, y| {
+        let u = (x as f64 + 0.5) / width as f64;
+        let v = 1.0 - (y as f64 + 0.5) / height as f64;
+        let dhdu = PI*16.0*(2.0*PI*16.0*u).cos()*(2.0*PI*8.0*v).sin();
+        let dhdv = PI*8.0*(2.0*PI*16.0*u).sin()*(2.0*PI*8.0*v).cos();
+        let n = Vec3::new(-0.01*dhdu, -0.02*dhdv, 1.0).unit_vector();
+        let encode = |c: f64| (255.0*(0.5*c + 0.5)).round() as u8;
+        Rgb([encode(n.x()), encode(n.y()), encode(n.z())])
+    })
+}
+
+pub fn get_scene() -> Scene {
+    // Image config
+    let aspect_ratio: f64 = 16.0/9.0;
+    let image_width: u32 = 600;
+
+    // Camera config
+    let lookfrom: Point3 = Point3::new(0.0, 1.0, 4.0);
+    let lookat: Point3 = Point3::new(0.0, 0.0, -1.0);
+    let vfov: f64 = 30.0;
+    let aperture_width: f64 = 0.0;
+    let focus_distance: f64 = (lookfrom - lookat).length();
+
+    // Raytracer config
+    let samples_per_pixel: u32 = 200;
+    let recursion_depth: u32 = 50;
+
+    // Textures
+    let heights:        Rc<dyn Texture> = Rc::new(ImageTexture::from_data(height_map(1024, 512)));
+    let normals:        Rc<dyn Texture> = Rc::new(ImageTexture::from_data(normal_map(1024, 512)));
+
+    // Materials
+    let material_ground:    Rc<dyn Material> = Rc::new(Lambertian::new(Color::new(0.5, 0.5, 0.5)));
+    let material_clay:      Rc<dyn Material> = Rc::new(Lambertian::new(Color::new(0.7, 0.4, 0.3)));
+    let material_bumpy:     Rc<dyn Material> = Rc::new(BumpMap::new(&material_clay, &heights, 0.03));
+    let material_copper:    Rc<dyn Material> = Rc::new(Conductor::copper(0.2));
+    let material_hammered:  Rc<dyn Material> = Rc::new(NormalMap::new(&material_copper, &normals, 1.0));
+    let material_lacquer:   Rc<dyn Material> = Rc::new(Principled::new(Color::new(0.1, 0.3, 0.1)).clearcoat(1.0));
+    let material_embossed:  Rc<dyn Material> = Rc::new(BumpMap::new(&material_lacquer, &heights, -0.03));
+
+    // World creation
+    let mut world = HittableList::new();
+    let ground_sphere:  Rc<dyn Hittable> = Rc::new(Sphere::new(Point3::new( 0.0, -100.5, -1.0), 100.0, &material_ground));
+    let sphere1:        Rc<dyn Hittable> = Rc::new(Sphere::new(Point3::new(-1.1,    0.0, -1.0),   0.5, &material_bumpy));
+    let sphere2:        Rc<dyn Hittable> = Rc::new(Sphere::new(Point3::new( 0.0,    0.0, -1.0),   0.5, &material_hammered));
+    let sphere3:        Rc<dyn Hittable> = Rc::new(Sphere::new(Point3::new( 1.1,    0.0, -1.0),   0.5, &material_embossed));
+
+    world.add(&ground_sphere);
+    world.add(&sphere1);
+    world.add(&sphere2);
+    world.add(&sphere3);
+
+    // world is frozen as an Rc<dyn Hittable> until the render loop is over
+    let world: Rc<dyn Hittable> = Rc::new(world);
+
+    Scene::new(
+        aspect_ratio,
+        image_width,
+        samples_per_pixel,
+        recursion_depth,
+        lookfrom,
+        lookat,
+        vfov,
+        aperture_width,
+        focus_distance,
+        world,
+    )
+}
@@ -0,0 +1,65 @@
+use std::rc::Rc;
+
+use crate::color::Color;
+use crate::hittable::{Hittable, HittableList, Sphere};
+use crate::material::{Dielectric, Ior, Lambertian, Material, Principled};
+use crate::texture::{Checker, IntoTexture, Texture};
+use crate::vec3::Point3;
+use crate::scene::Scene;
+
+pub fn get_scene() -> Scene {
+    // Image config
+    let aspect_ratio: f64 = 16.0/9.0;
+    let image_width: u32 = 600;
+
+    // Camera config
+    let lookfrom: Point3 = Point3::new(0.0, 0.6, 3.0);
+    let lookat: Point3 = Point3::new(0.0, 0.1, -1.0);
+    let vfov: f64 = 30.0;
+    let aperture_width: f64 = 0.0;
+    let focus_distance: f64 = (lookfrom - lookat).length();
+
+    // Raytracer config
+    let samples_per_pixel: u32 = 400;
+    let recursion_depth: u32 = 50;
+
+    // Textures
+    let stripes:        Rc<dyn Texture> = Rc::new(Checker::new(&Color::new(0.02, 0.02, 0.02).into_texture(), &Color::new(0.9, 0.9, 0.9).into_texture(), 0.1));
+
+    // Materials
+    let material_ground:    Rc<dyn Material> = Rc::new(Principled::new(&stripes).roughness(0.9));
+    let material_flint:     Rc<dyn Material> = Rc::new(Dielectric::dispersive(Ior::SF11));
+    let material_crown:     Rc<dyn Material> = Rc::new(Dielectric::dispersive(Ior::BK7));
+    let material_diffuse:   Rc<dyn Material> = Rc::new(Lambertian::new(Color::new(0.7, 0.2, 0.2)));
+
+    // World creation
+    let mut world = HittableList::new();
+    let ground_sphere:  Rc<dyn Hittable> = Rc::new(Sphere::new(Point3::new( 0.0, -100.5, -1.0), 100.0, &material_ground));
+    let sphere1:        Rc<dyn Hittable> = Rc::new(Sphere::new(Point3::new(-0.55,   0.0, -1.0),   0.5, &material_flint));
+    let sphere2:        Rc<dyn Hittable> = Rc::new(Sphere::new(Point3::new( 0.55,   0.0, -1.0),   0.5, &material_crown));
+    let sphere3:        Rc<dyn Hittable> = Rc::new(Sphere::new(Point3::new( 0.0,    0.0, -3.0),   0.5, &material_diffuse));
+
+    world.add(&ground_sphere);
+    world.add(&sphere1);
+    world.add(&sphere2);
+    world.add(&sphere3);
+
+    // world is frozen as an Rc<dyn Hittable> until the render loop is over
+    let world: Rc<dyn Hittable> = Rc::new(world);
+
+    // Dispersion only shows when tracing single wavelengths
+    let mut scene = Scene::new(
+        aspect_ratio,
+        image_width,
+        samples_per_pixel,
+        recursion_depth,
+        lookfrom,
+        lookat,
+        vfov,
+        aperture_width,
+        focus_distance,
+        world,
+    );
+    scene.spectral = true;
+    scene
+}
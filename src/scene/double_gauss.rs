@@ -0,0 +1,80 @@
+use std::rc::Rc;
+
+use crate::camera::LensSystem;
+use crate::color::Color;
+use crate::hittable::{Hittable, HittableList, Sphere};
+use crate::material::{Lambertian, Material, Metal};
+use crate::vec3::Point3;
+use crate::scene::Scene;
+
+/// 50mm f/2 double Gauss lens, from US patent 2,673,491 (Tronnier) as
+/// given in Modern Lens Design by Smith, scaled to 50mm
+const DOUBLE_GAUSS: &str = "
+# radius  thickness  ior    aperture
+29.475    3.76       1.67   25.2
+84.83     0.12       1      25.2
+19.275    4.025      1.67   23.0
+40.77     3.275      1.699  23.0
+12.75     5.705      1      18.0
+0         4.5        0      17.1
+-14.495   1.18       1.603  17.0
+40.77     6.065      1.658  20.0
+-20.385   0.19       1      20.0
+437.065   3.22       1.717  20.0
+-39.73    0          1      20.0
+";
+
+pub fn get_scene() -> Scene {
+    // Image config
+    let aspect_ratio: f64 = 3.0/2.0;
+    let image_width: u32 = 600;
+
+    // Camera config, the lens system sets the field of view and aperture
+    let lookfrom: Point3 = Point3::new(0.0, 0.6, 4.0);
+    let lookat: Point3 = Point3::new(0.0, 0.0, -1.0);
+    let vfov: f64 = 30.0;
+    let aperture_width: f64 = 0.0;
+    let focus_distance: f64 = (lookfrom - lookat).length();
+
+    // Raytracer config
+    let samples_per_pixel: u32 = 200;
+    let recursion_depth: u32 = 50;
+
+    // Materials
+    let material_ground:    Rc<dyn Material> = Rc::new(Lambertian::new(Color::new(0.5, 0.5, 0.5)));
+    let material_near:      Rc<dyn Material> = Rc::new(Lambertian::new(Color::new(0.7, 0.3, 0.3)));
+    let material_subject:   Rc<dyn Material> = Rc::new(Metal::new(Color::new(0.8, 0.8, 0.8), 0.0));
+    let material_far:       Rc<dyn Material> = Rc::new(Lambertian::new(Color::new(0.3, 0.4, 0.7)));
+
+    // World creation
+    let mut world = HittableList::new();
+    let ground_sphere:  Rc<dyn Hittable> = Rc::new(Sphere::new(Point3::new( 0.0, -100.5, -1.0), 100.0, &material_ground));
+    let sphere1:        Rc<dyn Hittable> = Rc::new(Sphere::new(Point3::new(-1.2,    0.0,  1.5),   0.5, &material_near));
+    let sphere2:        Rc<dyn Hittable> = Rc::new(Sphere::new(Point3::new( 0.0,    0.0, -1.0),   0.5, &material_subject));
+    let sphere3:        Rc<dyn Hittable> = Rc::new(Sphere::new(Point3::new( 1.5,    0.0, -5.0),   0.5, &material_far));
+
+    world.add(&ground_sphere);
+    world.add(&sphere1);
+    world.add(&sphere2);
+    world.add(&sphere3);
+
+    // world is frozen as an Rc<dyn Hittable> until the render loop is over
+    let world: Rc<dyn Hittable> = Rc::new(world);
+
+    // On a full frame sensor, with a world unit of a metre
+    let mut scene = Scene::new(
+        aspect_ratio,
+        image_width,
+        samples_per_pixel,
+        recursion_depth,
+        lookfrom,
+        lookat,
+        vfov,
+        aperture_width,
+        focus_distance,
+        world,
+    );
+    let lens = LensSystem::parse(DOUBLE_GAUSS).expect("lens prescription is valid");
+    scene.cam = scene.cam.lens_system(lens, 24.0).expect("lens focuses on the subject");
+    scene
+}
@@ -0,0 +1,63 @@
+use std::rc::Rc;
+
+use crate::color::Color;
+use crate::hittable::{Hittable, HittableList, Sphere};
+use crate::material::{Material, Dielectric, Lambertian, RoughDielectric};
+use crate::vec3::Point3;
+use crate::scene::Scene;
+
+pub fn get_scene() -> Scene {
+    // Image config
+    let aspect_ratio: f64 = 16.0/9.0;
+    let image_width: u32 = 600;
+
+    // Camera config
+    let lookfrom: Point3 = Point3::new(0.0, 1.0, 4.0);
+    let lookat: Point3 = Point3::new(0.0, 0.0, -1.0);
+    let vfov: f64 = 35.0;
+    let aperture_width: f64 = 0.0;
+    let focus_distance: f64 = (lookfrom - lookat).length();
+
+    // Raytracer config
+    let samples_per_pixel: u32 = 200;
+    let recursion_depth: u32 = 50;
+
+    // Materials
+    let material_ground:    Rc<dyn Material> = Rc::new(Lambertian::new(Color::new(0.8, 0.3, 0.3)));
+    let material_clear:     Rc<dyn Material> = Rc::new(Dielectric::new(1.5));
+    let material_frosted:   Rc<dyn Material> = Rc::new(RoughDielectric::new(1.5, 0.3));
+    let material_tinted:    Rc<dyn Material> = Rc::new(RoughDielectric::new(1.5, 0.0)
+        .with_absorption(Color::new(0.2, 0.6, 0.3), 1.0));
+    let material_smoky:     Rc<dyn Material> = Rc::new(RoughDielectric::new(1.5, 0.5)
+        .with_absorption(Color::new(0.3, 0.3, 0.35), 0.5));
+
+    // World creation
+    let mut world = HittableList::new();
+    let ground_sphere:  Rc<dyn Hittable> = Rc::new(Sphere::new(Point3::new( 0.0, -100.5, -1.0), 100.0, &material_ground));
+    let sphere1:        Rc<dyn Hittable> = Rc::new(Sphere::new(Point3::new(-1.65,   0.0, -1.0),   0.5, &material_clear));
+    let sphere2:        Rc<dyn Hittable> = Rc::new(Sphere::new(Point3::new(-0.55,   0.0, -1.0),   0.5, &material_frosted));
+    let sphere3:        Rc<dyn Hittable> = Rc::new(Sphere::new(Point3::new( 0.55,   0.0, -1.0),   0.5, &material_tinted));
+    let sphere4:        Rc<dyn Hittable> = Rc::new(Sphere::new(Point3::new( 1.65,   0.0, -1.0),   0.5, &material_smoky));
+
+    world.add(&ground_sphere);
+    world.add(&sphere1);
+    world.add(&sphere2);
+    world.add(&sphere3);
+    world.add(&sphere4);
+
+    // world is frozen as an Rc<dyn Hittable> until the render loop is over
+    let world: Rc<dyn Hittable> = Rc::new(world);
+
+    Scene::new(
+        aspect_ratio,
+        image_width,
+        samples_per_pixel,
+        recursion_depth,
+        lookfrom,
+        lookat,
+        vfov,
+        aperture_width,
+        focus_distance,
+        world,
+    )
+}
@@ -0,0 +1,75 @@
+use std::rc::Rc;
+
+use crate::color::Color;
+use crate::hittable::{Hittable, HittableList, Sphere};
+use crate::material::{Material, Lambertian, Dielectric, Metal, Principled};
+use crate::post::{Bloom, Glare};
+use crate::vec3::Point3;
+use crate::scene::Scene;
+
+pub fn get_scene() -> Scene {
+    // Image config
+    let aspect_ratio: f64 = 16.0/9.0;
+    let image_width: u32 = 600;
+
+    // Camera config
+    let lookfrom: Point3 = Point3::new(0.0, 0.8, 3.5);
+    let lookat: Point3 = Point3::new(0.0, 0.3, -1.0);
+    let vfov: f64 = 35.0;
+    let aperture_width: f64 = 0.0;
+    let focus_distance: f64 = 4.5;
+
+    // Raytracer config
+    let samples_per_pixel: u32 = 200;
+    let recursion_depth: u32 = 50;
+
+    // Post-processing config, balanced for the warm lights
+    let bloom = Bloom {threshold: 1.5, intensity: 0.15, radius: 0.015};
+    let glare = Glare {threshold: 5.0, intensity: 0.08, ..Glare::default()};
+    let white_balance: f64 = 5500.0;
+    let contrast: f64 = 1.15;
+    let saturation: f64 = 1.1;
+
+    // Materials
+    let material_ground: Rc<dyn Material> = Rc::new(Lambertian::new(Color::new(0.3, 0.3, 0.35)));
+    let material_glass:  Rc<dyn Material> = Rc::new(Dielectric::new(1.5));
+    let material_metal:  Rc<dyn Material> = Rc::new(Metal::new(Color::new(0.8, 0.8, 0.8), 0.02));
+    let material_lamp:   Rc<dyn Material> = Rc::new(Principled::new(Color::new(0.0, 0.0, 0.0)).emission(Color::new(60.0, 40.0, 20.0)));
+
+    // World creation
+    let mut world = HittableList::new();
+    let ground_sphere:  Rc<dyn Hittable> = Rc::new(Sphere::new(Point3::new( 0.0, -100.5, -1.0), 100.0, &material_ground));
+    let glass:          Rc<dyn Hittable> = Rc::new(Sphere::new(Point3::new(-0.6,    0.0, -1.0),   0.5, &material_glass));
+    let metal:          Rc<dyn Hittable> = Rc::new(Sphere::new(Point3::new( 0.6,    0.0, -1.0),   0.5, &material_metal));
+    let lamp_left:      Rc<dyn Hittable> = Rc::new(Sphere::new(Point3::new(-1.8,    0.9, -2.0),  0.05, &material_lamp));
+    let lamp_middle:    Rc<dyn Hittable> = Rc::new(Sphere::new(Point3::new( 0.0,    1.2, -2.5),  0.05, &material_lamp));
+    let lamp_right:     Rc<dyn Hittable> = Rc::new(Sphere::new(Point3::new( 1.8,    0.9, -2.0),  0.05, &material_lamp));
+    world.add(&ground_sphere);
+    world.add(&glass);
+    world.add(&metal);
+    world.add(&lamp_left);
+    world.add(&lamp_middle);
+    world.add(&lamp_right);
+
+    // world is frozen as an Rc<dyn Hittable> until the render loop is over
+    let world: Rc<dyn Hittable> = Rc::new(world);
+
+    let mut scene = Scene::new(
+        aspect_ratio,
+        image_width,
+        samples_per_pixel,
+        recursion_depth,
+        lookfrom,
+        lookat,
+        vfov,
+        aperture_width,
+        focus_distance,
+        world,
+    );
+    scene.post.bloom = Some(bloom);
+    scene.post.glare = Some(glare);
+    scene.post.white_balance = Some(white_balance);
+    scene.post.contrast = contrast;
+    scene.post.saturation = saturation;
+    scene
+}
@@ -0,0 +1,86 @@
+use std::rc::Rc;
+
+use image::{Rgb, RgbImage};
+
+use crate::PI;
+use crate::color::Color;
+use crate::hittable::{Hittable, HittableList, Sphere};
+use crate::material::{AddMaterial, Dielectric, Lambertian, Material, Metal, Sheen, ThinFilm};
+use crate::texture::{ImageTexture, Texture};
+use crate::vec3::Point3;
+use crate::scene::Scene;
+
+/// Relative thickness of a soap film over (u, v): thin at the top where it
+/// drains, with swirls, in [0, 1]
+fn film_thickness(width: u32, height: u32) -> RgbImage {
+    RgbImage::from_fn(width, height, |x, y| {
+        let u = (x as f64 + 0.5) / width as f64;
+        let v = 1.0 - (y as f64 + 0.5) / height as f64;
+        let swirl = 0.15*(2.0*PI*(3.0*u + 2.0*v)).sin()*(2.0*PI*5.0*v + 4.0*(2.0*PI*u).cos()).sin();
+        let t = (0.25 + 0.75*(1.0 - v) + swirl).clamp(0.0, 1.0);
+        let t = (255.0*t).round() as u8;
+        Rgb([t, t, t])
+    })
+}
+
+pub fn get_scene() -> Scene {
+    // Image config
+    let aspect_ratio: f64 = 16.0/9.0;
+    let image_width: u32 = 600;
+
+    // Camera config
+    let lookfrom: Point3 = Point3::new(0.0, 1.0, 4.0);
+    let lookat: Point3 = Point3::new(0.0, 0.0, -1.0);
+    let vfov: f64 = 35.0;
+    let aperture_width: f64 = 0.0;
+    let focus_distance: f64 = (lookfrom - lookat).length();
+
+    // Raytracer config
+    let samples_per_pixel: u32 = 200;
+    let recursion_depth: u32 = 50;
+
+    // Textures
+    let thickness:      Rc<dyn Texture> = Rc::new(ImageTexture::from_data(film_thickness(512, 256)));
+
+    // Materials
+    let material_ground:    Rc<dyn Material> = Rc::new(Lambertian::new(Color::new(0.5, 0.5, 0.5)));
+    let material_bubble:    Rc<dyn Material> = Rc::new(Dielectric::new(1.0)
+        .thin_film(ThinFilm::new(900.0, 1.33).thickness_map(&thickness)));
+    let material_lens:      Rc<dyn Material> = Rc::new(Dielectric::new(1.5)
+        .thin_film(ThinFilm::new(250.0, 1.38)));
+    let material_titanium:  Rc<dyn Material> = Rc::new(Metal::new(Color::new(0.55, 0.5, 0.45), 0.05)
+        .thin_film(ThinFilm::new(180.0, 2.4)));
+    let material_cloth:     Rc<dyn Material> = Rc::new(Lambertian::new(Color::new(0.25, 0.02, 0.05)));
+    let material_fibres:    Rc<dyn Material> = Rc::new(Sheen::new(Color::new(1.0, 0.6, 0.7), 0.4));
+    let material_velvet:    Rc<dyn Material> = Rc::new(AddMaterial::new(&material_cloth, &material_fibres, 1.0));
+
+    // World creation
+    let mut world = HittableList::new();
+    let ground_sphere:  Rc<dyn Hittable> = Rc::new(Sphere::new(Point3::new( 0.0, -100.5, -1.0), 100.0, &material_ground));
+    let sphere1:        Rc<dyn Hittable> = Rc::new(Sphere::new(Point3::new(-1.65,   0.0, -1.0),   0.5, &material_bubble));
+    let sphere2:        Rc<dyn Hittable> = Rc::new(Sphere::new(Point3::new(-0.55,   0.0, -1.0),   0.5, &material_lens));
+    let sphere3:        Rc<dyn Hittable> = Rc::new(Sphere::new(Point3::new( 0.55,   0.0, -1.0),   0.5, &material_titanium));
+    let sphere4:        Rc<dyn Hittable> = Rc::new(Sphere::new(Point3::new( 1.65,   0.0, -1.0),   0.5, &material_velvet));
+
+    world.add(&ground_sphere);
+    world.add(&sphere1);
+    world.add(&sphere2);
+    world.add(&sphere3);
+    world.add(&sphere4);
+
+    // world is frozen as an Rc<dyn Hittable> until the render loop is over
+    let world: Rc<dyn Hittable> = Rc::new(world);
+
+    Scene::new(
+        aspect_ratio,
+        image_width,
+        samples_per_pixel,
+        recursion_depth,
+        lookfrom,
+        lookat,
+        vfov,
+        aperture_width,
+        focus_distance,
+        world,
+    )
+}
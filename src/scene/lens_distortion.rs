@@ -0,0 +1,69 @@
+use std::rc::Rc;
+
+use crate::camera::LensDistortion;
+use crate::color::Color;
+use crate::hittable::{Hittable, HittableList, Sphere};
+use crate::material::{Material, Lambertian, Metal, Principled};
+use crate::texture::{Checker, IntoTexture, Texture};
+use crate::vec3::Point3;
+use crate::scene::Scene;
+
+pub fn get_scene() -> Scene {
+    // Image config
+    let aspect_ratio: f64 = 3.0/2.0;
+    let image_width: u32 = 600;
+
+    // Camera config, a cheap wide angle lens
+    let lookfrom: Point3 = Point3::new(0.0, 0.5, 3.0);
+    let lookat: Point3 = Point3::new(0.0, 0.5, -1.0);
+    let vfov: f64 = 75.0;
+    let aperture_width: f64 = 0.0;
+    let focus_distance: f64 = 4.0;
+    let distortion = LensDistortion::radial(-0.12, 0.015, 0.0).tangential(0.002, 0.0);
+    let chromatic_aberration: f64 = 0.006;
+    let vignetting: f64 = 1.0;
+
+    // Raytracer config
+    let samples_per_pixel: u32 = 100;
+    let recursion_depth: u32 = 50;
+
+    // Textures
+    let tiles:          Rc<dyn Texture> = Rc::new(Checker::new(&Color::new(0.05, 0.05, 0.05).into_texture(), &Color::new(0.9, 0.9, 0.9).into_texture(), 0.5));
+
+    // Materials
+    let material_tiles:  Rc<dyn Material> = Rc::new(Principled::new(&tiles).roughness(0.8));
+    let material_matte:  Rc<dyn Material> = Rc::new(Lambertian::new(Color::new(0.7, 0.2, 0.1)));
+    let material_metal:  Rc<dyn Material> = Rc::new(Metal::new(Color::new(0.8, 0.8, 0.8), 0.0));
+
+    // World creation, the floor and the back wall are huge spheres that look flat
+    let mut world = HittableList::new();
+    let floor:          Rc<dyn Hittable> = Rc::new(Sphere::new(Point3::new( 0.0, -1000.0,     0.0), 1000.0, &material_tiles));
+    let wall:           Rc<dyn Hittable> = Rc::new(Sphere::new(Point3::new( 0.0,     0.0, -1003.0), 1000.0, &material_tiles));
+    let sphere_left:    Rc<dyn Hittable> = Rc::new(Sphere::new(Point3::new(-1.2,     0.5,    -0.5),    0.5, &material_matte));
+    let sphere_right:   Rc<dyn Hittable> = Rc::new(Sphere::new(Point3::new( 1.2,     0.5,    -0.5),    0.5, &material_metal));
+    world.add(&floor);
+    world.add(&wall);
+    world.add(&sphere_left);
+    world.add(&sphere_right);
+
+    // world is frozen as an Rc<dyn Hittable> until the render loop is over
+    let world: Rc<dyn Hittable> = Rc::new(world);
+
+    let mut scene = Scene::new(
+        aspect_ratio,
+        image_width,
+        samples_per_pixel,
+        recursion_depth,
+        lookfrom,
+        lookat,
+        vfov,
+        aperture_width,
+        focus_distance,
+        world,
+    );
+    scene.cam = scene.cam
+        .distortion(distortion)
+        .chromatic_aberration(chromatic_aberration)
+        .vignetting(vignetting);
+    scene
+}
@@ -0,0 +1,62 @@
+use std::rc::Rc;
+
+use crate::PI;
+use crate::color::Color;
+use crate::hittable::{Hittable, HittableList, Sphere};
+use crate::material::{Material, Lambertian, Dielectric, Metal};
+use crate::vec3::Point3;
+use crate::scene::Scene;
+
+pub fn get_scene() -> Scene {
+    // Image config
+    let aspect_ratio: f64 = 1.0;
+    let image_width: u32 = 400;
+
+    // Camera config, looking straight up along the default up vector
+    let lookfrom: Point3 = Point3::new(0.0, 0.0, 0.0);
+    let lookat: Point3 = Point3::new(0.0, 5.0, 0.0);
+    let vfov: f64 = 70.0;
+    let aperture_width: f64 = 0.0;
+    let focus_distance: f64 = 5.0;
+    let roll: f64 = 20.0;
+
+    // Raytracer config
+    let samples_per_pixel: u32 = 100;
+    let recursion_depth: u32 = 50;
+
+    // Materials
+    let material_center: Rc<dyn Material> = Rc::new(Dielectric::new(1.5));
+    let material_warm:   Rc<dyn Material> = Rc::new(Lambertian::new(Color::new(0.8, 0.3, 0.1)));
+    let material_metal:  Rc<dyn Material> = Rc::new(Metal::new(Color::new(0.8, 0.8, 0.9), 0.0));
+
+    // World creation
+    let mut world = HittableList::new();
+    let center: Rc<dyn Hittable> = Rc::new(Sphere::new(Point3::new(0.0, 6.0, 0.0), 1.0, &material_center));
+    world.add(&center);
+
+    // A ring of spheres overhead, alternating materials, with the first one towards -z
+    for i in 0..8 {
+        let phi = 2.0*PI*(i as f64) / 8.0;
+        let material = if i % 2 == 0 {&material_warm} else {&material_metal};
+        let sphere: Rc<dyn Hittable> = Rc::new(Sphere::new(Point3::new(2.5*phi.sin(), 4.0, -2.5*phi.cos()), 0.5, material));
+        world.add(&sphere);
+    }
+
+    // world is frozen as an Rc<dyn Hittable> until the render loop is over
+    let world: Rc<dyn Hittable> = Rc::new(world);
+
+    let mut scene = Scene::new(
+        aspect_ratio,
+        image_width,
+        samples_per_pixel,
+        recursion_depth,
+        lookfrom,
+        lookat,
+        vfov,
+        aperture_width,
+        focus_distance,
+        world,
+    );
+    scene.cam = scene.cam.roll(roll);
+    scene
+}
@@ -62,7 +62,7 @@ pub fn get_scene() -> Scene {
             };
             let ground_normal = Point3::random_unit_vector();
             let ground_point = 100.0*ground_normal + Point3::new(0.0, -100.5, -1.0);  // Point on ground sphere
-            let center = &ground_point + &ground_normal*radius;
+            let center = ground_point + ground_normal*radius;
 
             // Continue if not within frame
             if Vec3::dot(&Vec3::new(0.0, 1.0, 0.0), &ground_normal).acos().to_degrees() > 5.0 {continue;}
@@ -71,7 +71,7 @@ pub fn get_scene() -> Scene {
             let mut overlaps = false;
             for sphere in spheres.iter() {
                 let min_distance = radius + sphere.radius();
-                if (&center - sphere.center()).length() < min_distance {
+                if (center - sphere.center()).length() < min_distance {
                     overlaps = true;
                 }
             }
@@ -0,0 +1,66 @@
+use std::rc::Rc;
+
+use crate::color::Color;
+use crate::hittable::{Hittable, HittableList, Sphere};
+use crate::material::{Material, Conductor, Lambertian};
+use crate::vec3::Point3;
+use crate::scene::Scene;
+
+pub fn get_scene() -> Scene {
+    // Image config
+    let aspect_ratio: f64 = 16.0/9.0;
+    let image_width: u32 = 600;
+
+    // Camera config
+    let lookfrom: Point3 = Point3::new(0.0, 1.0, 4.0);
+    let lookat: Point3 = Point3::new(0.0, 0.0, -1.0);
+    let vfov: f64 = 35.0;
+    let aperture_width: f64 = 0.0;
+    let focus_distance: f64 = (lookfrom - lookat).length();
+
+    // Raytracer config
+    let samples_per_pixel: u32 = 200;
+    let recursion_depth: u32 = 50;
+
+    // Materials
+    let material_ground:    Rc<dyn Material> = Rc::new(Lambertian::new(Color::new(0.5, 0.5, 0.5)));
+    let material_gold:      Rc<dyn Material> = Rc::new(Conductor::gold(0.1));
+    let material_copper:    Rc<dyn Material> = Rc::new(Conductor::copper(0.35));
+    let material_silver:    Rc<dyn Material> = Rc::new(Conductor::silver(0.0));
+    let material_brushed:   Rc<dyn Material> = Rc::new(Conductor::anisotropic(
+        Color::new(1.657, 0.880, 0.521),  // Aluminium
+        Color::new(9.224, 6.270, 4.837),
+        0.6,
+        0.1,
+    ));
+
+    // World creation
+    let mut world = HittableList::new();
+    let ground_sphere:  Rc<dyn Hittable> = Rc::new(Sphere::new(Point3::new( 0.0, -100.5, -1.0), 100.0, &material_ground));
+    let sphere1:        Rc<dyn Hittable> = Rc::new(Sphere::new(Point3::new(-1.65,   0.0, -1.0),   0.5, &material_gold));
+    let sphere2:        Rc<dyn Hittable> = Rc::new(Sphere::new(Point3::new(-0.55,   0.0, -1.0),   0.5, &material_copper));
+    let sphere3:        Rc<dyn Hittable> = Rc::new(Sphere::new(Point3::new( 0.55,   0.0, -1.0),   0.5, &material_silver));
+    let sphere4:        Rc<dyn Hittable> = Rc::new(Sphere::new(Point3::new( 1.65,   0.0, -1.0),   0.5, &material_brushed));
+
+    world.add(&ground_sphere);
+    world.add(&sphere1);
+    world.add(&sphere2);
+    world.add(&sphere3);
+    world.add(&sphere4);
+
+    // world is frozen as an Rc<dyn Hittable> until the render loop is over
+    let world: Rc<dyn Hittable> = Rc::new(world);
+
+    Scene::new(
+        aspect_ratio,
+        image_width,
+        samples_per_pixel,
+        recursion_depth,
+        lookfrom,
+        lookat,
+        vfov,
+        aperture_width,
+        focus_distance,
+        world,
+    )
+}
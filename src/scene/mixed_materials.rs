@@ -0,0 +1,67 @@
+use std::rc::Rc;
+
+use crate::color::Color;
+use crate::hittable::{Hittable, HittableList, Sphere};
+use crate::material::{AddMaterial, Conductor, Dielectric, Lambertian, Material, Metal, MixMaterial};
+use crate::texture::{Checker, IntoTexture, Texture};
+use crate::vec3::Point3;
+use crate::scene::Scene;
+
+pub fn get_scene() -> Scene {
+    // Image config
+    let aspect_ratio: f64 = 16.0/9.0;
+    let image_width: u32 = 600;
+
+    // Camera config
+    let lookfrom: Point3 = Point3::new(0.0, 1.0, 4.0);
+    let lookat: Point3 = Point3::new(0.0, 0.0, -1.0);
+    let vfov: f64 = 30.0;
+    let aperture_width: f64 = 0.0;
+    let focus_distance: f64 = (lookfrom - lookat).length();
+
+    // Raytracer config
+    let samples_per_pixel: u32 = 200;
+    let recursion_depth: u32 = 50;
+
+    // Textures
+    let rust_mask:      Rc<dyn Texture> = Rc::new(Checker::new(&0.0.into_texture(), &0.8.into_texture(), 0.12));
+
+    // Materials
+    let material_ground:    Rc<dyn Material> = Rc::new(Lambertian::new(Color::new(0.5, 0.5, 0.5)));
+    let material_steel:     Rc<dyn Material> = Rc::new(Metal::new(Color::new(0.7, 0.7, 0.75), 0.1));
+    let material_rust:      Rc<dyn Material> = Rc::new(Lambertian::new(Color::new(0.45, 0.18, 0.06)));
+    let material_rusted:    Rc<dyn Material> = Rc::new(MixMaterial::new(&material_steel, &material_rust, &rust_mask));
+    let material_paint:     Rc<dyn Material> = Rc::new(Lambertian::new(Color::new(0.1, 0.3, 0.6)));
+    let material_gloss:     Rc<dyn Material> = Rc::new(Conductor::silver(0.15));
+    let material_lacquered: Rc<dyn Material> = Rc::new(AddMaterial::new(&material_paint, &material_gloss, 0.08));
+    let material_glass:     Rc<dyn Material> = Rc::new(Dielectric::new(1.5));
+    let material_murky:     Rc<dyn Material> = Rc::new(MixMaterial::new(&material_glass, &material_rust, 0.3));
+
+    // World creation
+    let mut world = HittableList::new();
+    let ground_sphere:  Rc<dyn Hittable> = Rc::new(Sphere::new(Point3::new( 0.0, -100.5, -1.0), 100.0, &material_ground));
+    let sphere1:        Rc<dyn Hittable> = Rc::new(Sphere::new(Point3::new(-1.1,    0.0, -1.0),   0.5, &material_rusted));
+    let sphere2:        Rc<dyn Hittable> = Rc::new(Sphere::new(Point3::new( 0.0,    0.0, -1.0),   0.5, &material_lacquered));
+    let sphere3:        Rc<dyn Hittable> = Rc::new(Sphere::new(Point3::new( 1.1,    0.0, -1.0),   0.5, &material_murky));
+
+    world.add(&ground_sphere);
+    world.add(&sphere1);
+    world.add(&sphere2);
+    world.add(&sphere3);
+
+    // world is frozen as an Rc<dyn Hittable> until the render loop is over
+    let world: Rc<dyn Hittable> = Rc::new(world);
+
+    Scene::new(
+        aspect_ratio,
+        image_width,
+        samples_per_pixel,
+        recursion_depth,
+        lookfrom,
+        lookat,
+        vfov,
+        aperture_width,
+        focus_distance,
+        world,
+    )
+}
@@ -1,25 +1,112 @@
+pub mod alpha_cutouts;
+pub mod animated_spheres;
+pub mod bokeh;
+pub mod bumpy_spheres;
 pub mod dielectric_lambertian_metal;
+pub mod dispersion;
+pub mod double_gauss;
+pub mod frosted_glass;
+pub mod glowing_lights;
+pub mod iridescence;
 pub mod lonely_sphere;
+pub mod lens_distortion;
+pub mod looking_up;
 pub mod lots_of_random_spheres;
+pub mod microfacet_metals;
+pub mod mixed_materials;
+pub mod panorama;
+pub mod photographic;
+pub mod principled_spheres;
+pub mod smoke_and_fire;
+pub mod subsurface_spheres;
+pub mod tilt_shift;
 pub mod two_spheres_wide_fov;
 
 use std::rc::Rc;
 
-use crate::camera::Camera;
+use crate::INFINITY;
+use crate::animation::Timeline;
+use crate::camera::{Camera, Focus, PhysicalCamera};
+use crate::checkpoint::Fnv64;
 use crate::hittable::Hittable;
+use crate::post::PostSettings;
 use crate::vec3::{Point3, Vec3};
 
+/// Names of the scenes that can be loaded with by_name
+pub const NAMES: [&str; 23] = [
+    "alpha_cutouts",
+    "animated_spheres",
+    "bokeh",
+    "bumpy_spheres",
+    "dielectric_lambertian_metal",
+    "dispersion",
+    "double_gauss",
+    "frosted_glass",
+    "glowing_lights",
+    "iridescence",
+    "lonely_sphere",
+    "lens_distortion",
+    "looking_up",
+    "lots_of_random_spheres",
+    "microfacet_metals",
+    "mixed_materials",
+    "panorama",
+    "photographic",
+    "principled_spheres",
+    "smoke_and_fire",
+    "subsurface_spheres",
+    "tilt_shift",
+    "two_spheres_wide_fov",
+];
+
+/// Builds the scene with the given module name
+pub fn by_name(name: &str) -> Option<Scene> {
+    let scene = match name {
+        "alpha_cutouts" => Some(alpha_cutouts::get_scene()),
+        "animated_spheres" => Some(animated_spheres::get_scene()),
+        "bokeh" => Some(bokeh::get_scene()),
+        "bumpy_spheres" => Some(bumpy_spheres::get_scene()),
+        "dielectric_lambertian_metal" => Some(dielectric_lambertian_metal::get_scene()),
+        "dispersion" => Some(dispersion::get_scene()),
+        "double_gauss" => Some(double_gauss::get_scene()),
+        "frosted_glass" => Some(frosted_glass::get_scene()),
+        "glowing_lights" => Some(glowing_lights::get_scene()),
+        "iridescence" => Some(iridescence::get_scene()),
+        "lonely_sphere" => Some(lonely_sphere::get_scene()),
+        "lens_distortion" => Some(lens_distortion::get_scene()),
+        "looking_up" => Some(looking_up::get_scene()),
+        "lots_of_random_spheres" => Some(lots_of_random_spheres::get_scene()),
+        "microfacet_metals" => Some(microfacet_metals::get_scene()),
+        "mixed_materials" => Some(mixed_materials::get_scene()),
+        "panorama" => Some(panorama::get_scene()),
+        "photographic" => Some(photographic::get_scene()),
+        "principled_spheres" => Some(principled_spheres::get_scene()),
+        "smoke_and_fire" => Some(smoke_and_fire::get_scene()),
+        "subsurface_spheres" => Some(subsurface_spheres::get_scene()),
+        "tilt_shift" => Some(tilt_shift::get_scene()),
+        "two_spheres_wide_fov" => Some(two_spheres_wide_fov::get_scene()),
+        _ => None,
+    };
+    scene.map(|scene| Scene {description: name.to_string(), ..scene})
+}
+
 pub struct Scene {
     pub aspect_ratio: f64,
     pub image_width: u32,
     pub image_height: u32,
     pub samples_per_pixel: u32,
     pub recursion_depth: u32,
+    pub spectral: bool,  // Whether to trace single wavelengths instead of RGB
     pub cam: Camera,
     pub world: Rc<dyn Hittable>,
+    pub timeline: Option<Timeline>,  // Set for animated scenes
+    pub time: f64,  // In seconds, when the current frame starts
+    pub post: PostSettings,  // Effects applied to the image after rendering
+    pub description: String,  // What the world was built from, eg. the scene's name
 }
 
 impl Scene {
+    #[allow(clippy::too_many_arguments)]
     pub fn new(
         aspect_ratio: f64,
         image_width: u32,
@@ -38,6 +125,7 @@ impl Scene {
             image_height: ((image_width as f64) / aspect_ratio) as u32,
             samples_per_pixel,
             recursion_depth,
+            spectral: false,
             cam: Camera::new(
                 lookfrom,
                 lookat,
@@ -48,6 +136,114 @@ impl Scene {
                 focus_distance,
             ),
             world,
+            timeline: None,
+            time: 0.0,
+            post: PostSettings::default(),
+            description: String::new(),
         }
     }
+
+    /// Scene with a camera set up in photographic terms rather than by
+    /// vfov, aperture width and focus distance
+    ///
+    /// Auto-focus casts a ray through the chosen pixel and focuses on the
+    /// first thing it hits, or on lookat if it hits nothing.
+    #[allow(clippy::too_many_arguments)]
+    pub fn physical(
+        aspect_ratio: f64,
+        image_width: u32,
+        samples_per_pixel: u32,
+        recursion_depth: u32,
+        lookfrom: Point3,
+        lookat: Point3,
+        settings: &PhysicalCamera,
+        world: Rc<dyn Hittable>,
+    ) -> Self {
+        let image_height = ((image_width as f64) / aspect_ratio) as u32;
+        let vup = Vec3::new(0.0, 1.0, 0.0);
+        let axis = (lookat - lookfrom).unit_vector();
+        let focus_distance = match settings.focus {
+            Focus::Distance(distance) => distance,
+            Focus::Auto {x, y} => {
+                let pinhole = Camera::new(lookfrom, lookat, vup, settings.vfov(aspect_ratio), aspect_ratio, 0.0, 1.0);
+                let s = (x as f64 + 0.5) / image_width as f64;
+                let t = 1.0 - (y as f64 + 0.5) / image_height as f64;
+                pinhole.get_ray(s, t)
+                    .and_then(|r| world.hit(&r, 0.001, INFINITY))
+                    .map_or((lookat - lookfrom).length(), |hit_record| Vec3::dot(&(hit_record.p() - lookfrom), &axis))
+            },
+        };
+
+        Self {
+            aspect_ratio,
+            image_width,
+            image_height,
+            samples_per_pixel,
+            recursion_depth,
+            spectral: false,
+            cam: Camera::physical(lookfrom, lookat, vup, aspect_ratio, settings, focus_distance),
+            world,
+            timeline: None,
+            time: 0.0,
+            post: PostSettings::default(),
+            description: String::new(),
+        }
+    }
+
+    /// Moves the scene to frame of its timeline, setting up the camera for
+    /// it. Does nothing if the scene isn't animated.
+    pub fn set_frame(&mut self, frame: u32) {
+        if let Some(timeline) = &self.timeline {
+            self.time = timeline.time(frame);
+            self.cam = timeline.camera(self.time);
+        }
+    }
+
+    /// How long the shutter stays open during a frame, in seconds
+    pub fn shutter_time(&self) -> f64 {
+        self.timeline.as_ref().map_or(0.0, |timeline| timeline.shutter / timeline.fps)
+    }
+
+    /// Fingerprint of the scene used to guard checkpoints against being
+    /// resumed with a different scene
+    ///
+    /// Covers the image dimensions, recursion depth, spectral mode and camera,
+    /// plus the description of the world. Scenes are built the same way from
+    /// the same description and seed, so together with the seed stored in the
+    /// checkpoint that pins down the geometry, materials and textures without
+    /// tracing any rays. samples_per_pixel is deliberately left out so that
+    /// more samples can be added to a finished render.
+    pub fn hash(&self) -> u64 {
+        let mut hasher = Fnv64::new();
+        hasher.write_u32(self.image_width);
+        hasher.write_u32(self.image_height);
+        hasher.write_u32(self.recursion_depth);
+        hasher.write(&[self.spectral as u8]);
+        hasher.write(format!("{:?}", self.cam).as_bytes());
+        hasher.write(self.description.as_bytes());
+        hasher.finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_scene_hash() {
+        // Rebuilding a scene gives the same hash, and hashing doesn't consume random numbers
+        let scene = by_name("lonely_sphere").unwrap();
+        let state = crate::rng::state();
+        let hash = scene.hash();
+        assert_eq!(crate::rng::state(), state);
+        assert_eq!(by_name("lonely_sphere").unwrap().hash(), hash);
+
+        // Other worlds and spectral rendering change it
+        let mut other = by_name("lonely_sphere").unwrap();
+        other.description = "two_spheres_wide_fov".to_string();
+        assert_ne!(other.hash(), hash);
+        let mut spectral = by_name("lonely_sphere").unwrap();
+        spectral.spectral = true;
+        assert_ne!(spectral.hash(), hash);
+    }
 }
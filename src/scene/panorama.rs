@@ -0,0 +1,67 @@
+use std::rc::Rc;
+
+use crate::PI;
+use crate::camera::Projection;
+use crate::color::Color;
+use crate::hittable::{Hittable, HittableList, Sphere};
+use crate::material::{Dielectric, Lambertian, Material, Metal};
+use crate::vec3::Point3;
+use crate::scene::Scene;
+
+pub fn get_scene() -> Scene {
+    // Image config
+    let aspect_ratio: f64 = 2.0;
+    let image_width: u32 = 800;
+
+    // Camera config
+    let lookfrom: Point3 = Point3::new(0.0, 0.0, 0.0);
+    let lookat: Point3 = Point3::new(0.0, 0.0, -1.0);
+    let vfov: f64 = 90.0;
+    let aperture_width: f64 = 0.0;
+    let focus_distance: f64 = 1.0;
+
+    // Raytracer config
+    let samples_per_pixel: u32 = 100;
+    let recursion_depth: u32 = 50;
+
+    // Materials
+    let material_ground:    Rc<dyn Material> = Rc::new(Lambertian::new(Color::new(0.5, 0.5, 0.5)));
+    let material_glass:     Rc<dyn Material> = Rc::new(Dielectric::new(1.5));
+    let material_metal:     Rc<dyn Material> = Rc::new(Metal::new(Color::new(0.8, 0.8, 0.8), 0.0));
+
+    // World creation
+    let mut world = HittableList::new();
+    let ground_sphere:  Rc<dyn Hittable> = Rc::new(Sphere::new(Point3::new(0.0, -100.5, 0.0), 100.0, &material_ground));
+    world.add(&ground_sphere);
+
+    // A ring of spheres all the way around the camera
+    for i in 0..8 {
+        let angle = 2.0*PI*(i as f64) / 8.0;
+        let material: Rc<dyn Material> = match i % 4 {
+            0 => Rc::clone(&material_glass),
+            2 => Rc::clone(&material_metal),
+            _ => Rc::new(Lambertian::new(Color::new(0.2 + 0.1*i as f64, 0.3, 0.9 - 0.1*i as f64))),
+        };
+        let sphere: Rc<dyn Hittable> = Rc::new(Sphere::new(Point3::new(2.0*angle.sin(), 0.0, -2.0*angle.cos()), 0.5, &material));
+        world.add(&sphere);
+    }
+
+    // world is frozen as an Rc<dyn Hittable> until the render loop is over
+    let world: Rc<dyn Hittable> = Rc::new(world);
+
+    // The whole sphere of directions, for environment maps and VR viewers
+    let mut scene = Scene::new(
+        aspect_ratio,
+        image_width,
+        samples_per_pixel,
+        recursion_depth,
+        lookfrom,
+        lookat,
+        vfov,
+        aperture_width,
+        focus_distance,
+        world,
+    );
+    scene.cam = scene.cam.projection(Projection::Equirectangular);
+    scene
+}
@@ -0,0 +1,60 @@
+use std::rc::Rc;
+
+use crate::camera::{Focus, PhysicalCamera};
+use crate::color::Color;
+use crate::hittable::{Hittable, HittableList, Sphere};
+use crate::material::{Lambertian, Material, Metal};
+use crate::vec3::Point3;
+use crate::scene::Scene;
+
+pub fn get_scene() -> Scene {
+    // Image config
+    let aspect_ratio: f64 = 3.0/2.0;
+    let image_width: u32 = 600;
+
+    // Camera config, a portrait lens wide open at dusk (the sky is about 1 cd/m^2),
+    // auto-focused on the third sphere
+    let lookfrom: Point3 = Point3::new(3.0, 1.0, 6.0);
+    let lookat: Point3 = Point3::new(0.0, 0.0, -2.0);
+    let camera = PhysicalCamera::new(85.0, 1.8)
+        .exposure(1.0 / 4.0, 1600.0)
+        .focus(Focus::Auto {x: 245, y: 220});
+
+    // Raytracer config
+    let samples_per_pixel: u32 = 200;
+    let recursion_depth: u32 = 50;
+
+    // Materials
+    let material_ground:    Rc<dyn Material> = Rc::new(Lambertian::new(Color::new(0.5, 0.5, 0.5)));
+    let material_metal:     Rc<dyn Material> = Rc::new(Metal::new(Color::new(0.8, 0.7, 0.6), 0.2));
+
+    // World creation
+    let mut world = HittableList::new();
+    let ground_sphere:  Rc<dyn Hittable> = Rc::new(Sphere::new(Point3::new(0.0, -100.5, -1.0), 100.0, &material_ground));
+    world.add(&ground_sphere);
+
+    // A row of spheres leading away from the camera
+    for i in 0..7 {
+        let material: Rc<dyn Material> = if i % 2 == 0 {
+            Rc::new(Lambertian::new(Color::new(0.1 + 0.12*i as f64, 0.3, 0.8 - 0.1*i as f64)))
+        } else {
+            Rc::clone(&material_metal)
+        };
+        let sphere: Rc<dyn Hittable> = Rc::new(Sphere::new(Point3::new(0.0, 0.0, 2.0 - 1.5*i as f64), 0.5, &material));
+        world.add(&sphere);
+    }
+
+    // world is frozen as an Rc<dyn Hittable> until the render loop is over
+    let world: Rc<dyn Hittable> = Rc::new(world);
+
+    Scene::physical(
+        aspect_ratio,
+        image_width,
+        samples_per_pixel,
+        recursion_depth,
+        lookfrom,
+        lookat,
+        &camera,
+        world,
+    )
+}
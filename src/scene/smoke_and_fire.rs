@@ -0,0 +1,102 @@
+use std::rc::Rc;
+
+use crate::PI;
+use crate::color::Color;
+use crate::grid::{DenseGrid, Grid, SparseGrid};
+use crate::hittable::{GridVolume, Hittable, HittableList, Sphere};
+use crate::material::{Lambertian, Material};
+use crate::vec3::Point3;
+use crate::scene::Scene;
+
+/// Voxels along each side of the grids
+const RESOLUTION: usize = 64;
+
+/// Lumpy variation in [-1, 1] at (x, y, z) in [0, 1]^3, a few octaves of sines
+fn turbulence(x: f64, y: f64, z: f64) -> f64 {
+    (0..3).map(|octave| {
+        let f = 2.0*PI*(2 << octave) as f64;
+        let amplitude = 0.5_f64.powi(octave);
+        amplitude*(f*x + 1.7*(f*z).sin()).sin()*(f*y + 1.3*(f*x).cos()).sin()*(f*z + 0.9*(f*y).sin()).cos()
+    }).sum::<f64>() / 1.75
+}
+
+/// Position of the centre of voxel (i, j, k) in [0, 1]^3
+fn voxel_position(i: usize, j: usize, k: usize) -> (f64, f64, f64) {
+    let n = RESOLUTION as f64;
+    ((i as f64 + 0.5) / n, (j as f64 + 0.5) / n, (k as f64 + 0.5) / n)
+}
+
+/// Puffy cloud of smoke: a ball with turbulent edges, empty outside it
+fn smoke_density(i: usize, j: usize, k: usize) -> f64 {
+    let (x, y, z) = voxel_position(i, j, k);
+    let r = ((x - 0.5).powi(2) + (y - 0.5).powi(2) + (z - 0.5).powi(2)).sqrt();
+    ((0.4 - r + 0.12*turbulence(x, y, z)) / 0.1).clamp(0.0, 1.0)
+}
+
+/// Flame rising from the bottom of the grid, narrowing and thinning out
+/// with height, as (density, temperature) with temperature in [0, 1]
+fn flame(i: usize, j: usize, k: usize) -> (f64, f64) {
+    let (x, y, z) = voxel_position(i, j, k);
+    let radius = 0.3*(1.0 - y).powf(0.7) + 0.06*turbulence(x, 0.5*y, z);
+    let r = ((x - 0.5).powi(2) + (z - 0.5).powi(2)).sqrt();
+    let inside = ((radius - r) / 0.08).clamp(0.0, 1.0);
+    (inside*(1.0 - 0.6*y), inside*(1.0 - y).powi(2))
+}
+
+pub fn get_scene() -> Scene {
+    // Image config
+    let aspect_ratio: f64 = 16.0/9.0;
+    let image_width: u32 = 600;
+
+    // Camera config
+    let lookfrom: Point3 = Point3::new(0.0, 0.8, 4.5);
+    let lookat: Point3 = Point3::new(0.0, 0.2, -1.0);
+    let vfov: f64 = 35.0;
+    let aperture_width: f64 = 0.0;
+    let focus_distance: f64 = (lookfrom - lookat).length();
+
+    // Raytracer config
+    let samples_per_pixel: u32 = 200;
+    let recursion_depth: u32 = 50;
+
+    // Grids, the smoke is mostly empty so it's stored sparsely
+    let dims = [RESOLUTION; 3];
+    let smoke:          Rc<dyn Grid> = Rc::new(SparseGrid::from_grid(&DenseGrid::from_fn(dims, smoke_density)));
+    let flame_density:  Rc<dyn Grid> = Rc::new(DenseGrid::from_fn(dims, |i, j, k| flame(i, j, k).0));
+    let temperature:    Rc<dyn Grid> = Rc::new(DenseGrid::from_fn(dims, |i, j, k| flame(i, j, k).1));
+
+    // Materials
+    let material_ground:    Rc<dyn Material> = Rc::new(Lambertian::new(Color::new(0.5, 0.5, 0.5)));
+
+    // World creation
+    let mut world = HittableList::new();
+    let ground_sphere:  Rc<dyn Hittable> = Rc::new(Sphere::new(Point3::new(0.0, -100.5, -1.0), 100.0, &material_ground));
+    let cloud:          Rc<dyn Hittable> = Rc::new(GridVolume::new(&smoke, Point3::new(-1.9, -0.4, -1.8), 1.6 / RESOLUTION as f64)
+        .density_scale(8.0)
+        .albedo(Color::new(0.9, 0.9, 0.92))
+        .anisotropy(0.6));
+    let fire:           Rc<dyn Hittable> = Rc::new(GridVolume::new(&flame_density, Point3::new(0.3, -0.5, -1.8), 1.6 / RESOLUTION as f64)
+        .density_scale(12.0)
+        .albedo(Color::new(0.2, 0.2, 0.2))
+        .emission(&temperature, Color::new(12.0, 4.0, 0.8)));
+
+    world.add(&ground_sphere);
+    world.add(&cloud);
+    world.add(&fire);
+
+    // world is frozen as an Rc<dyn Hittable> until the render loop is over
+    let world: Rc<dyn Hittable> = Rc::new(world);
+
+    Scene::new(
+        aspect_ratio,
+        image_width,
+        samples_per_pixel,
+        recursion_depth,
+        lookfrom,
+        lookat,
+        vfov,
+        aperture_width,
+        focus_distance,
+        world,
+    )
+}
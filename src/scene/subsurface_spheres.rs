@@ -0,0 +1,72 @@
+use std::rc::Rc;
+
+use crate::color::Color;
+use crate::hittable::{Hittable, HittableList, Sphere, Subsurface};
+use crate::material::{Dielectric, Lambertian, Material, RoughDielectric};
+use crate::medium::Medium;
+use crate::vec3::Point3;
+use crate::scene::Scene;
+
+pub fn get_scene() -> Scene {
+    // Image config
+    let aspect_ratio: f64 = 16.0/9.0;
+    let image_width: u32 = 600;
+
+    // Camera config
+    let lookfrom: Point3 = Point3::new(0.0, 1.0, 4.0);
+    let lookat: Point3 = Point3::new(0.0, 0.0, -1.0);
+    let vfov: f64 = 35.0;
+    let aperture_width: f64 = 0.0;
+    let focus_distance: f64 = (lookfrom - lookat).length();
+
+    // Raytracer config
+    let samples_per_pixel: u32 = 200;
+    let recursion_depth: u32 = 50;
+
+    // Materials
+    let material_ground:    Rc<dyn Material> = Rc::new(Lambertian::new(Color::new(0.5, 0.5, 0.5)));
+    let material_skin:      Rc<dyn Material> = Rc::new(RoughDielectric::new(1.4, 0.3));
+    let material_wax:       Rc<dyn Material> = Rc::new(Dielectric::new(1.45));
+    let material_marble:    Rc<dyn Material> = Rc::new(Dielectric::new(1.5));
+    let material_milk:      Rc<dyn Material> = Rc::new(Dielectric::new(1.35));
+
+    // Media filling the spheres, the materials being their surfaces
+    let skin:   Medium = Medium::from_albedo(Color::new(0.8, 0.5, 0.4), Color::new(0.12, 0.04, 0.02));
+    let wax:    Medium = Medium::from_albedo(Color::new(0.9, 0.75, 0.5), Color::new(0.2, 0.15, 0.08));
+    let marble: Medium = Medium::from_albedo(Color::new(0.93, 0.93, 0.9), Color::new(0.03, 0.03, 0.04));
+    let milk:   Medium = Medium::from_albedo(Color::new(0.95, 0.93, 0.88), Color::new(0.15, 0.15, 0.1)).anisotropy(0.7);
+
+    // World creation
+    let mut world = HittableList::new();
+    let ground_sphere:  Rc<dyn Hittable> = Rc::new(Sphere::new(Point3::new( 0.0, -100.5, -1.0), 100.0, &material_ground));
+    let sphere1:        Rc<dyn Hittable> = Rc::new(Sphere::new(Point3::new(-1.65,   0.0, -1.0),   0.5, &material_skin));
+    let sphere2:        Rc<dyn Hittable> = Rc::new(Sphere::new(Point3::new(-0.55,   0.0, -1.0),   0.5, &material_wax));
+    let sphere3:        Rc<dyn Hittable> = Rc::new(Sphere::new(Point3::new( 0.55,   0.0, -1.0),   0.5, &material_marble));
+    let sphere4:        Rc<dyn Hittable> = Rc::new(Sphere::new(Point3::new( 1.65,   0.0, -1.0),   0.5, &material_milk));
+    let sphere1:        Rc<dyn Hittable> = Rc::new(Subsurface::new(&sphere1, skin));
+    let sphere2:        Rc<dyn Hittable> = Rc::new(Subsurface::new(&sphere2, wax));
+    let sphere3:        Rc<dyn Hittable> = Rc::new(Subsurface::new(&sphere3, marble));
+    let sphere4:        Rc<dyn Hittable> = Rc::new(Subsurface::new(&sphere4, milk));
+
+    world.add(&ground_sphere);
+    world.add(&sphere1);
+    world.add(&sphere2);
+    world.add(&sphere3);
+    world.add(&sphere4);
+
+    // world is frozen as an Rc<dyn Hittable> until the render loop is over
+    let world: Rc<dyn Hittable> = Rc::new(world);
+
+    Scene::new(
+        aspect_ratio,
+        image_width,
+        samples_per_pixel,
+        recursion_depth,
+        lookfrom,
+        lookat,
+        vfov,
+        aperture_width,
+        focus_distance,
+        world,
+    )
+}
@@ -0,0 +1,64 @@
+use std::rc::Rc;
+
+use crate::color::Color;
+use crate::hittable::{Hittable, HittableList, Sphere};
+use crate::material::{Lambertian, Material};
+use crate::vec3::Point3;
+use crate::scene::Scene;
+
+pub fn get_scene() -> Scene {
+    // Image config
+    let aspect_ratio: f64 = 16.0/9.0;
+    let image_width: u32 = 600;
+
+    // Camera config
+    let lookfrom: Point3 = Point3::new(0.0, 6.0, 8.0);
+    let lookat: Point3 = Point3::new(0.0, 0.0, -2.0);
+    let vfov: f64 = 40.0;
+    let aperture_width: f64 = 0.8;
+    let focus_distance: f64 = (lookfrom - lookat).length();
+
+    // Raytracer config
+    let samples_per_pixel: u32 = 100;
+    let recursion_depth: u32 = 50;
+
+    // Materials
+    let material_ground:    Rc<dyn Material> = Rc::new(Lambertian::new(Color::new(0.4, 0.5, 0.35)));
+
+    // World creation
+    let mut world = HittableList::new();
+    let ground_sphere:  Rc<dyn Hittable> = Rc::new(Sphere::new(Point3::new(0.0, -1000.0, 0.0), 1000.0, &material_ground));
+    world.add(&ground_sphere);
+
+    // Rows of spheres receding from the camera
+    for row in 0..12 {
+        for column in -5..=5 {
+            let hue = (row + column + 12) as f64 / 20.0;
+            let color = Color::new(0.3 + 0.6*hue, 0.8 - 0.5*hue, 0.3 + 0.4*(1.0 - hue)*hue);
+            let material: Rc<dyn Material> = Rc::new(Lambertian::new(color));
+            let center = Point3::new(1.2*column as f64, 0.4, 4.0 - 1.8*row as f64);
+            let sphere: Rc<dyn Hittable> = Rc::new(Sphere::new(center, 0.4, &material));
+            world.add(&sphere);
+        }
+    }
+
+    // world is frozen as an Rc<dyn Hittable> until the render loop is over
+    let world: Rc<dyn Hittable> = Rc::new(world);
+
+    // Tilting the focal plane against the ground leaves a thin band in
+    // focus, which makes the scene look like a miniature
+    let mut scene = Scene::new(
+        aspect_ratio,
+        image_width,
+        samples_per_pixel,
+        recursion_depth,
+        lookfrom,
+        lookat,
+        vfov,
+        aperture_width,
+        focus_distance,
+        world,
+    );
+    scene.cam = scene.cam.tilt(-30.0, 0.0).shift(0.0, 0.1);
+    scene
+}
@@ -0,0 +1,172 @@
+use std::sync::OnceLock;
+
+use crate::color::{Color, ColorSpace};
+
+/// Range of wavelengths, in nm, traced in spectral mode
+pub const LAMBDA_MIN: f64 = 360.0;
+pub const LAMBDA_MAX: f64 = 830.0;
+
+/// Wavelength of the sodium d-line, where refractive indices are usually
+/// quoted and the one dispersive materials use when rendering in RGB
+pub const LAMBDA_D: f64 = 587.6;
+
+/// Samples a wavelength in [LAMBDA_MIN, LAMBDA_MAX] from u uniform in [0, 1)
+///
+/// Wavelengths the eye is more sensitive to are sampled more often
+/// (Radziszewski et al. 2009). Returns (wavelength, pdf).
+pub fn sample_wavelength(u: f64) -> (f64, f64) {
+    let lambda = 538.0 - 138.888889*(0.85691062 - 1.82750197*u).atanh();
+    let lambda = lambda.clamp(LAMBDA_MIN, LAMBDA_MAX);
+    (lambda, wavelength_pdf(lambda))
+}
+
+/// Density of sample_wavelength returning lambda
+pub fn wavelength_pdf(lambda: f64) -> f64 {
+    if !(LAMBDA_MIN..=LAMBDA_MAX).contains(&lambda) {
+        return 0.0;
+    }
+    0.0039398042 / (0.0072*(lambda - 538.0)).cosh().powi(2)
+}
+
+/// Piecewise Gaussian used by the CIE fits
+fn gaussian(x: f64, mu: f64, sigma_below: f64, sigma_above: f64) -> f64 {
+    let t = (x - mu) / if x < mu {sigma_below} else {sigma_above};
+    (-0.5*t*t).exp()
+}
+
+/// CIE 1931 2° color matching functions at lambda, as the multi-lobe fit
+/// of Wyman, Sloan and Shirley 2013
+pub fn cie_xyz(lambda: f64) -> Color {
+    let x = 1.056*gaussian(lambda, 599.8, 37.9, 31.0)
+        + 0.362*gaussian(lambda, 442.0, 16.0, 26.7)
+        - 0.065*gaussian(lambda, 501.1, 20.4, 26.2);
+    let y = 0.821*gaussian(lambda, 568.8, 46.9, 40.5)
+        + 0.286*gaussian(lambda, 530.9, 16.3, 31.1);
+    let z = 1.217*gaussian(lambda, 437.0, 11.8, 36.0)
+        + 0.681*gaussian(lambda, 459.0, 26.0, 13.8);
+    Color::new(x, y, z)
+}
+
+/// Integral of the y color matching function, and the linear sRGB color of
+/// a constant spectrum of 1, which is the white the output is balanced to
+fn normalization() -> &'static (f64, Color) {
+    static NORMALIZATION: OnceLock<(f64, Color)> = OnceLock::new();
+    NORMALIZATION.get_or_init(|| {
+        let steps = 4700;
+        let d_lambda = (LAMBDA_MAX - LAMBDA_MIN) / steps as f64;
+        let mut xyz = Color::new(0.0, 0.0, 0.0);
+        for i in 0..steps {
+            xyz += cie_xyz(LAMBDA_MIN + (i as f64 + 0.5)*d_lambda)*d_lambda;
+        }
+        let y_integral = xyz.g();
+        (y_integral, ColorSpace::Srgb.from_xyz(&(xyz / y_integral)))
+    })
+}
+
+/// Color of a single spectral sample of radiance at lambda, drawn with
+/// density pdf
+///
+/// The average over many samples converges to the color of the spectrum,
+/// white balanced so a constant spectrum comes out as (1, 1, 1).
+pub fn to_rgb(radiance: f64, lambda: f64, pdf: f64) -> Color {
+    if pdf <= 0.0 {
+        return Color::new(0.0, 0.0, 0.0);
+    }
+    let (y_integral, white) = normalization();
+    let rgb = ColorSpace::Srgb.from_xyz(&(cie_xyz(lambda)*(radiance / (pdf*y_integral))));
+    rgb / white
+}
+
+// Smits 1999 basis spectra, in 10 bins evenly spread over 380-720 nm
+const SMITS_WHITE: [f64; 10] = [1.0000, 1.0000, 0.9999, 0.9993, 0.9992, 0.9998, 1.0000, 1.0000, 1.0000, 1.0000];
+const SMITS_CYAN: [f64; 10] = [0.9710, 0.9426, 1.0007, 1.0007, 1.0007, 1.0007, 0.1564, 0.0000, 0.0000, 0.0000];
+const SMITS_MAGENTA: [f64; 10] = [1.0000, 1.0000, 0.9685, 0.2229, 0.0000, 0.0458, 0.8369, 1.0000, 1.0000, 0.9959];
+const SMITS_YELLOW: [f64; 10] = [0.0001, 0.0000, 0.1088, 0.6651, 1.0000, 1.0000, 0.9996, 0.9586, 0.9685, 0.9840];
+const SMITS_RED: [f64; 10] = [0.1012, 0.0515, 0.0000, 0.0000, 0.0000, 0.0000, 0.8325, 1.0149, 1.0149, 1.0149];
+const SMITS_GREEN: [f64; 10] = [0.0000, 0.0000, 0.0273, 0.7937, 1.0000, 0.9418, 0.1719, 0.0000, 0.0000, 0.0025];
+const SMITS_BLUE: [f64; 10] = [1.0000, 1.0000, 0.8916, 0.3323, 0.0000, 0.0000, 0.0003, 0.0369, 0.0483, 0.0496];
+
+/// Value at lambda of a smooth spectrum whose color is c (Smits 1999)
+///
+/// Used for reflectances and emission alike, so existing RGB scenes render
+/// in spectral mode unchanged apart from noise.
+pub fn upsample(c: &Color, lambda: f64) -> f64 {
+    let bin = (((lambda - 380.0) / 34.0).floor().max(0.0) as usize).min(9);
+    let (r, g, b) = (c.r(), c.g(), c.b());
+
+    // The smallest component is white, the rest is built from the
+    // secondary then primary colors in between the other two
+    if r <= g && r <= b {
+        r*SMITS_WHITE[bin] + if g <= b {
+            (g - r)*SMITS_CYAN[bin] + (b - g)*SMITS_BLUE[bin]
+        } else {
+            (b - r)*SMITS_CYAN[bin] + (g - b)*SMITS_GREEN[bin]
+        }
+    } else if g <= r && g <= b {
+        g*SMITS_WHITE[bin] + if r <= b {
+            (r - g)*SMITS_MAGENTA[bin] + (b - r)*SMITS_BLUE[bin]
+        } else {
+            (b - g)*SMITS_MAGENTA[bin] + (r - b)*SMITS_RED[bin]
+        }
+    } else {
+        b*SMITS_WHITE[bin] + if r <= g {
+            (r - b)*SMITS_YELLOW[bin] + (g - r)*SMITS_GREEN[bin]
+        } else {
+            (g - b)*SMITS_YELLOW[bin] + (r - g)*SMITS_RED[bin]
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use approx::assert_relative_eq;
+    use super::*;
+
+    /// Color of the spectrum of c, integrated with the midpoint rule
+    fn round_trip(c: &Color) -> Color {
+        let steps = 4700;
+        let d_lambda = (LAMBDA_MAX - LAMBDA_MIN) / steps as f64;
+        let pdf = 1.0 / (LAMBDA_MAX - LAMBDA_MIN);
+        let mut rgb = Color::new(0.0, 0.0, 0.0);
+        for i in 0..steps {
+            let lambda = LAMBDA_MIN + (i as f64 + 0.5)*d_lambda;
+            rgb += to_rgb(upsample(c, lambda), lambda, pdf)*(d_lambda*pdf);
+        }
+        rgb
+    }
+
+    #[test]
+    fn test_white_round_trips() {
+        let rgb = round_trip(&Color::new(0.5, 0.5, 0.5));
+        assert_relative_eq!(rgb.r(), 0.5, epsilon = 5e-3);
+        assert_relative_eq!(rgb.g(), 0.5, epsilon = 5e-3);
+        assert_relative_eq!(rgb.b(), 0.5, epsilon = 5e-3);
+    }
+
+    #[test]
+    fn test_primaries_keep_their_hue() {
+        for (i, c) in [Color::new(1.0, 0.0, 0.0), Color::new(0.0, 1.0, 0.0), Color::new(0.0, 0.0, 1.0)].iter().enumerate() {
+            let rgb = round_trip(c);
+            for j in 0..3 {
+                if j != i {
+                    assert!(rgb[i] > 2.0*rgb[j], "{:?} came back as {:?}", c, rgb);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_wavelength_pdf() {
+        // Sampling inverts the CDF of the pdf
+        let (lambda, pdf) = sample_wavelength(0.5);
+        assert_relative_eq!(pdf, wavelength_pdf(lambda));
+        let steps = 47000;
+        let d_lambda = (LAMBDA_MAX - LAMBDA_MIN) / steps as f64;
+        let cdf: f64 = (0..steps)
+            .map(|i| LAMBDA_MIN + (i as f64 + 0.5)*d_lambda)
+            .take_while(|l| *l < lambda)
+            .map(|l| wavelength_pdf(l)*d_lambda)
+            .sum();
+        assert_relative_eq!(cdf, 0.5, epsilon = 1e-3);
+    }
+}
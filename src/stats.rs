@@ -0,0 +1,191 @@
+use std::cell::RefCell;
+use std::fmt;
+use std::time::{Duration, Instant};
+
+/// Events counted while rendering
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Counter {
+    CameraRays,
+    ScatterRays,
+    IntersectionTests,  // Ray-primitive intersection tests
+}
+
+impl Counter {
+    pub const ALL: [Counter; 3] = [
+        Counter::CameraRays,
+        Counter::ScatterRays,
+        Counter::IntersectionTests,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Counter::CameraRays => "camera_rays",
+            Counter::ScatterRays => "scatter_rays",
+            Counter::IntersectionTests => "intersection_tests",
+        }
+    }
+}
+
+/// Timed phases of a run of the renderer
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Phase {
+    SceneBuild,
+    Render,
+    Denoise,
+    PostProcess,
+    Write,
+}
+
+impl Phase {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Phase::SceneBuild => "scene_build",
+            Phase::Render => "render",
+            Phase::Denoise => "denoise",
+            Phase::PostProcess => "post_process",
+            Phase::Write => "write",
+        }
+    }
+}
+
+/// Counters and phase timings collected on the current thread
+#[derive(Debug, Clone, Default)]
+pub struct Stats {
+    counters: [u64; Counter::ALL.len()],
+    phases: Vec<(Phase, Duration)>,
+}
+
+thread_local! {
+    static STATS: RefCell<Stats> = RefCell::new(Stats::default());
+}
+
+/// Increments counter by one
+pub fn count(counter: Counter) {
+    add(counter, 1);
+}
+
+/// Increments counter by n
+pub fn add(counter: Counter, n: u64) {
+    STATS.with(|stats| stats.borrow_mut().counters[counter as usize] += n);
+}
+
+/// Runs f and adds the time it took to phase
+pub fn time<T, F: FnOnce() -> T>(phase: Phase, f: F) -> T {
+    let start = Instant::now();
+    let result = f();
+    let elapsed = start.elapsed();
+
+    STATS.with(|stats| {
+        let mut stats = stats.borrow_mut();
+        match stats.phases.iter_mut().find(|(p, _)| *p == phase) {
+            Some((_, duration)) => *duration += elapsed,
+            None => stats.phases.push((phase, elapsed)),
+        }
+    });
+    result
+}
+
+/// Returns the stats collected so far on the current thread
+pub fn snapshot() -> Stats {
+    STATS.with(|stats| stats.borrow().clone())
+}
+
+/// Clears the stats of the current thread
+pub fn reset() {
+    STATS.with(|stats| *stats.borrow_mut() = Stats::default());
+}
+
+impl Stats {
+    pub fn get(&self, counter: Counter) -> u64 {
+        self.counters[counter as usize]
+    }
+
+    /// Time spent in phase, None if it never ran
+    pub fn phase(&self, phase: Phase) -> Option<Duration> {
+        self.phases.iter().find(|(p, _)| *p == phase).map(|(_, d)| *d)
+    }
+
+    pub fn total_rays(&self) -> u64 {
+        self.get(Counter::CameraRays) + self.get(Counter::ScatterRays)
+    }
+
+    /// Mean number of segments in a camera path (the camera ray plus every scattered ray)
+    pub fn average_path_length(&self) -> f64 {
+        let camera_rays = self.get(Counter::CameraRays);
+        if camera_rays == 0 {
+            0.0
+        } else {
+            (camera_rays + self.get(Counter::ScatterRays)) as f64 / camera_rays as f64
+        }
+    }
+
+    /// Rays traced per second of the render phase
+    pub fn rays_per_second(&self) -> f64 {
+        match self.phase(Phase::Render) {
+            Some(render_time) if render_time.as_secs_f64() > 0.0 => self.total_rays() as f64 / render_time.as_secs_f64(),
+            _ => 0.0,
+        }
+    }
+
+    /// Stats as a JSON object, for tracking performance regressions
+    pub fn to_json(&self) -> String {
+        let mut fields: Vec<String> = Counter::ALL.iter()
+            .map(|c| format!("\"{}\": {}", c.name(), self.get(*c)))
+            .collect();
+        fields.push(format!("\"total_rays\": {}", self.total_rays()));
+        fields.push(format!("\"average_path_length\": {}", self.average_path_length()));
+        fields.push(format!("\"rays_per_second\": {}", self.rays_per_second()));
+
+        let phases: Vec<String> = self.phases.iter()
+            .map(|(p, d)| format!("\"{}\": {}", p.name(), d.as_secs_f64()))
+            .collect();
+        fields.push(format!("\"phase_seconds\": {{{}}}", phases.join(", ")));
+
+        format!("{{{}}}", fields.join(", "))
+    }
+}
+
+impl fmt::Display for Stats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Render statistics")?;
+        writeln!(f, "  Rays")?;
+        writeln!(f, "    camera               {:>16}", self.get(Counter::CameraRays))?;
+        writeln!(f, "    scatter              {:>16}", self.get(Counter::ScatterRays))?;
+        writeln!(f, "    total                {:>16}", self.total_rays())?;
+        writeln!(f, "  Intersection tests     {:>16}", self.get(Counter::IntersectionTests))?;
+        writeln!(f, "  Average path length    {:>16.3}", self.average_path_length())?;
+        writeln!(f, "  Rays/sec               {:>16.0}", self.rays_per_second())?;
+        writeln!(f, "  Time")?;
+        for (phase, duration) in self.phases.iter() {
+            writeln!(f, "    {:<20} {:>15.3}s", phase.name(), duration.as_secs_f64())?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_snapshot_and_json() {
+        reset();
+        count(Counter::CameraRays);
+        count(Counter::CameraRays);
+        add(Counter::ScatterRays, 3);
+        time(Phase::Render, || ());
+        let stats = snapshot();
+        assert_eq!(stats.get(Counter::CameraRays), 2);
+        assert_eq!(stats.total_rays(), 5);
+        assert_eq!(stats.average_path_length(), 2.5);
+        assert!(stats.phase(Phase::Render).is_some());
+        assert!(stats.phase(Phase::Denoise).is_none());
+
+        let json = stats.to_json();
+        assert!(json.starts_with("{\"camera_rays\": 2, \"scatter_rays\": 3, \"intersection_tests\": 0, \"total_rays\": 5, "));
+        assert!(json.contains("\"phase_seconds\": {\"render\": "));
+
+        reset();
+        assert_eq!(snapshot().total_rays(), 0);
+    }
+}
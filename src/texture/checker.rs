@@ -0,0 +1,33 @@
+use std::rc::Rc;
+
+use crate::color::Color;
+use crate::texture::Texture;
+use crate::vec3::Point3;
+
+/// 3D checkerboard alternating between two textures
+pub struct Checker {
+    even: Rc<dyn Texture>,
+    odd: Rc<dyn Texture>,
+    scale: f64,  // Size of a checker cell
+}
+
+impl Checker {
+    pub fn new(even: &Rc<dyn Texture>, odd: &Rc<dyn Texture>, scale: f64) -> Self {
+        Checker {
+            even: Rc::clone(even),
+            odd: Rc::clone(odd),
+            scale,
+        }
+    }
+}
+
+impl Texture for Checker {
+    fn value(&self, u: f64, v: f64, p: &Point3) -> Color {
+        let cell = |x: f64| (x / self.scale).floor() as i64;
+        if (cell(p.x()) + cell(p.y()) + cell(p.z())).rem_euclid(2) == 0 {
+            self.even.value(u, v, p)
+        } else {
+            self.odd.value(u, v, p)
+        }
+    }
+}
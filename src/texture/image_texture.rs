@@ -0,0 +1,84 @@
+use std::path::Path;
+
+use image::{ImageResult, Rgb, RgbImage};
+
+use crate::clamp;
+use crate::color::{Color, Encoding};
+use crate::texture::Texture;
+use crate::vec3::Point3;
+
+/// Texture read from an image file, looked up with bilinear filtering
+pub struct ImageTexture {
+    img: RgbImage,
+    encoding: Encoding,  // How texels are stored, LINEAR for plain data
+}
+
+impl ImageTexture {
+    /// Loads an 8-bit sRGB image, like the renderer writes by default
+    pub fn open<P: AsRef<Path>>(path: P) -> ImageResult<Self> {
+        Self::open_with(path, Encoding::SRGB)
+    }
+
+    /// Loads an 8-bit image whose colors are stored with encoding
+    pub fn open_with<P: AsRef<Path>>(path: P, encoding: Encoding) -> ImageResult<Self> {
+        Ok(Self::from_image_with(image::open(path)?.to_rgb8(), encoding))
+    }
+
+    /// Loads an 8-bit image of data rather than colors, like a normal or
+    /// height map, mapping texels to [0, 1] as they are
+    pub fn open_data<P: AsRef<Path>>(path: P) -> ImageResult<Self> {
+        Ok(Self::from_data(image::open(path)?.to_rgb8()))
+    }
+
+    /// Loads the alpha channel of an image as a grayscale data texture,
+    /// for cutout masks. Images without alpha are fully opaque.
+    pub fn open_alpha<P: AsRef<Path>>(path: P) -> ImageResult<Self> {
+        let rgba = image::open(path)?.to_rgba8();
+        let alpha = RgbImage::from_fn(rgba.width(), rgba.height(), |x, y| {
+            let a = rgba.get_pixel(x, y).0[3];
+            Rgb([a, a, a])
+        });
+        Ok(Self::from_data(alpha))
+    }
+
+    pub fn from_image(img: RgbImage) -> Self {
+        Self::from_image_with(img, Encoding::SRGB)
+    }
+
+    pub fn from_image_with(img: RgbImage, encoding: Encoding) -> Self {
+        ImageTexture {img, encoding}
+    }
+
+    pub fn from_data(img: RgbImage) -> Self {
+        Self::from_image_with(img, Encoding::LINEAR)
+    }
+
+    fn texel(&self, x: u32, y: u32) -> Color {
+        let [r, g, b] = self.img.get_pixel(x, y).0;
+        let stored = Color::new(r as f64, g as f64, b as f64) / 255.0;
+        self.encoding.decode(&stored)
+    }
+}
+
+impl Texture for ImageTexture {
+    fn value(&self, u: f64, v: f64, _p: &Point3) -> Color {
+        let (width, height) = self.img.dimensions();
+        if width == 0 || height == 0 {
+            return Color::new(0.0, 1.0, 1.0);  // Debugging aid for missing images
+        }
+
+        // v = 0 is the bottom of the image, which the image crate stores last
+        let x = clamp(u, 0.0, 1.0)*(width as f64) - 0.5;
+        let y = (1.0 - clamp(v, 0.0, 1.0))*(height as f64) - 0.5;
+
+        let x0 = x.floor();
+        let y0 = y.floor();
+        let (tx, ty) = (x - x0, y - y0);
+        let px = |x: f64| clamp(x, 0.0, (width - 1) as f64) as u32;
+        let py = |y: f64| clamp(y, 0.0, (height - 1) as f64) as u32;
+
+        let top = (1.0 - tx)*self.texel(px(x0), py(y0)) + tx*self.texel(px(x0 + 1.0), py(y0));
+        let bottom = (1.0 - tx)*self.texel(px(x0), py(y0 + 1.0)) + tx*self.texel(px(x0 + 1.0), py(y0 + 1.0));
+        (1.0 - ty)*top + ty*bottom
+    }
+}
@@ -0,0 +1,53 @@
+pub mod checker;
+pub mod image_texture;
+pub mod solid_color;
+
+// Re-export structs that implement Texture
+pub use checker::Checker;
+pub use image_texture::ImageTexture;
+pub use solid_color::SolidColor;
+
+use std::rc::Rc;
+
+use crate::color::Color;
+use crate::vec3::Point3;
+
+pub trait Texture {
+    /// Color of the texture at surface coordinates (u, v) and hit point p
+    fn value(&self, u: f64, v: f64, p: &Point3) -> Color;
+
+    /// Value of a single-channel texture, taken from its first channel
+    fn scalar(&self, u: f64, v: f64, p: &Point3) -> f64 {
+        self.value(u, v, p).r()
+    }
+}
+
+/// Anything that can be used where a material parameter expects a texture:
+/// constants become SolidColor textures
+pub trait IntoTexture {
+    fn into_texture(self) -> Rc<dyn Texture>;
+}
+
+impl IntoTexture for Rc<dyn Texture> {
+    fn into_texture(self) -> Rc<dyn Texture> {
+        self
+    }
+}
+
+impl IntoTexture for &Rc<dyn Texture> {
+    fn into_texture(self) -> Rc<dyn Texture> {
+        Rc::clone(self)
+    }
+}
+
+impl IntoTexture for Color {
+    fn into_texture(self) -> Rc<dyn Texture> {
+        Rc::new(SolidColor::new(self))
+    }
+}
+
+impl IntoTexture for f64 {
+    fn into_texture(self) -> Rc<dyn Texture> {
+        Rc::new(SolidColor::new(Color::new(self, self, self)))
+    }
+}
@@ -411,6 +411,7 @@ mod tests {
         }
 
         #[test]
+        #[allow(clippy::op_ref)]  // The reference combinations are what is being tested
         fn test_op_macro_combinations() {
             // We assume that if the macros correctly generate
             // impls for all combinations of Vec3, &Vec, and f64, for